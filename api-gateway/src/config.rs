@@ -0,0 +1,306 @@
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use structopt::StructOpt;
+use tonic::transport::Endpoint;
+
+/// File read when `--config` isn't given, if it exists
+const DEFAULT_CONFIG_FILE: &str = "gateway.toml";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "microbiome_server")]
+/// Settings given on the command line, which take precedence over the environment and the file
+struct Opt {
+    /// TOML file to read settings from
+    #[structopt(long = "config", parse(from_os_str))]
+    config: Option<PathBuf>,
+    #[structopt(short = "s", long = "socket")]
+    socket: Option<String>,
+    #[structopt(long = "users-service-uri")]
+    users_service_uri: Option<String>,
+    #[structopt(long = "courses-service-uri")]
+    courses_service_uri: Option<String>,
+    #[structopt(long = "static-root", parse(from_os_str))]
+    static_root: Option<PathBuf>,
+    /// Origin allowed to call the gateway from a browser, may be repeated
+    #[structopt(long = "cors-origin")]
+    cors_origins: Vec<String>,
+    #[structopt(long = "graphiql")]
+    graphiql: Option<bool>,
+    #[structopt(long = "introspection")]
+    introspection: Option<bool>,
+    /// Deepest nesting of fields allowed in an operation
+    #[structopt(long = "max-depth")]
+    max_depth: Option<usize>,
+    /// Highest cost allowed for an operation
+    #[structopt(long = "max-complexity")]
+    max_complexity: Option<u64>,
+    /// Seconds before a call to a service is abandoned
+    #[structopt(long = "request-timeout")]
+    request_timeout: Option<u64>,
+    /// `text` or `json`
+    #[structopt(long = "log-format")]
+    log_format: Option<LogFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    // One JSON object per line, for log collectors
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("expected text or json, got \"{}\"", other)),
+        }
+    }
+}
+
+/// Settings of the gateway, read from defaults, then a TOML file, then `GATEWAY_*` environment
+/// variables and finally the command line, each overriding the last
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub socket: String,
+    pub users_service_uri: String,
+    pub courses_service_uri: String,
+    // Directory that the frontend bundle and public media are served from
+    pub static_root: PathBuf,
+    // Origins allowed to call the gateway from a browser, or `*` for any
+    pub cors_origins: Vec<String>,
+    pub graphiql: bool,
+    pub introspection: bool,
+    // Deepest nesting of fields and highest cost allowed for an operation
+    pub max_depth: usize,
+    pub max_complexity: u64,
+    // Seconds before a call to a service is abandoned
+    pub request_timeout: u64,
+    pub log_format: LogFormat,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            socket: "0.0.0.0:8000".to_owned(),
+            users_service_uri: String::new(),
+            courses_service_uri: String::new(),
+            static_root: PathBuf::from("./static"),
+            cors_origins: vec![],
+            graphiql: false,
+            introspection: false,
+            // Leaves room for every query the frontend makes
+            max_depth: 12,
+            max_complexity: 5000,
+            request_timeout: 30,
+            log_format: LogFormat::Text,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, message: String },
+    Invalid { setting: String, message: String },
+    Missing(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Read {
+                ref path,
+                ref source,
+            } => write!(f, "Failed to read {}: {}", path.display(), source),
+            ConfigError::Parse {
+                ref path,
+                ref message,
+            } => write!(f, "Invalid config file {}: {}", path.display(), message),
+            ConfigError::Invalid {
+                ref setting,
+                ref message,
+            } => write!(f, "Invalid {}: {}", setting, message),
+            ConfigError::Missing(ref setting) => write!(f, "{} must be set", setting),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Reads every layer of settings for this process and checks the result
+    pub fn load() -> Result<Self, ConfigError> {
+        let opt = Opt::from_args();
+
+        let mut config = match opt.config {
+            Some(ref path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        config.apply_opt(opt);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|err| ConfigError::Parse {
+            path: path.to_owned(),
+            message: err.to_string(),
+        })
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        env_override("GATEWAY_SOCKET", &mut self.socket)?;
+        // Named like this before the gateway had a config file
+        env_override("USERS_SERVICE_URI", &mut self.users_service_uri)?;
+        env_override("COURSES_SERVICE_URI", &mut self.courses_service_uri)?;
+        env_override("GATEWAY_STATIC_ROOT", &mut self.static_root)?;
+        env_override("GATEWAY_GRAPHIQL", &mut self.graphiql)?;
+        env_override("GATEWAY_INTROSPECTION", &mut self.introspection)?;
+        // Also predate the config file, like the service URIs
+        env_override("GRAPHQL_MAX_DEPTH", &mut self.max_depth)?;
+        env_override("GRAPHQL_MAX_COMPLEXITY", &mut self.max_complexity)?;
+        env_override("GATEWAY_REQUEST_TIMEOUT", &mut self.request_timeout)?;
+        env_override("GATEWAY_LOG_FORMAT", &mut self.log_format)?;
+        if let Ok(origins) = env::var("GATEWAY_CORS_ORIGINS") {
+            self.cors_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    fn apply_opt(&mut self, opt: Opt) {
+        if let Some(socket) = opt.socket {
+            self.socket = socket;
+        }
+        if let Some(uri) = opt.users_service_uri {
+            self.users_service_uri = uri;
+        }
+        if let Some(uri) = opt.courses_service_uri {
+            self.courses_service_uri = uri;
+        }
+        if let Some(static_root) = opt.static_root {
+            self.static_root = static_root;
+        }
+        if !opt.cors_origins.is_empty() {
+            self.cors_origins = opt.cors_origins;
+        }
+        if let Some(graphiql) = opt.graphiql {
+            self.graphiql = graphiql;
+        }
+        if let Some(introspection) = opt.introspection {
+            self.introspection = introspection;
+        }
+        if let Some(max_depth) = opt.max_depth {
+            self.max_depth = max_depth;
+        }
+        if let Some(max_complexity) = opt.max_complexity {
+            self.max_complexity = max_complexity;
+        }
+        if let Some(request_timeout) = opt.request_timeout {
+            self.request_timeout = request_timeout;
+        }
+        if let Some(log_format) = opt.log_format {
+            self.log_format = log_format;
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.users_endpoint()?;
+        self.courses_endpoint()?;
+        if self.request_timeout == 0 {
+            return Err(ConfigError::Invalid {
+                setting: "request_timeout".to_owned(),
+                message: "must be at least one second".to_owned(),
+            });
+        }
+        if self.max_depth == 0 || self.max_complexity == 0 {
+            return Err(ConfigError::Invalid {
+                setting: "max_depth and max_complexity".to_owned(),
+                message: "must be at least one".to_owned(),
+            });
+        }
+        if let Some(origin) = self.cors_origins.iter().find(|origin| !is_origin(origin)) {
+            return Err(ConfigError::Invalid {
+                setting: "cors_origins".to_owned(),
+                message: format!("\"{}\" is not an origin like https://example.com", origin),
+            });
+        }
+        if !self.static_root.is_dir() {
+            return Err(ConfigError::Invalid {
+                setting: "static_root".to_owned(),
+                message: format!("{} is not a directory", self.static_root.display()),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn users_endpoint(&self) -> Result<Endpoint, ConfigError> {
+        self.endpoint("users_service_uri", &self.users_service_uri)
+    }
+
+    pub fn courses_endpoint(&self) -> Result<Endpoint, ConfigError> {
+        self.endpoint("courses_service_uri", &self.courses_service_uri)
+    }
+
+    fn endpoint(&self, setting: &str, uri: &str) -> Result<Endpoint, ConfigError> {
+        if uri.is_empty() {
+            return Err(ConfigError::Missing(setting.to_owned()));
+        }
+        Endpoint::from_shared(uri.to_owned())
+            .map(|endpoint| endpoint.timeout(Duration::from_secs(self.request_timeout)))
+            .map_err(|err| ConfigError::Invalid {
+                setting: setting.to_owned(),
+                message: format!("\"{}\" is not a URI: {}", uri, err),
+            })
+    }
+}
+
+/// Browsers send origins as a scheme and host, without a path or trailing slash
+fn is_origin(origin: &str) -> bool {
+    origin == "*"
+        || (origin.starts_with("http://") || origin.starts_with("https://"))
+            && !origin.ends_with('/')
+}
+
+/// Replaces a setting with the value of an environment variable, if it's set
+fn env_override<T>(name: &str, setting: &mut T) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Ok(value) = env::var(name) {
+        *setting = value.parse().map_err(|err: T::Err| ConfigError::Invalid {
+            setting: name.to_owned(),
+            message: err.to_string(),
+        })?;
+    }
+
+    Ok(())
+}
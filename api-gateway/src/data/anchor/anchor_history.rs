@@ -0,0 +1,15 @@
+use schema::courses::{
+    courses_client::CoursesClient, GetAnchorHistoryRequest, GetAnchorHistoryResponse,
+};
+
+use crate::errors::GatewayError;
+
+pub async fn anchor_history(
+    anchor_id: i32,
+    channel: tonic::transport::Channel,
+) -> Result<GetAnchorHistoryResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let request = tonic::Request::new(GetAnchorHistoryRequest { anchor_id });
+    let response = client.get_anchor_history(request).await?.into_inner();
+    Ok(response)
+}
@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetAnchorsByIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::Anchor, errors::GatewayError};
+use crate::{data::batch_results, entities::Anchor, errors::GatewayError};
 
 async fn get_anchor_by_id(
     map: &mut HashMap<i32, Anchor>,
@@ -33,17 +33,17 @@ impl AnchorBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, Anchor> for AnchorBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Anchor> {
+impl BatchFn<i32, Result<Anchor, GatewayError>> for AnchorBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Anchor, GatewayError>> {
         let client = CoursesClient::new(self.channel.clone());
 
         let mut anchor_map = HashMap::new();
-        let _ = get_anchor_by_id(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let loaded = get_anchor_by_id(&mut anchor_map, keys.to_vec(), client).await;
+        batch_results("anchor", keys, anchor_map, loaded)
     }
 }
 
-pub type AnchorLoader = Loader<i32, Anchor, AnchorBatcher>;
+pub type AnchorLoader = Loader<i32, Result<Anchor, GatewayError>, AnchorBatcher>;
 
 pub fn get_loader(channel: Channel) -> AnchorLoader {
     Loader::new(AnchorBatcher::new(channel))
@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetAnchorsByPageIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::Anchor, errors::GatewayError};
+use crate::{data::batch_results, entities::Anchor, errors::GatewayError};
 
 async fn get_anchors_by_page_id(
     map: &mut HashMap<i32, Vec<Anchor>>,
@@ -36,20 +36,20 @@ impl PageAnchorBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, Vec<Anchor>> for PageAnchorBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Vec<Anchor>> {
+impl BatchFn<i32, Result<Vec<Anchor>, GatewayError>> for PageAnchorBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Vec<Anchor>, GatewayError>> {
         let client = CoursesClient::new(self.channel.clone());
 
         let mut page_anchor_map = HashMap::new();
         keys.iter().for_each(|&k| {
             page_anchor_map.insert(k, vec![]);
         });
-        let _ = get_anchors_by_page_id(&mut page_anchor_map, keys.to_vec(), client).await;
-        page_anchor_map
+        let loaded = get_anchors_by_page_id(&mut page_anchor_map, keys.to_vec(), client).await;
+        batch_results("anchor", keys, page_anchor_map, loaded)
     }
 }
 
-pub type PageAnchorLoader = Loader<i32, Vec<Anchor>, PageAnchorBatcher>;
+pub type PageAnchorLoader = Loader<i32, Result<Vec<Anchor>, GatewayError>, PageAnchorBatcher>;
 
 pub fn get_page_loader(channel: Channel) -> PageAnchorLoader {
     Loader::new(PageAnchorBatcher::new(channel))
@@ -1,18 +1,14 @@
-use schema::{
-    courses::{courses_client::CoursesClient, CreateAnchorRequest, CreateAnchorResponse},
-    shared::User,
-};
+use schema::courses::{courses_client::CoursesClient, CreateAnchorRequest, CreateAnchorResponse};
 
-use crate::{entities::CreateAnchor, errors::GatewayError};
+use crate::{data::forward_token, entities::CreateAnchor, errors::GatewayError};
 
 pub async fn create_anchor(
-    user: Option<User>,
+    token: Option<String>,
     data: CreateAnchor,
     channel: tonic::transport::Channel,
 ) -> Result<CreateAnchorResponse, GatewayError> {
     let mut client = CoursesClient::new(channel);
-    let request = tonic::Request::new(CreateAnchorRequest {
-        active_user: user,
+    let mut request = tonic::Request::new(CreateAnchorRequest {
         title: data.title,
         track_time: data.track_time as f32,
         position_top: data.position_top as f32,
@@ -20,6 +16,7 @@ pub async fn create_anchor(
         page_id: data.page_id,
         track_id: data.track_id,
     });
+    forward_token(&mut request, token);
     let response = client.create_anchor(request).await?.into_inner();
     Ok(response)
 }
@@ -1,20 +1,15 @@
-use schema::{
-    courses::{courses_client::CoursesClient, DeleteAnchorRequest, DeleteAnchorResponse},
-    shared::User,
-};
+use schema::courses::{courses_client::CoursesClient, DeleteAnchorRequest, DeleteAnchorResponse};
 
-use crate::errors::GatewayError;
+use crate::{data::forward_token, errors::GatewayError};
 
 pub async fn delete_anchor(
-    user: Option<User>,
+    token: Option<String>,
     anchor_id: i32,
     channel: tonic::transport::Channel,
 ) -> Result<DeleteAnchorResponse, GatewayError> {
     let mut client = CoursesClient::new(channel);
-    let request = tonic::Request::new(DeleteAnchorRequest {
-        active_user: user,
-        id: anchor_id,
-    });
+    let mut request = tonic::Request::new(DeleteAnchorRequest { id: anchor_id });
+    forward_token(&mut request, token);
     let response = client.delete_anchor(request).await?.into_inner();
     Ok(response)
 }
@@ -0,0 +1,20 @@
+use schema::courses::{
+    courses_client::CoursesClient, AnchorFileFormat, ExportDocumentAnchorsRequest,
+    ExportDocumentAnchorsResponse,
+};
+
+use crate::errors::GatewayError;
+
+pub async fn export_document_anchors(
+    document_id: i32,
+    format: AnchorFileFormat,
+    channel: tonic::transport::Channel,
+) -> Result<ExportDocumentAnchorsResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let request = tonic::Request::new(ExportDocumentAnchorsRequest {
+        document_id,
+        format: format as i32,
+    });
+    let response = client.export_document_anchors(request).await?.into_inner();
+    Ok(response)
+}
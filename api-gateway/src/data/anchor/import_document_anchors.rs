@@ -0,0 +1,26 @@
+use schema::courses::{
+    courses_client::CoursesClient, AnchorFile, AnchorFileFormat, ImportDocumentAnchorsRequest,
+    ImportDocumentAnchorsResponse,
+};
+
+use crate::{data::forward_token, errors::GatewayError};
+
+pub async fn import_document_anchors(
+    token: Option<String>,
+    document_id: i32,
+    format: AnchorFileFormat,
+    files: Vec<AnchorFile>,
+    replace: bool,
+    channel: tonic::transport::Channel,
+) -> Result<ImportDocumentAnchorsResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(ImportDocumentAnchorsRequest {
+        document_id,
+        format: format as i32,
+        files,
+        replace,
+    });
+    forward_token(&mut request, token);
+    let response = client.import_document_anchors(request).await?.into_inner();
+    Ok(response)
+}
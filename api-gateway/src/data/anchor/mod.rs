@@ -1,13 +1,27 @@
+use std::convert::TryFrom;
+
+use futures::{Stream, StreamExt};
+
 use crate::{
-    entities::{Anchor, CreateAnchor, DeleteAnchorResponse},
+    data::{found, Resettable},
+    entities::{
+        Anchor, AnchorChange, AnchorEvent, AnchorFile, AnchorFileFormat, CreateAnchor,
+        DeleteAnchorResponse, ImportAnchorsResponse, NewAnchorFile, UpdateAnchor, UserAnchor,
+    },
     errors::GatewayError,
 };
-use schema::shared::User;
 
+mod anchor_history;
 mod anchors_by_id;
 mod anchors_by_page_id;
 mod create_anchor;
 mod delete_anchor;
+mod export_document_anchors;
+mod import_document_anchors;
+mod promote_user_anchor;
+mod restore_anchor;
+mod update_anchor;
+mod watch_document_anchors;
 
 use anchors_by_id::{get_loader, AnchorLoader};
 use anchors_by_page_id::{get_page_loader, PageAnchorLoader};
@@ -15,44 +29,194 @@ use anchors_by_page_id::{get_page_loader, PageAnchorLoader};
 #[derive(Clone)]
 pub struct AnchorData {
     channel: tonic::transport::Channel,
-    anchors_by_id: AnchorLoader,
-    anchors_by_page_id: PageAnchorLoader,
+    anchors_by_id: Resettable<AnchorLoader>,
+    anchors_by_page_id: Resettable<PageAnchorLoader>,
 }
 
 impl AnchorData {
     pub fn new(channel: tonic::transport::Channel) -> Self {
         Self {
-            anchors_by_id: get_loader(channel.clone()),
-            anchors_by_page_id: get_page_loader(channel.clone()),
+            anchors_by_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_loader(channel.clone()))
+            },
+            anchors_by_page_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_page_loader(channel.clone()))
+            },
             channel,
         }
     }
 
-    pub async fn anchors_by_id(&self, id: i32) -> Anchor {
-        self.anchors_by_id.load(id).await
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.anchors_by_id.reset();
+        self.anchors_by_page_id.reset();
+    }
+
+    /// Replaces the request's copy of an anchor that a mutation has returned, and drops the
+    /// anchors of the pages it was on and is now on, so that later operations of a batch see
+    /// the change
+    async fn refresh(&self, anchor: &Anchor, previous_page_id: Option<i32>) {
+        self.anchors_by_id
+            .prime(anchor.id, Ok(anchor.clone()))
+            .await;
+        self.forget_pages(previous_page_id.into_iter().chain(Some(anchor.page_id)))
+            .await;
+    }
+
+    /// Drops the request's copy of an anchor that no longer exists, along with the anchors of
+    /// the page it was on
+    async fn forget(&self, anchor_id: i32, page_id: Option<i32>) {
+        self.anchors_by_id.get().clear(anchor_id).await;
+        self.forget_pages(page_id).await;
+    }
+
+    async fn forget_pages(&self, page_ids: impl IntoIterator<Item = i32>) {
+        for page_id in page_ids {
+            self.anchors_by_page_id.get().clear(page_id).await;
+        }
+    }
+
+    pub async fn anchors_by_id(&self, id: i32) -> Result<Option<Anchor>, GatewayError> {
+        found(self.anchors_by_id.get().try_load(id).await)
     }
 
-    pub async fn page_anchors(&self, page_id: i32) -> Vec<Anchor> {
-        self.anchors_by_page_id.load(page_id).await
+    pub async fn page_anchors(&self, page_id: i32) -> Result<Vec<Anchor>, GatewayError> {
+        self.anchors_by_page_id.get().load(page_id).await
     }
 
     pub async fn create_anchor(
         &self,
-        user: Option<User>,
+        token: Option<String>,
         data: CreateAnchor,
     ) -> Result<Anchor, GatewayError> {
-        let response = create_anchor::create_anchor(user, data, self.channel.clone()).await?;
-        Ok(response.anchor.unwrap().into())
+        let response = create_anchor::create_anchor(token, data, self.channel.clone()).await?;
+        let anchor: Anchor = response
+            .anchor
+            .ok_or_else(|| GatewayError::missing_field("anchor"))?
+            .into();
+        self.refresh(&anchor, None).await;
+        Ok(anchor)
+    }
+
+    pub async fn update_anchor(
+        &self,
+        token: Option<String>,
+        data: UpdateAnchor,
+    ) -> Result<Anchor, GatewayError> {
+        // Loaded first to find the page that the anchor may be moved off
+        let previous = self.anchors_by_id(data.id).await?;
+        let response = update_anchor::update_anchor(token, data, self.channel.clone()).await?;
+        let anchor: Anchor = response
+            .anchor
+            .ok_or_else(|| GatewayError::missing_field("anchor"))?
+            .into();
+        self.refresh(&anchor, previous.map(|a| a.page_id)).await;
+        Ok(anchor)
+    }
+
+    pub async fn promote_user_anchor(
+        &self,
+        token: Option<String>,
+        user_anchor_id: i32,
+    ) -> Result<Anchor, GatewayError> {
+        let response =
+            promote_user_anchor::promote_user_anchor(token, user_anchor_id, self.channel.clone())
+                .await?;
+        let anchor: Anchor = response
+            .anchor
+            .ok_or_else(|| GatewayError::missing_field("anchor"))?
+            .into();
+        self.refresh(&anchor, None).await;
+        Ok(anchor)
+    }
+
+    pub async fn export_document_anchors(
+        &self,
+        document_id: i32,
+        format: AnchorFileFormat,
+    ) -> Result<Vec<AnchorFile>, GatewayError> {
+        let response = export_document_anchors::export_document_anchors(
+            document_id,
+            format.into(),
+            self.channel.clone(),
+        )
+        .await?;
+        Ok(response.files.into_iter().map(AnchorFile::from).collect())
+    }
+
+    pub async fn import_document_anchors(
+        &self,
+        token: Option<String>,
+        document_id: i32,
+        format: AnchorFileFormat,
+        files: Vec<NewAnchorFile>,
+        replace: bool,
+    ) -> Result<ImportAnchorsResponse, GatewayError> {
+        let response = import_document_anchors::import_document_anchors(
+            token,
+            document_id,
+            format.into(),
+            files.into_iter().map(|f| f.into()).collect(),
+            replace,
+            self.channel.clone(),
+        )
+        .await?;
+        self.forget_pages(response.page_ids).await;
+        Ok(ImportAnchorsResponse {
+            imported: response.imported,
+        })
     }
 
     pub async fn delete_anchor(
         &self,
-        user: Option<User>,
+        token: Option<String>,
         anchor_id: i32,
     ) -> Result<DeleteAnchorResponse, GatewayError> {
-        let response = delete_anchor::delete_anchor(user, anchor_id, self.channel.clone()).await?;
+        let previous = self.anchors_by_id(anchor_id).await?;
+        let response = delete_anchor::delete_anchor(token, anchor_id, self.channel.clone()).await?;
+        self.forget(anchor_id, previous.map(|a| a.page_id)).await;
         Ok(DeleteAnchorResponse {
             success: response.success,
         })
     }
+
+    pub async fn anchor_history(&self, anchor_id: i32) -> Result<Vec<AnchorEvent>, GatewayError> {
+        let response = anchor_history::anchor_history(anchor_id, self.channel.clone()).await?;
+        Ok(response.events.into_iter().map(AnchorEvent::from).collect())
+    }
+
+    /// Restores the state of an anchor from before an event, returning the anchor if it still
+    /// exists along with the user anchor given back by undoing a promotion
+    pub async fn restore_anchor(
+        &self,
+        token: Option<String>,
+        event_id: i32,
+    ) -> Result<(Option<Anchor>, Option<UserAnchor>), GatewayError> {
+        let response =
+            restore_anchor::restore_anchor(token, event_id, self.channel.clone()).await?;
+        let anchor = response.anchor.map(Anchor::from);
+        let replaced = response.replaced.map(Anchor::from);
+        match (&anchor, replaced) {
+            (Some(anchor), replaced) => self.refresh(anchor, replaced.map(|a| a.page_id)).await,
+            (None, Some(replaced)) => self.forget(replaced.id, Some(replaced.page_id)).await,
+            (None, None) => {}
+        }
+        Ok((anchor, response.user_anchor.map(UserAnchor::from)))
+    }
+
+    pub async fn watch_document_anchors(
+        &self,
+        document_id: i32,
+    ) -> Result<impl Stream<Item = Result<AnchorChange, GatewayError>>, GatewayError> {
+        let changes =
+            watch_document_anchors::watch_document_anchors(document_id, self.channel.clone())
+                .await?;
+        Ok(changes.map(|change| {
+            change
+                .map_err(GatewayError::from)
+                .and_then(AnchorChange::try_from)
+        }))
+    }
 }
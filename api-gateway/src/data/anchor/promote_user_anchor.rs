@@ -0,0 +1,17 @@
+use schema::courses::{
+    courses_client::CoursesClient, PromoteUserAnchorRequest, PromoteUserAnchorResponse,
+};
+
+use crate::{data::forward_token, errors::GatewayError};
+
+pub async fn promote_user_anchor(
+    token: Option<String>,
+    user_anchor_id: i32,
+    channel: tonic::transport::Channel,
+) -> Result<PromoteUserAnchorResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(PromoteUserAnchorRequest { id: user_anchor_id });
+    forward_token(&mut request, token);
+    let response = client.promote_user_anchor(request).await?.into_inner();
+    Ok(response)
+}
@@ -0,0 +1,15 @@
+use schema::courses::{courses_client::CoursesClient, RestoreAnchorRequest, RestoreAnchorResponse};
+
+use crate::{data::forward_token, errors::GatewayError};
+
+pub async fn restore_anchor(
+    token: Option<String>,
+    event_id: i32,
+    channel: tonic::transport::Channel,
+) -> Result<RestoreAnchorResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(RestoreAnchorRequest { event_id });
+    forward_token(&mut request, token);
+    let response = client.restore_anchor(request).await?.into_inner();
+    Ok(response)
+}
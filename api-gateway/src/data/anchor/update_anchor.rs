@@ -0,0 +1,23 @@
+use schema::courses::{courses_client::CoursesClient, UpdateAnchorRequest, UpdateAnchorResponse};
+
+use crate::{data::forward_token, entities::UpdateAnchor, errors::GatewayError};
+
+pub async fn update_anchor(
+    token: Option<String>,
+    data: UpdateAnchor,
+    channel: tonic::transport::Channel,
+) -> Result<UpdateAnchorResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(UpdateAnchorRequest {
+        id: data.id,
+        title: data.title,
+        track_time: data.track_time as f32,
+        position_top: data.position_top as f32,
+        position_left: data.position_left as f32,
+        page_id: data.page_id,
+        track_id: data.track_id,
+    });
+    forward_token(&mut request, token);
+    let response = client.update_anchor(request).await?.into_inner();
+    Ok(response)
+}
@@ -0,0 +1,14 @@
+use schema::courses::{courses_client::CoursesClient, AnchorChange, WatchDocumentAnchorsRequest};
+use tonic::Streaming;
+
+use crate::errors::GatewayError;
+
+pub async fn watch_document_anchors(
+    document_id: i32,
+    channel: tonic::transport::Channel,
+) -> Result<Streaming<AnchorChange>, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let request = tonic::Request::new(WatchDocumentAnchorsRequest { document_id });
+    let response = client.watch_document_anchors(request).await?.into_inner();
+    Ok(response)
+}
@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetBookmarksByIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::Bookmark, errors::GatewayError};
+use crate::{data::batch_results, entities::Bookmark, errors::GatewayError};
 
 async fn get_bookmark_by_id(
     map: &mut HashMap<i32, Bookmark>,
@@ -33,17 +33,17 @@ impl BookmarkBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, Bookmark> for BookmarkBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Bookmark> {
+impl BatchFn<i32, Result<Bookmark, GatewayError>> for BookmarkBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Bookmark, GatewayError>> {
         let client = CoursesClient::new(self.channel.clone());
 
         let mut anchor_map = HashMap::new();
-        let _ = get_bookmark_by_id(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let loaded = get_bookmark_by_id(&mut anchor_map, keys.to_vec(), client).await;
+        batch_results("bookmark", keys, anchor_map, loaded)
     }
 }
 
-pub type BookmarkLoader = Loader<i32, Bookmark, BookmarkBatcher>;
+pub type BookmarkLoader = Loader<i32, Result<Bookmark, GatewayError>, BookmarkBatcher>;
 
 pub fn get_loader(channel: Channel) -> BookmarkLoader {
     Loader::new(BookmarkBatcher::new(channel))
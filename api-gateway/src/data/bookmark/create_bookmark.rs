@@ -1,24 +1,24 @@
-use schema::{
-    courses::{courses_client::CoursesClient, CreateBookmarkRequest},
-    shared::User,
-};
+use schema::courses::{courses_client::CoursesClient, CreateBookmarkRequest};
 
-use crate::{entities::Bookmark, errors::GatewayError};
+use crate::{data::forward_token, entities::Bookmark, errors::GatewayError};
 
 pub async fn create_bookmark(
     channel: tonic::transport::Channel,
     title: String,
     page_id: i32,
     document_id: i32,
-    active_user: Option<User>,
+    token: Option<String>,
 ) -> Result<Bookmark, GatewayError> {
     let mut client = CoursesClient::new(channel);
-    let request = tonic::Request::new(CreateBookmarkRequest {
+    let mut request = tonic::Request::new(CreateBookmarkRequest {
         title,
         page_id,
         document_id,
-        active_user,
     });
+    forward_token(&mut request, token);
     let response = client.create_bookmark(request).await?.into_inner();
-    Ok(response.bookmark.unwrap().into())
+    Ok(response
+        .bookmark
+        .ok_or_else(|| GatewayError::missing_field("bookmark"))?
+        .into())
 }
@@ -7,7 +7,7 @@ pub async fn document_bookmarks(
     document_id: i32,
     limit: i32,
     offset: i32,
-) -> Result<Vec<Bookmark>, GatewayError> {
+) -> Result<(Vec<Bookmark>, i32), GatewayError> {
     let mut client = CoursesClient::new(channel);
     let request = tonic::Request::new(GetDocumentBookmarksRequest {
         document_id,
@@ -15,5 +15,8 @@ pub async fn document_bookmarks(
         offset,
     });
     let result = client.get_document_bookmarks(request).await?.into_inner();
-    Ok(result.bookmarks.into_iter().map(|b| b.into()).collect())
+    Ok((
+        result.bookmarks.into_iter().map(|b| b.into()).collect(),
+        result.total,
+    ))
 }
@@ -1,6 +1,5 @@
-use schema::shared::User;
-
 use crate::{
+    data::{found, Resettable},
     entities::{Bookmark, DeleteBookmarkResponse},
     errors::GatewayError,
 };
@@ -15,19 +14,27 @@ use bookmarks_by_id::{get_loader, BookmarkLoader};
 #[derive(Clone)]
 pub struct BookmarkData {
     channel: tonic::transport::Channel,
-    bookmarks_by_id: BookmarkLoader,
+    bookmarks_by_id: Resettable<BookmarkLoader>,
 }
 
 impl BookmarkData {
     pub fn new(channel: tonic::transport::Channel) -> Self {
         Self {
-            bookmarks_by_id: get_loader(channel.clone()),
+            bookmarks_by_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_loader(channel.clone()))
+            },
             channel,
         }
     }
 
-    pub async fn bookmarks_by_id(&self, id: i32) -> Bookmark {
-        self.bookmarks_by_id.load(id).await
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.bookmarks_by_id.reset();
+    }
+
+    pub async fn bookmarks_by_id(&self, id: i32) -> Result<Option<Bookmark>, GatewayError> {
+        found(self.bookmarks_by_id.get().try_load(id).await)
     }
 
     pub async fn document_bookmarks(
@@ -35,7 +42,7 @@ impl BookmarkData {
         document_id: i32,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<Bookmark>, GatewayError> {
+    ) -> Result<(Vec<Bookmark>, i32), GatewayError> {
         document_bookmarks::document_bookmarks(self.channel.clone(), document_id, limit, offset)
             .await
     }
@@ -45,23 +52,30 @@ impl BookmarkData {
         title: String,
         page_id: i32,
         document_id: i32,
-        acitve_user: Option<User>,
+        token: Option<String>,
     ) -> Result<Bookmark, GatewayError> {
-        create_bookmark::create_bookmark(
+        let bookmark = create_bookmark::create_bookmark(
             self.channel.clone(),
             title,
             page_id,
             document_id,
-            acitve_user,
+            token,
         )
-        .await
+        .await?;
+        self.bookmarks_by_id
+            .prime(bookmark.id, Ok(bookmark.clone()))
+            .await;
+        Ok(bookmark)
     }
 
     pub async fn delete_bookmark(
         &self,
         bookmark_id: i32,
-        acitve_user: Option<User>,
+        token: Option<String>,
     ) -> Result<DeleteBookmarkResponse, GatewayError> {
-        delete_bookmark::delete_bookmark(self.channel.clone(), bookmark_id, acitve_user).await
+        let response =
+            delete_bookmark::delete_bookmark(self.channel.clone(), bookmark_id, token).await?;
+        self.bookmarks_by_id.get().clear(bookmark_id).await;
+        Ok(response)
     }
 }
@@ -0,0 +1,56 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Number of entries kept before expired ones are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Short-lived cache of entities by ID, shared by every request the gateway handles. Only meant
+/// for entities that rarely change, and kept up to date by the mutations that change them.
+/// Changes made by other gateways or straight in the database show up once entries expire.
+pub struct EntityCache<T> {
+    ttl: Duration,
+    entries: Mutex<HashMap<i32, (Instant, T)>>,
+}
+
+impl<T: Clone> EntityCache<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Entries of the given IDs that haven't expired yet
+    pub fn get_many(&self, ids: &[i32]) -> HashMap<i32, T> {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap();
+
+        ids.iter()
+            .filter_map(|id| match entries.get(id) {
+                Some((expires, value)) if *expires > now => Some((*id, value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn insert(&self, id: i32, value: T) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.len() > PRUNE_THRESHOLD {
+            entries.retain(|_, (expires, _)| *expires > now);
+        }
+        entries.insert(id, (now + self.ttl, value));
+    }
+
+    /// Drops every entry the predicate matches, such as those of a deleted document
+    pub fn remove_where(&self, predicate: impl Fn(&T) -> bool) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, (_, value)| !predicate(value));
+    }
+}
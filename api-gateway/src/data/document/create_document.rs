@@ -0,0 +1,28 @@
+use schema::courses::{courses_client::CoursesClient, CreateDocumentRequest};
+
+use crate::{data::forward_token, entities::Document, errors::GatewayError};
+
+pub async fn create_document(
+    channel: tonic::transport::Channel,
+    title: String,
+    language: Option<String>,
+    level: Option<String>,
+    description: Option<String>,
+    private: Option<bool>,
+    token: Option<String>,
+) -> Result<Document, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(CreateDocumentRequest {
+        title,
+        language: language.unwrap_or_default(),
+        level: level.unwrap_or_default(),
+        description: description.unwrap_or_default(),
+        private: private.unwrap_or_default(),
+    });
+    forward_token(&mut request, token);
+    let response = client.create_document(request).await?.into_inner();
+    Ok(response
+        .document
+        .ok_or_else(|| GatewayError::missing_field("document"))?
+        .into())
+}
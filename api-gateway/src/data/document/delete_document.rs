@@ -0,0 +1,17 @@
+use schema::courses::{courses_client::CoursesClient, DeleteDocumentRequest};
+
+use crate::{data::forward_token, entities::DeleteDocumentResponse, errors::GatewayError};
+
+pub async fn delete_document(
+    channel: tonic::transport::Channel,
+    document_id: i32,
+    token: Option<String>,
+) -> Result<DeleteDocumentResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(DeleteDocumentRequest { document_id });
+    forward_token(&mut request, token);
+    let response = client.delete_document(request).await?.into_inner();
+    Ok(DeleteDocumentResponse {
+        success: response.success,
+    })
+}
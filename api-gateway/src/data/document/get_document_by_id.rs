@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetDocumentsByIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::Document, errors::GatewayError};
+use crate::{data::batch_results, entities::Document, errors::GatewayError};
 
 async fn get_document_by_id(
     map: &mut HashMap<i32, Document>,
@@ -33,17 +33,17 @@ impl DocumentBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, Document> for DocumentBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Document> {
+impl BatchFn<i32, Result<Document, GatewayError>> for DocumentBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Document, GatewayError>> {
         let client = CoursesClient::new(self.channel.clone());
 
         let mut anchor_map = HashMap::new();
-        let _ = get_document_by_id(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let loaded = get_document_by_id(&mut anchor_map, keys.to_vec(), client).await;
+        batch_results("document", keys, anchor_map, loaded)
     }
 }
 
-pub type DocumentLoader = Loader<i32, Document, DocumentBatcher>;
+pub type DocumentLoader = Loader<i32, Result<Document, GatewayError>, DocumentBatcher>;
 
 pub fn get_loader(channel: Channel) -> DocumentLoader {
     Loader::new(DocumentBatcher::new(channel))
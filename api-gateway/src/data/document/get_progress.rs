@@ -0,0 +1,15 @@
+use schema::courses::{courses_client::CoursesClient, GetProgressRequest};
+
+use crate::{data::forward_token, entities::Progress, errors::GatewayError};
+
+pub async fn get_progress(
+    channel: tonic::transport::Channel,
+    document_id: i32,
+    token: Option<String>,
+) -> Result<Option<Progress>, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(GetProgressRequest { document_id });
+    forward_token(&mut request, token);
+    let response = client.get_progress(request).await?.into_inner();
+    Ok(response.progress.map(Progress::from))
+}
@@ -1,33 +1,125 @@
-use crate::{entities::Document, errors::GatewayError};
+use crate::{
+    data::{found, Resettable},
+    entities::{
+        DeleteDocumentResponse, Document, DocumentFilters, Progress, SaveProgress, TextMatch,
+    },
+    errors::GatewayError,
+};
 
 mod all_documents;
+mod create_document;
+mod delete_document;
 mod get_document_by_id;
+mod get_progress;
+mod save_progress;
+mod search_document_text;
+mod search_documents;
 
 use get_document_by_id::{get_loader, DocumentLoader};
 
 #[derive(Clone)]
 pub struct DocumentData {
     channel: tonic::transport::Channel,
-    documents_by_id: DocumentLoader,
+    documents_by_id: Resettable<DocumentLoader>,
 }
 
 impl DocumentData {
     pub fn new(channel: tonic::transport::Channel) -> Self {
         Self {
-            documents_by_id: get_loader(channel.clone()),
+            documents_by_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_loader(channel.clone()))
+            },
             channel,
         }
     }
 
-    pub async fn documents_by_id(&self, id: i32) -> Document {
-        self.documents_by_id.load(id).await
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.documents_by_id.reset();
+    }
+
+    pub async fn documents_by_id(&self, id: i32) -> Result<Option<Document>, GatewayError> {
+        found(self.documents_by_id.get().try_load(id).await)
     }
 
     pub async fn all_documents(
         &self,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<Document>, GatewayError> {
+    ) -> Result<(Vec<Document>, i32), GatewayError> {
         all_documents::all_documents(self.channel.clone(), limit, offset).await
     }
+
+    pub async fn search_documents(
+        &self,
+        query: String,
+        filters: DocumentFilters,
+        limit: i32,
+        offset: i32,
+    ) -> Result<(Vec<Document>, i32), GatewayError> {
+        search_documents::search_documents(self.channel.clone(), query, filters, limit, offset)
+            .await
+    }
+
+    pub async fn search_document_text(
+        &self,
+        document_id: i32,
+        query: String,
+        limit: i32,
+    ) -> Result<Vec<TextMatch>, GatewayError> {
+        search_document_text::search_document_text(self.channel.clone(), document_id, query, limit)
+            .await
+    }
+
+    pub async fn create_document(
+        &self,
+        title: String,
+        language: Option<String>,
+        level: Option<String>,
+        description: Option<String>,
+        private: Option<bool>,
+        token: Option<String>,
+    ) -> Result<Document, GatewayError> {
+        let document = create_document::create_document(
+            self.channel.clone(),
+            title,
+            language,
+            level,
+            description,
+            private,
+            token,
+        )
+        .await?;
+        self.documents_by_id
+            .prime(document.id, Ok(document.clone()))
+            .await;
+        Ok(document)
+    }
+
+    pub async fn delete_document(
+        &self,
+        document_id: i32,
+        token: Option<String>,
+    ) -> Result<DeleteDocumentResponse, GatewayError> {
+        let response =
+            delete_document::delete_document(self.channel.clone(), document_id, token).await?;
+        self.documents_by_id.get().clear(document_id).await;
+        Ok(response)
+    }
+    pub async fn progress(
+        &self,
+        document_id: i32,
+        token: Option<String>,
+    ) -> Result<Option<Progress>, GatewayError> {
+        get_progress::get_progress(self.channel.clone(), document_id, token).await
+    }
+
+    pub async fn save_progress(
+        &self,
+        data: SaveProgress,
+        token: Option<String>,
+    ) -> Result<Progress, GatewayError> {
+        save_progress::save_progress(self.channel.clone(), data, token).await
+    }
 }
@@ -0,0 +1,28 @@
+use schema::courses::{courses_client::CoursesClient, SaveProgressRequest};
+
+use crate::{
+    data::forward_token,
+    entities::{Progress, SaveProgress},
+    errors::GatewayError,
+};
+
+pub async fn save_progress(
+    channel: tonic::transport::Channel,
+    data: SaveProgress,
+    token: Option<String>,
+) -> Result<Progress, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(SaveProgressRequest {
+        document_id: data.document_id,
+        track_id: data.track_id,
+        track_time: data.track_time as f32,
+        page_id: data.page_id,
+        completion: data.completion as f32,
+    });
+    forward_token(&mut request, token);
+    let response = client.save_progress(request).await?.into_inner();
+    Ok(response
+        .progress
+        .ok_or_else(|| GatewayError::missing_field("progress"))?
+        .into())
+}
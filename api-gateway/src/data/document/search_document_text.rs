@@ -0,0 +1,19 @@
+use schema::courses::{courses_client::CoursesClient, SearchDocumentTextRequest};
+
+use crate::{entities::TextMatch, errors::GatewayError};
+
+pub async fn search_document_text(
+    channel: tonic::transport::Channel,
+    document_id: i32,
+    query: String,
+    limit: i32,
+) -> Result<Vec<TextMatch>, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let request = tonic::Request::new(SearchDocumentTextRequest {
+        document_id,
+        query,
+        limit,
+    });
+    let result = client.search_document_text(request).await?.into_inner();
+    Ok(result.matches.into_iter().map(|m| m.into()).collect())
+}
@@ -0,0 +1,29 @@
+use schema::courses::{courses_client::CoursesClient, SearchDocumentsRequest};
+
+use crate::{
+    entities::{Document, DocumentFilters},
+    errors::GatewayError,
+};
+
+pub async fn search_documents(
+    channel: tonic::transport::Channel,
+    query: String,
+    filters: DocumentFilters,
+    limit: i32,
+    offset: i32,
+) -> Result<(Vec<Document>, i32), GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let request = tonic::Request::new(SearchDocumentsRequest {
+        query,
+        language: filters.language.unwrap_or_default(),
+        level: filters.level.unwrap_or_default(),
+        sort: schema::courses::DocumentSort::from(filters.sort.unwrap_or_default()) as i32,
+        limit,
+        offset,
+    });
+    let result = client.search_documents(request).await?.into_inner();
+    Ok((
+        result.documents.into_iter().map(|d| d.into()).collect(),
+        result.total,
+    ))
+}
@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use crate::errors::GatewayError;
+
 mod user;
 mod document;
 mod bookmark;
@@ -5,6 +9,9 @@ mod page;
 mod track;
 mod anchor;
 mod user_anchor;
+mod user_note;
+mod cache;
+mod resettable;
 
 pub use user::UserData;
 pub use document::DocumentData;
@@ -13,3 +20,41 @@ pub use page::PageData;
 pub use track::TrackData;
 pub use anchor::AnchorData;
 pub use user_anchor::UserAnchorData;
+pub use user_note::UserNoteData;
+pub use cache::EntityCache;
+pub(crate) use resettable::Resettable;
+
+/// Attaches the caller's access token so the services can verify who is making the request
+pub(crate) fn forward_token<T>(request: &mut tonic::Request<T>, token: Option<String>) {
+    if let Some(token) = token.and_then(|token| token.parse().ok()) {
+        request.metadata_mut().insert("authorization", token);
+    }
+}
+
+/// Results of a batch, leaving out keys that don't exist. A failed request is given as the result
+/// of every key, so that it comes back from the loader as an error instead of a panic.
+pub(crate) fn batch_results<T>(
+    entity: &str,
+    keys: &[i32],
+    found: HashMap<i32, T>,
+    loaded: Result<(), GatewayError>,
+) -> HashMap<i32, Result<T, GatewayError>> {
+    match loaded {
+        Ok(()) => found.into_iter().map(|(key, v)| (key, Ok(v))).collect(),
+        Err(err) => {
+            log::error!("Failed to load {}s {:?}: {}", entity, keys, err);
+            keys.iter().map(|&key| (key, Err(err.clone()))).collect()
+        }
+    }
+}
+
+/// Result of `Loader::try_load`, which fails when the batch left the key out because nothing
+/// exists with that ID
+pub(crate) fn found<T>(
+    result: Result<Result<T, GatewayError>, std::io::Error>,
+) -> Result<Option<T>, GatewayError> {
+    match result {
+        Ok(result) => result.map(Some),
+        Err(_) => Ok(None),
+    }
+}
@@ -0,0 +1,23 @@
+use schema::courses::{courses_client::CoursesClient, AddPagesRequest};
+
+use crate::{
+    data::forward_token,
+    entities::{NewPage, Page},
+    errors::GatewayError,
+};
+
+pub async fn add_pages(
+    channel: tonic::transport::Channel,
+    document_id: i32,
+    pages: Vec<NewPage>,
+    token: Option<String>,
+) -> Result<Vec<Page>, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(AddPagesRequest {
+        document_id,
+        pages: pages.into_iter().map(|p| p.into()).collect(),
+    });
+    forward_token(&mut request, token);
+    let response = client.add_pages(request).await?.into_inner();
+    Ok(response.pages.into_iter().map(|p| p.into()).collect())
+}
@@ -7,7 +7,7 @@ pub async fn document_pages(
     document_id: i32,
     limit: i32,
     offset: i32,
-) -> Result<Vec<Page>, GatewayError> {
+) -> Result<(Vec<Page>, i32), GatewayError> {
     let mut client = CoursesClient::new(channel);
     let request = tonic::Request::new(GetDocumentPagesRequest {
         document_id,
@@ -15,5 +15,8 @@ pub async fn document_pages(
         offset,
     });
     let result = client.get_document_pages(request).await?.into_inner();
-    Ok(result.pages.into_iter().map(|b| b.into()).collect())
+    Ok((
+        result.pages.into_iter().map(|b| b.into()).collect(),
+        result.total,
+    ))
 }
@@ -1,26 +1,73 @@
-use crate::{entities::Page, errors::GatewayError};
+use std::sync::Arc;
 
+use crate::{
+    data::{found, EntityCache, Resettable},
+    entities::{NewPage, Page, TextRegion},
+    errors::GatewayError,
+};
+
+mod add_pages;
 mod document_pages;
 mod pages_by_id;
+mod reorder_pages;
+mod text_regions_by_page_id;
 
 use pages_by_id::{get_loader, PageLoader};
+use text_regions_by_page_id::{get_page_loader, PageTextRegionLoader};
 
 #[derive(Clone)]
 pub struct PageData {
     channel: tonic::transport::Channel,
-    pages_by_id: PageLoader,
+    cache: Arc<EntityCache<Page>>,
+    pages_by_id: Resettable<PageLoader>,
+    text_regions_by_page_id: Resettable<PageTextRegionLoader>,
 }
 
 impl PageData {
-    pub fn new(channel: tonic::transport::Channel) -> Self {
+    pub fn new(channel: tonic::transport::Channel, cache: Arc<EntityCache<Page>>) -> Self {
         Self {
-            pages_by_id: get_loader(channel.clone()),
+            pages_by_id: {
+                let (channel, cache) = (channel.clone(), cache.clone());
+                Resettable::new(move || get_loader(channel.clone(), cache.clone()))
+            },
+            text_regions_by_page_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_page_loader(channel.clone()))
+            },
             channel,
+            cache,
+        }
+    }
+
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.pages_by_id.reset();
+        self.text_regions_by_page_id.reset();
+    }
+
+    /// Replaces the cached copies of pages that a mutation has returned
+    async fn refresh(&self, pages: &[Page]) {
+        for page in pages {
+            self.cache.insert(page.id, page.clone());
+            self.pages_by_id
+                .get()
+                .prime(page.id, Ok(page.clone()))
+                .await;
         }
     }
 
-    pub async fn pages_by_id(&self, id: i32) -> Page {
-        self.pages_by_id.load(id).await
+    /// Drops the cached pages of a deleted document
+    pub fn forget_document(&self, document_id: i32) {
+        self.cache
+            .remove_where(|page| page.document_id == document_id);
+    }
+
+    pub async fn pages_by_id(&self, id: i32) -> Result<Option<Page>, GatewayError> {
+        found(self.pages_by_id.get().try_load(id).await)
+    }
+
+    pub async fn page_text_regions(&self, page_id: i32) -> Result<Vec<TextRegion>, GatewayError> {
+        self.text_regions_by_page_id.get().load(page_id).await
     }
 
     pub async fn document_pages(
@@ -28,7 +75,31 @@ impl PageData {
         document_id: i32,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<Page>, GatewayError> {
+    ) -> Result<(Vec<Page>, i32), GatewayError> {
         document_pages::document_pages(self.channel.clone(), document_id, limit, offset).await
     }
+
+    pub async fn add_pages(
+        &self,
+        document_id: i32,
+        pages: Vec<NewPage>,
+        token: Option<String>,
+    ) -> Result<Vec<Page>, GatewayError> {
+        let pages = add_pages::add_pages(self.channel.clone(), document_id, pages, token).await?;
+        self.refresh(&pages).await;
+        Ok(pages)
+    }
+
+    pub async fn reorder_pages(
+        &self,
+        document_id: i32,
+        page_ids: Vec<i32>,
+        token: Option<String>,
+    ) -> Result<Vec<Page>, GatewayError> {
+        let pages =
+            reorder_pages::reorder_pages(self.channel.clone(), document_id, page_ids, token)
+                .await?;
+        self.refresh(&pages).await;
+        Ok(pages)
+    }
 }
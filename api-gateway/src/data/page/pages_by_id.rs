@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetPagesByIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::Page, errors::GatewayError};
+use crate::{
+    data::{batch_results, EntityCache},
+    entities::Page,
+    errors::GatewayError,
+};
 
 async fn get_page_by_id(
     map: &mut HashMap<i32, Page>,
@@ -24,27 +28,42 @@ async fn get_page_by_id(
 
 pub struct PageBatcher {
     channel: Channel,
+    cache: Arc<EntityCache<Page>>,
 }
 
 impl PageBatcher {
-    pub fn new(channel: Channel) -> Self {
-        Self { channel }
+    pub fn new(channel: Channel, cache: Arc<EntityCache<Page>>) -> Self {
+        Self { channel, cache }
     }
 }
 
 #[async_trait]
-impl BatchFn<i32, Page> for PageBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Page> {
-        let client = CoursesClient::new(self.channel.clone());
+impl BatchFn<i32, Result<Page, GatewayError>> for PageBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Page, GatewayError>> {
+        // Only pages that aren't in the gateway cache are requested from the courses service
+        let mut page_map = self.cache.get_many(keys);
+        let missing = keys
+            .iter()
+            .filter(|key| !page_map.contains_key(key))
+            .copied()
+            .collect::<Vec<i32>>();
+        if missing.is_empty() {
+            return batch_results("page", keys, page_map, Ok(()));
+        }
 
-        let mut anchor_map = HashMap::new();
-        let _ = get_page_by_id(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let client = CoursesClient::new(self.channel.clone());
+        let mut loaded_map = HashMap::new();
+        let loaded = get_page_by_id(&mut loaded_map, missing, client).await;
+        for (page_id, page) in loaded_map {
+            self.cache.insert(page_id, page.clone());
+            page_map.insert(page_id, page);
+        }
+        batch_results("page", keys, page_map, loaded)
     }
 }
 
-pub type PageLoader = Loader<i32, Page, PageBatcher>;
+pub type PageLoader = Loader<i32, Result<Page, GatewayError>, PageBatcher>;
 
-pub fn get_loader(channel: Channel) -> PageLoader {
-    Loader::new(PageBatcher::new(channel))
+pub fn get_loader(channel: Channel, cache: Arc<EntityCache<Page>>) -> PageLoader {
+    Loader::new(PageBatcher::new(channel, cache))
 }
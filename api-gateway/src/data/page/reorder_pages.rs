@@ -0,0 +1,19 @@
+use schema::courses::{courses_client::CoursesClient, ReorderPagesRequest};
+
+use crate::{data::forward_token, entities::Page, errors::GatewayError};
+
+pub async fn reorder_pages(
+    channel: tonic::transport::Channel,
+    document_id: i32,
+    page_ids: Vec<i32>,
+    token: Option<String>,
+) -> Result<Vec<Page>, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(ReorderPagesRequest {
+        document_id,
+        page_ids,
+    });
+    forward_token(&mut request, token);
+    let response = client.reorder_pages(request).await?.into_inner();
+    Ok(response.pages.into_iter().map(|p| p.into()).collect())
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use dataloader::{cached::Loader, BatchFn};
+use schema::courses::{courses_client::CoursesClient, GetTextRegionsByPageIDsRequest};
+use tonic::transport::Channel;
+
+use crate::{data::batch_results, entities::TextRegion, errors::GatewayError};
+
+async fn get_text_regions_by_page_id(
+    map: &mut HashMap<i32, Vec<TextRegion>>,
+    ids: Vec<i32>,
+    mut client: CoursesClient<Channel>,
+) -> Result<(), GatewayError> {
+    let request = tonic::Request::new(GetTextRegionsByPageIDsRequest { ids });
+    let response = client
+        .get_text_regions_by_page_ids(request)
+        .await?
+        .into_inner();
+
+    for (page_id, page_text_regions) in response.text_regions {
+        map.insert(
+            page_id,
+            page_text_regions
+                .text_regions
+                .into_iter()
+                .map(|r| r.into())
+                .collect(),
+        );
+    }
+
+    Ok(())
+}
+
+pub struct PageTextRegionBatcher {
+    channel: Channel,
+}
+
+impl PageTextRegionBatcher {
+    pub fn new(channel: Channel) -> Self {
+        Self { channel }
+    }
+}
+
+#[async_trait]
+impl BatchFn<i32, Result<Vec<TextRegion>, GatewayError>> for PageTextRegionBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Vec<TextRegion>, GatewayError>> {
+        let client = CoursesClient::new(self.channel.clone());
+
+        let mut page_region_map = HashMap::new();
+        keys.iter().for_each(|&k| {
+            page_region_map.insert(k, vec![]);
+        });
+        let loaded = get_text_regions_by_page_id(&mut page_region_map, keys.to_vec(), client).await;
+        batch_results("text region", keys, page_region_map, loaded)
+    }
+}
+
+pub type PageTextRegionLoader =
+    Loader<i32, Result<Vec<TextRegion>, GatewayError>, PageTextRegionBatcher>;
+
+pub fn get_page_loader(channel: Channel) -> PageTextRegionLoader {
+    Loader::new(PageTextRegionBatcher::new(channel))
+}
@@ -0,0 +1,35 @@
+use std::sync::{Arc, RwLock};
+
+/// Loader whose cache can be dropped all at once by swapping in a new loader. Clones share the
+/// loader, so resetting one resets every copy of the context holding it.
+pub struct Resettable<L> {
+    loader: Arc<RwLock<L>>,
+    new_loader: Arc<dyn Fn() -> L + Send + Sync>,
+}
+
+impl<L: Clone> Resettable<L> {
+    pub fn new(new_loader: impl Fn() -> L + Send + Sync + 'static) -> Self {
+        Self {
+            loader: Arc::new(RwLock::new(new_loader())),
+            new_loader: Arc::new(new_loader),
+        }
+    }
+
+    /// The current loader, which keeps its cache for the loads already in flight if it's reset
+    pub fn get(&self) -> L {
+        self.loader.read().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        *self.loader.write().unwrap() = (self.new_loader)();
+    }
+}
+
+impl<L> Clone for Resettable<L> {
+    fn clone(&self) -> Self {
+        Self {
+            loader: self.loader.clone(),
+            new_loader: self.new_loader.clone(),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use schema::courses::{courses_client::CoursesClient, AddTracksRequest};
+
+use crate::{
+    data::forward_token,
+    entities::{NewTrack, Track},
+    errors::GatewayError,
+};
+
+pub async fn add_tracks(
+    channel: tonic::transport::Channel,
+    document_id: i32,
+    tracks: Vec<NewTrack>,
+    token: Option<String>,
+) -> Result<Vec<Track>, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(AddTracksRequest {
+        document_id,
+        tracks: tracks.into_iter().map(|t| t.into()).collect(),
+    });
+    forward_token(&mut request, token);
+    let response = client.add_tracks(request).await?.into_inner();
+    Ok(response.tracks.into_iter().map(|t| t.into()).collect())
+}
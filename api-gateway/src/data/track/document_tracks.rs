@@ -7,7 +7,7 @@ pub async fn document_tracks(
     document_id: i32,
     limit: i32,
     offset: i32,
-) -> Result<Vec<Track>, GatewayError> {
+) -> Result<(Vec<Track>, i32), GatewayError> {
     let mut client = CoursesClient::new(channel);
     let request = tonic::Request::new(GetDocumentTracksRequest {
         document_id,
@@ -15,5 +15,8 @@ pub async fn document_tracks(
         offset,
     });
     let result = client.get_document_tracks(request).await?.into_inner();
-    Ok(result.tracks.into_iter().map(|t| t.into()).collect())
+    Ok((
+        result.tracks.into_iter().map(|t| t.into()).collect(),
+        result.total,
+    ))
 }
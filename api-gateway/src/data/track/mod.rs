@@ -1,7 +1,12 @@
-use schema::shared::User;
+use std::sync::Arc;
 
-use crate::{entities::Track, errors::GatewayError};
+use crate::{
+    data::{found, EntityCache, Resettable},
+    entities::{NewTrack, Track},
+    errors::GatewayError,
+};
 
+mod add_tracks;
 mod document_tracks;
 mod tracks_by_id;
 mod update_track_title;
@@ -11,19 +16,46 @@ use tracks_by_id::{get_loader, TrackLoader};
 #[derive(Clone)]
 pub struct TrackData {
     channel: tonic::transport::Channel,
-    tracks_by_id: TrackLoader,
+    cache: Arc<EntityCache<Track>>,
+    tracks_by_id: Resettable<TrackLoader>,
 }
 
 impl TrackData {
-    pub fn new(channel: tonic::transport::Channel) -> Self {
+    pub fn new(channel: tonic::transport::Channel, cache: Arc<EntityCache<Track>>) -> Self {
         Self {
-            tracks_by_id: get_loader(channel.clone()),
+            tracks_by_id: {
+                let (channel, cache) = (channel.clone(), cache.clone());
+                Resettable::new(move || get_loader(channel.clone(), cache.clone()))
+            },
             channel,
+            cache,
         }
     }
 
-    pub async fn tracks_by_id(&self, id: i32) -> Track {
-        self.tracks_by_id.load(id).await
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.tracks_by_id.reset();
+    }
+
+    /// Replaces the cached copies of tracks that a mutation has returned
+    async fn refresh(&self, tracks: &[Track]) {
+        for track in tracks {
+            self.cache.insert(track.id, track.clone());
+            self.tracks_by_id
+                .get()
+                .prime(track.id, Ok(track.clone()))
+                .await;
+        }
+    }
+
+    /// Drops the cached tracks of a deleted document
+    pub fn forget_document(&self, document_id: i32) {
+        self.cache
+            .remove_where(|track| track.document_id == document_id);
+    }
+
+    pub async fn tracks_by_id(&self, id: i32) -> Result<Option<Track>, GatewayError> {
+        found(self.tracks_by_id.get().try_load(id).await)
     }
 
     pub async fn document_tracks(
@@ -31,17 +63,32 @@ impl TrackData {
         document_id: i32,
         limit: i32,
         offset: i32,
-    ) -> Result<Vec<Track>, GatewayError> {
+    ) -> Result<(Vec<Track>, i32), GatewayError> {
         document_tracks::document_tracks(self.channel.clone(), document_id, limit, offset).await
     }
 
+    pub async fn add_tracks(
+        &self,
+        document_id: i32,
+        tracks: Vec<NewTrack>,
+        token: Option<String>,
+    ) -> Result<Vec<Track>, GatewayError> {
+        let tracks =
+            add_tracks::add_tracks(self.channel.clone(), document_id, tracks, token).await?;
+        self.refresh(&tracks).await;
+        Ok(tracks)
+    }
+
     pub async fn update_track_title(
         &self,
         track_id: i32,
         title: String,
-        active_user: Option<User>,
+        token: Option<String>,
     ) -> Result<Track, GatewayError> {
-        update_track_title::update_track_title(active_user, track_id, title, self.channel.clone())
-            .await
+        let track =
+            update_track_title::update_track_title(token, track_id, title, self.channel.clone())
+                .await?;
+        self.refresh(&[track.clone()]).await;
+        Ok(track)
     }
 }
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetTracksByIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::Track, errors::GatewayError};
+use crate::{
+    data::{batch_results, EntityCache},
+    entities::Track,
+    errors::GatewayError,
+};
 
 async fn get_track_by_id(
     map: &mut HashMap<i32, Track>,
@@ -24,27 +28,42 @@ async fn get_track_by_id(
 
 pub struct TrackBatcher {
     channel: Channel,
+    cache: Arc<EntityCache<Track>>,
 }
 
 impl TrackBatcher {
-    pub fn new(channel: Channel) -> Self {
-        Self { channel }
+    pub fn new(channel: Channel, cache: Arc<EntityCache<Track>>) -> Self {
+        Self { channel, cache }
     }
 }
 
 #[async_trait]
-impl BatchFn<i32, Track> for TrackBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Track> {
-        let client = CoursesClient::new(self.channel.clone());
+impl BatchFn<i32, Result<Track, GatewayError>> for TrackBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Track, GatewayError>> {
+        // Only tracks that aren't in the gateway cache are requested from the courses service
+        let mut track_map = self.cache.get_many(keys);
+        let missing = keys
+            .iter()
+            .filter(|key| !track_map.contains_key(key))
+            .copied()
+            .collect::<Vec<i32>>();
+        if missing.is_empty() {
+            return batch_results("track", keys, track_map, Ok(()));
+        }
 
-        let mut anchor_map = HashMap::new();
-        let _ = get_track_by_id(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let client = CoursesClient::new(self.channel.clone());
+        let mut loaded_map = HashMap::new();
+        let loaded = get_track_by_id(&mut loaded_map, missing, client).await;
+        for (track_id, track) in loaded_map {
+            self.cache.insert(track_id, track.clone());
+            track_map.insert(track_id, track);
+        }
+        batch_results("track", keys, track_map, loaded)
     }
 }
 
-pub type TrackLoader = Loader<i32, Track, TrackBatcher>;
+pub type TrackLoader = Loader<i32, Result<Track, GatewayError>, TrackBatcher>;
 
-pub fn get_loader(channel: Channel) -> TrackLoader {
-    Loader::new(TrackBatcher::new(channel))
+pub fn get_loader(channel: Channel, cache: Arc<EntityCache<Track>>) -> TrackLoader {
+    Loader::new(TrackBatcher::new(channel, cache))
 }
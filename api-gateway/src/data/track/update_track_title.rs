@@ -1,24 +1,20 @@
-use schema::{
-    courses::{courses_client::CoursesClient, UpdateTrackTitleRequest},
-    shared::User,
-};
+use schema::courses::{courses_client::CoursesClient, UpdateTrackTitleRequest};
 
-use crate::{entities::Track, errors::GatewayError};
+use crate::{data::forward_token, entities::Track, errors::GatewayError};
 
 pub async fn update_track_title(
-    active_user: Option<User>,
+    token: Option<String>,
     track_id: i32,
     title: String,
     channel: tonic::transport::Channel,
 ) -> Result<Track, GatewayError> {
     let mut client = CoursesClient::new(channel);
-    let request = tonic::Request::new(UpdateTrackTitleRequest {
-        active_user,
-        track_id,
-        title,
-    });
+    let mut request = tonic::Request::new(UpdateTrackTitleRequest { track_id, title });
+    forward_token(&mut request, token);
     let response = client.update_track_title(request).await?.into_inner();
-    let track = response.track.unwrap();
+    let track = response
+        .track
+        .ok_or_else(|| GatewayError::missing_field("track"))?;
     Ok(Track {
         id: track.id,
         track_number: track.track_number,
@@ -11,7 +11,9 @@ pub async fn authenticate(
     let result = client
         .authenticate(request)
         .await?
-        .into_inner().user.unwrap();
+        .into_inner()
+        .user
+        .ok_or_else(|| GatewayError::missing_field("user"))?;
 
     Ok(result)
 }
@@ -15,5 +15,8 @@ pub async fn create_user(
         password: data.password,
     });
     let response = client.create_user(request).await?.into_inner();
-    Ok(response.user.unwrap().into())
+    Ok(response
+        .user
+        .ok_or_else(|| GatewayError::missing_field("user"))?
+        .into())
 }
@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::users::{users_client::UsersClient, GetUsersByIdsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::User, errors::GatewayError};
+use crate::{data::batch_results, entities::User, errors::GatewayError};
 
 async fn get_user_by_ids(
     map: &mut HashMap<i32, User>,
@@ -33,17 +33,17 @@ impl UserBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, User> for UserBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, User> {
+impl BatchFn<i32, Result<User, GatewayError>> for UserBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<User, GatewayError>> {
         let client = UsersClient::new(self.channel.clone());
 
         let mut anchor_map = HashMap::new();
-        let _ = get_user_by_ids(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let loaded = get_user_by_ids(&mut anchor_map, keys.to_vec(), client).await;
+        batch_results("user", keys, anchor_map, loaded)
     }
 }
 
-pub type UserLoader = Loader<i32, User, UserBatcher>;
+pub type UserLoader = Loader<i32, Result<User, GatewayError>, UserBatcher>;
 
 pub fn get_loader(channel: Channel) -> UserLoader {
     Loader::new(UserBatcher::new(channel))
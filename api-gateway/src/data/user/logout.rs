@@ -0,0 +1,16 @@
+use schema::users::{users_client::UsersClient, RevokeRefreshTokenRequest};
+
+use crate::entities::LogoutResponse;
+use crate::errors::GatewayError;
+
+pub async fn logout(
+    refresh_token: String,
+    channel: tonic::transport::Channel,
+) -> Result<LogoutResponse, GatewayError> {
+    let mut client = UsersClient::new(channel);
+    let request = tonic::Request::new(RevokeRefreshTokenRequest { refresh_token });
+    let response = client.revoke_refresh_token(request).await?.into_inner();
+    Ok(LogoutResponse {
+        success: response.success,
+    })
+}
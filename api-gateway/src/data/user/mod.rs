@@ -1,5 +1,6 @@
 use crate::{
-    entities::{NewUser, User, UserRole},
+    data::{found, Resettable},
+    entities::{LoginResponse, LogoutResponse, NewUser, User, UserRole},
     errors::GatewayError,
 };
 
@@ -10,34 +11,59 @@ use get_user_by_id::{get_loader, UserLoader};
 use schema::users::UpdateUserRoleResponse;
 mod authenticate;
 mod login;
+mod logout;
+mod refresh_token;
 mod update_user_role;
 
 #[derive(Clone)]
 pub struct UserData {
     channel: tonic::transport::Channel,
-    user_by_id: UserLoader,
+    user_by_id: Resettable<UserLoader>,
 }
 
 impl UserData {
     pub fn new(channel: tonic::transport::Channel) -> Self {
         Self {
-            user_by_id: get_loader(channel.clone()),
+            user_by_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_loader(channel.clone()))
+            },
             channel,
         }
     }
 
-    pub async fn user_by_id(&self, id: i32) -> User {
-        self.user_by_id.load(id).await
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.user_by_id.reset();
+    }
+
+    pub async fn user_by_id(&self, id: i32) -> Result<Option<User>, GatewayError> {
+        found(self.user_by_id.get().try_load(id).await)
     }
 
     pub async fn create_user(&self, data: NewUser) -> Result<User, GatewayError> {
         create_user::create_user(data, self.channel.clone()).await
     }
 
-    pub async fn login(&self, username: String, password: String) -> Result<(String, User), GatewayError> {
+    pub async fn login(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<LoginResponse, GatewayError> {
         login::login(username, password, self.channel.clone()).await
     }
 
+    pub async fn refresh_token(
+        &self,
+        refresh_token: String,
+    ) -> Result<LoginResponse, GatewayError> {
+        refresh_token::refresh_token(refresh_token, self.channel.clone()).await
+    }
+
+    pub async fn logout(&self, refresh_token: String) -> Result<LogoutResponse, GatewayError> {
+        logout::logout(refresh_token, self.channel.clone()).await
+    }
+
     pub async fn all_users(&self) -> Result<Vec<User>, GatewayError> {
         all_users::all_users(self.channel.clone()).await
     }
@@ -50,8 +76,13 @@ impl UserData {
         &self,
         user_id: i32,
         new_role: UserRole,
-        user: Option<schema::shared::User>,
+        token: Option<String>,
     ) -> Result<UpdateUserRoleResponse, GatewayError> {
-        update_user_role::update_user_role(user, user_id, new_role, self.channel.clone()).await
+        let response =
+            update_user_role::update_user_role(token, user_id, new_role, self.channel.clone())
+                .await?;
+        // Loaded again by later operations of a batch, with the new role
+        self.user_by_id.get().clear(user_id).await;
+        Ok(response)
     }
 }
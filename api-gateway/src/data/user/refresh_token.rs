@@ -0,0 +1,16 @@
+use std::convert::TryFrom;
+
+use schema::users::{users_client::UsersClient, RefreshTokenRequest};
+
+use crate::entities::LoginResponse;
+use crate::errors::GatewayError;
+
+pub async fn refresh_token(
+    refresh_token: String,
+    channel: tonic::transport::Channel,
+) -> Result<LoginResponse, GatewayError> {
+    let mut client = UsersClient::new(channel);
+    let request = tonic::Request::new(RefreshTokenRequest { refresh_token });
+    let response = client.refresh_token(request).await?.into_inner();
+    LoginResponse::try_from(response)
+}
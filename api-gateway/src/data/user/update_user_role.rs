@@ -1,19 +1,15 @@
-use schema::{
-    shared::User,
-    users::{users_client::UsersClient, UpdateUserRoleRequest, UpdateUserRoleResponse},
-};
+use schema::users::{users_client::UsersClient, UpdateUserRoleRequest, UpdateUserRoleResponse};
 
-use crate::{entities::UserRole, errors::GatewayError};
+use crate::{data::forward_token, entities::UserRole, errors::GatewayError};
 
 pub async fn update_user_role(
-    user: Option<User>,
+    token: Option<String>,
     user_id: i32,
     new_role: UserRole,
     channel: tonic::transport::Channel,
 ) -> Result<UpdateUserRoleResponse, GatewayError> {
     let mut client = UsersClient::new(channel);
-    let request = tonic::Request::new(UpdateUserRoleRequest {
-        active_user: user,
+    let mut request = tonic::Request::new(UpdateUserRoleRequest {
         user_id: user_id,
         new_role: match new_role {
             UserRole::Standard => 0,
@@ -21,6 +17,7 @@ pub async fn update_user_role(
             UserRole::Administrator => 2,
         },
     });
+    forward_token(&mut request, token);
     let response = client.update_user_role(request).await?.into_inner();
     Ok(response)
 }
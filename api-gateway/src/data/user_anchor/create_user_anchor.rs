@@ -1,18 +1,16 @@
-use schema::{
-    courses::{courses_client::CoursesClient, CreateUserAnchorRequest, CreateUserAnchorResponse},
-    shared::User,
+use schema::courses::{
+    courses_client::CoursesClient, CreateUserAnchorRequest, CreateUserAnchorResponse,
 };
 
-use crate::{entities::CreateUserAnchor, errors::GatewayError};
+use crate::{data::forward_token, entities::CreateUserAnchor, errors::GatewayError};
 
 pub async fn create_user_anchor(
-    user: Option<User>,
+    token: Option<String>,
     data: CreateUserAnchor,
     channel: tonic::transport::Channel,
 ) -> Result<CreateUserAnchorResponse, GatewayError> {
     let mut client = CoursesClient::new(channel);
-    let request = tonic::Request::new(CreateUserAnchorRequest {
-        active_user: user,
+    let mut request = tonic::Request::new(CreateUserAnchorRequest {
         title: data.title,
         track_time: data.track_time as f32,
         position_top: data.position_top as f32,
@@ -20,6 +18,7 @@ pub async fn create_user_anchor(
         page_id: data.page_id,
         track_id: data.track_id,
     });
+    forward_token(&mut request, token);
     let response = client.create_user_anchor(request).await?.into_inner();
     Ok(response)
 }
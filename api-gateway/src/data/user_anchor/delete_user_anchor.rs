@@ -1,20 +1,17 @@
-use schema::{
-    courses::{courses_client::CoursesClient, DeleteUserAnchorRequest, DeleteUserAnchorResponse},
-    shared::User,
+use schema::courses::{
+    courses_client::CoursesClient, DeleteUserAnchorRequest, DeleteUserAnchorResponse,
 };
 
-use crate::errors::GatewayError;
+use crate::{data::forward_token, errors::GatewayError};
 
 pub async fn delete_user_anchor(
-    user: Option<User>,
+    token: Option<String>,
     anchor_id: i32,
     channel: tonic::transport::Channel,
 ) -> Result<DeleteUserAnchorResponse, GatewayError> {
     let mut client = CoursesClient::new(channel);
-    let request = tonic::Request::new(DeleteUserAnchorRequest {
-        active_user: user,
-        id: anchor_id,
-    });
+    let mut request = tonic::Request::new(DeleteUserAnchorRequest { id: anchor_id });
+    forward_token(&mut request, token);
     let response = client.delete_user_anchor(request).await?.into_inner();
     Ok(response)
 }
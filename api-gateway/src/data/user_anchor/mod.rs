@@ -1,11 +1,12 @@
 use crate::{
-    entities::{CreateUserAnchor, DeleteUserAnchorResponse, UserAnchor},
+    data::{found, Resettable},
+    entities::{CreateUserAnchor, DeleteUserAnchorResponse, UpdateUserAnchor, UserAnchor},
     errors::GatewayError,
 };
-use schema::shared::User;
 
 mod create_user_anchor;
 mod delete_user_anchor;
+mod update_user_anchor;
 mod user_anchors_by_id;
 mod user_anchors_by_page_id;
 
@@ -15,45 +16,107 @@ use user_anchors_by_page_id::{get_page_loader, PageUserAnchorLoader};
 #[derive(Clone)]
 pub struct UserAnchorData {
     channel: tonic::transport::Channel,
-    user_anchors_by_id: UserAnchorLoader,
-    user_anchors_by_page_id: PageUserAnchorLoader,
+    user_anchors_by_id: Resettable<UserAnchorLoader>,
+    user_anchors_by_page_id: Resettable<PageUserAnchorLoader>,
 }
 
 impl UserAnchorData {
     pub fn new(channel: tonic::transport::Channel) -> Self {
         Self {
-            user_anchors_by_id: get_loader(channel.clone()),
-            user_anchors_by_page_id: get_page_loader(channel.clone()),
+            user_anchors_by_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_loader(channel.clone()))
+            },
+            user_anchors_by_page_id: {
+                let channel = channel.clone();
+                Resettable::new(move || get_page_loader(channel.clone()))
+            },
             channel,
         }
     }
 
-    pub async fn user_anchors_by_id(&self, id: i32) -> UserAnchor {
-        self.user_anchors_by_id.load(id).await
+    /// Drops everything the loaders have cached
+    pub fn reset(&self) {
+        self.user_anchors_by_id.reset();
+        self.user_anchors_by_page_id.reset();
     }
 
-    pub async fn page_user_anchors(&self, page_id: i32) -> Vec<UserAnchor> {
-        self.user_anchors_by_page_id.load(page_id).await
+    /// Replaces the request's copy of a user anchor that a mutation has returned, and drops the
+    /// user anchors of the pages it was on and is now on, so that later operations of a batch
+    /// see the change
+    pub async fn refresh(&self, user_anchor: &UserAnchor, previous_page_id: Option<i32>) {
+        self.user_anchors_by_id
+            .prime(user_anchor.id, Ok(user_anchor.clone()))
+            .await;
+        for page_id in previous_page_id
+            .into_iter()
+            .chain(Some(user_anchor.page_id))
+        {
+            self.user_anchors_by_page_id.get().clear(page_id).await;
+        }
+    }
+
+    /// Drops the request's copy of a user anchor that no longer exists, along with the user
+    /// anchors of the page it was on
+    pub async fn forget(&self, user_anchor_id: i32, page_id: Option<i32>) {
+        self.user_anchors_by_id.get().clear(user_anchor_id).await;
+        if let Some(page_id) = page_id {
+            self.user_anchors_by_page_id.get().clear(page_id).await;
+        }
+    }
+
+    pub async fn user_anchors_by_id(&self, id: i32) -> Result<Option<UserAnchor>, GatewayError> {
+        found(self.user_anchors_by_id.get().try_load(id).await)
+    }
+
+    pub async fn page_user_anchors(&self, page_id: i32) -> Result<Vec<UserAnchor>, GatewayError> {
+        self.user_anchors_by_page_id.get().load(page_id).await
     }
 
     pub async fn create_user_anchor(
         &self,
-        user: Option<User>,
+        token: Option<String>,
         data: CreateUserAnchor,
     ) -> Result<UserAnchor, GatewayError> {
         let response =
-            create_user_anchor::create_user_anchor(user, data, self.channel.clone()).await?;
-        Ok(response.user_anchor.unwrap().into())
+            create_user_anchor::create_user_anchor(token, data, self.channel.clone()).await?;
+        let user_anchor: UserAnchor = response
+            .user_anchor
+            .ok_or_else(|| GatewayError::missing_field("user anchor"))?
+            .into();
+        self.refresh(&user_anchor, None).await;
+        Ok(user_anchor)
+    }
+
+    pub async fn update_user_anchor(
+        &self,
+        token: Option<String>,
+        data: UpdateUserAnchor,
+    ) -> Result<UserAnchor, GatewayError> {
+        // Loaded first to find the page that the user anchor may be moved off
+        let previous = self.user_anchors_by_id(data.id).await?;
+        let response =
+            update_user_anchor::update_user_anchor(token, data, self.channel.clone()).await?;
+        let user_anchor: UserAnchor = response
+            .user_anchor
+            .ok_or_else(|| GatewayError::missing_field("user anchor"))?
+            .into();
+        self.refresh(&user_anchor, previous.map(|a| a.page_id))
+            .await;
+        Ok(user_anchor)
     }
 
     pub async fn delete_user_anchor(
         &self,
-        user: Option<User>,
+        token: Option<String>,
         user_anchor_id: i32,
     ) -> Result<DeleteUserAnchorResponse, GatewayError> {
+        let previous = self.user_anchors_by_id(user_anchor_id).await?;
         let response =
-            delete_user_anchor::delete_user_anchor(user, user_anchor_id, self.channel.clone())
+            delete_user_anchor::delete_user_anchor(token, user_anchor_id, self.channel.clone())
                 .await?;
+        self.forget(user_anchor_id, previous.map(|a| a.page_id))
+            .await;
         Ok(DeleteUserAnchorResponse {
             success: response.success,
         })
@@ -0,0 +1,25 @@
+use schema::courses::{
+    courses_client::CoursesClient, UpdateUserAnchorRequest, UpdateUserAnchorResponse,
+};
+
+use crate::{data::forward_token, entities::UpdateUserAnchor, errors::GatewayError};
+
+pub async fn update_user_anchor(
+    token: Option<String>,
+    data: UpdateUserAnchor,
+    channel: tonic::transport::Channel,
+) -> Result<UpdateUserAnchorResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(UpdateUserAnchorRequest {
+        id: data.id,
+        title: data.title,
+        track_time: data.track_time as f32,
+        position_top: data.position_top as f32,
+        position_left: data.position_left as f32,
+        page_id: data.page_id,
+        track_id: data.track_id,
+    });
+    forward_token(&mut request, token);
+    let response = client.update_user_anchor(request).await?.into_inner();
+    Ok(response)
+}
@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetUserAnchorsByIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::UserAnchor, errors::GatewayError};
+use crate::{data::batch_results, entities::UserAnchor, errors::GatewayError};
 
 async fn get_anchor_by_id(
     map: &mut HashMap<i32, UserAnchor>,
@@ -33,17 +33,17 @@ impl UserAnchorBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, UserAnchor> for UserAnchorBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, UserAnchor> {
+impl BatchFn<i32, Result<UserAnchor, GatewayError>> for UserAnchorBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<UserAnchor, GatewayError>> {
         let client = CoursesClient::new(self.channel.clone());
 
         let mut anchor_map = HashMap::new();
-        let _ = get_anchor_by_id(&mut anchor_map, keys.to_vec(), client).await;
-        anchor_map
+        let loaded = get_anchor_by_id(&mut anchor_map, keys.to_vec(), client).await;
+        batch_results("user anchor", keys, anchor_map, loaded)
     }
 }
 
-pub type UserAnchorLoader = Loader<i32, UserAnchor, UserAnchorBatcher>;
+pub type UserAnchorLoader = Loader<i32, Result<UserAnchor, GatewayError>, UserAnchorBatcher>;
 
 pub fn get_loader(channel: Channel) -> UserAnchorLoader {
     Loader::new(UserAnchorBatcher::new(channel))
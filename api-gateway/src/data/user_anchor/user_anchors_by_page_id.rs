@@ -5,7 +5,7 @@ use dataloader::{cached::Loader, BatchFn};
 use schema::courses::{courses_client::CoursesClient, GetUserAnchorsByPageIDsRequest};
 use tonic::transport::Channel;
 
-use crate::{entities::UserAnchor, errors::GatewayError};
+use crate::{data::batch_results, entities::UserAnchor, errors::GatewayError};
 
 async fn get_user_anchors_by_page_id(
     map: &mut HashMap<i32, Vec<UserAnchor>>,
@@ -43,20 +43,21 @@ impl PageUserAnchorBatcher {
 }
 
 #[async_trait]
-impl BatchFn<i32, Vec<UserAnchor>> for PageUserAnchorBatcher {
-    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Vec<UserAnchor>> {
+impl BatchFn<i32, Result<Vec<UserAnchor>, GatewayError>> for PageUserAnchorBatcher {
+    async fn load(&mut self, keys: &[i32]) -> HashMap<i32, Result<Vec<UserAnchor>, GatewayError>> {
         let client = CoursesClient::new(self.channel.clone());
 
         let mut page_anchor_map = HashMap::new();
         keys.iter().for_each(|&k| {
             page_anchor_map.insert(k, vec![]);
         });
-        let _ = get_user_anchors_by_page_id(&mut page_anchor_map, keys.to_vec(), client).await;
-        page_anchor_map
+        let loaded = get_user_anchors_by_page_id(&mut page_anchor_map, keys.to_vec(), client).await;
+        batch_results("user anchor", keys, page_anchor_map, loaded)
     }
 }
 
-pub type PageUserAnchorLoader = Loader<i32, Vec<UserAnchor>, PageUserAnchorBatcher>;
+pub type PageUserAnchorLoader =
+    Loader<i32, Result<Vec<UserAnchor>, GatewayError>, PageUserAnchorBatcher>;
 
 pub fn get_page_loader(channel: Channel) -> PageUserAnchorLoader {
     Loader::new(PageUserAnchorBatcher::new(channel))
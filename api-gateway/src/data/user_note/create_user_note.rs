@@ -0,0 +1,25 @@
+use schema::courses::{
+    courses_client::CoursesClient, CreateUserNoteRequest, CreateUserNoteResponse,
+};
+
+use crate::{data::forward_token, entities::CreateUserNote, errors::GatewayError};
+
+pub async fn create_user_note(
+    token: Option<String>,
+    data: CreateUserNote,
+    channel: tonic::transport::Channel,
+) -> Result<CreateUserNoteResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(CreateUserNoteRequest {
+        document_id: data.document_id,
+        content: data.content,
+        page_id: data.page_id.unwrap_or(0),
+        position_top: data.position_top.unwrap_or(0.0) as f32,
+        position_left: data.position_left.unwrap_or(0.0) as f32,
+        track_id: data.track_id.unwrap_or(0),
+        track_time: data.track_time.unwrap_or(0.0) as f32,
+    });
+    forward_token(&mut request, token);
+    let response = client.create_user_note(request).await?.into_inner();
+    Ok(response)
+}
@@ -0,0 +1,17 @@
+use schema::courses::{
+    courses_client::CoursesClient, DeleteUserNoteRequest, DeleteUserNoteResponse,
+};
+
+use crate::{data::forward_token, errors::GatewayError};
+
+pub async fn delete_user_note(
+    token: Option<String>,
+    user_note_id: i32,
+    channel: tonic::transport::Channel,
+) -> Result<DeleteUserNoteResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(DeleteUserNoteRequest { id: user_note_id });
+    forward_token(&mut request, token);
+    let response = client.delete_user_note(request).await?.into_inner();
+    Ok(response)
+}
@@ -0,0 +1,17 @@
+use schema::courses::{
+    courses_client::CoursesClient, GetDocumentUserNotesRequest, GetDocumentUserNotesResponse,
+};
+
+use crate::{data::forward_token, errors::GatewayError};
+
+pub async fn document_user_notes(
+    token: Option<String>,
+    document_id: i32,
+    channel: tonic::transport::Channel,
+) -> Result<GetDocumentUserNotesResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(GetDocumentUserNotesRequest { document_id });
+    forward_token(&mut request, token);
+    let response = client.get_document_user_notes(request).await?.into_inner();
+    Ok(response)
+}
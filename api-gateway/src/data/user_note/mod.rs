@@ -0,0 +1,73 @@
+use crate::{
+    entities::{CreateUserNote, DeleteUserNoteResponse, UpdateUserNote, UserNote},
+    errors::GatewayError,
+};
+
+mod create_user_note;
+mod delete_user_note;
+mod document_user_notes;
+mod update_user_note;
+
+#[derive(Clone)]
+pub struct UserNoteData {
+    channel: tonic::transport::Channel,
+}
+
+impl UserNoteData {
+    pub fn new(channel: tonic::transport::Channel) -> Self {
+        Self { channel }
+    }
+
+    pub async fn document_user_notes(
+        &self,
+        token: Option<String>,
+        document_id: i32,
+    ) -> Result<Vec<UserNote>, GatewayError> {
+        let response =
+            document_user_notes::document_user_notes(token, document_id, self.channel.clone())
+                .await?;
+        Ok(response
+            .user_notes
+            .into_iter()
+            .map(UserNote::from)
+            .collect())
+    }
+
+    pub async fn create_user_note(
+        &self,
+        token: Option<String>,
+        data: CreateUserNote,
+    ) -> Result<UserNote, GatewayError> {
+        let response =
+            create_user_note::create_user_note(token, data, self.channel.clone()).await?;
+        Ok(response
+            .user_note
+            .ok_or_else(|| GatewayError::missing_field("user note"))?
+            .into())
+    }
+
+    pub async fn update_user_note(
+        &self,
+        token: Option<String>,
+        data: UpdateUserNote,
+    ) -> Result<UserNote, GatewayError> {
+        let response =
+            update_user_note::update_user_note(token, data, self.channel.clone()).await?;
+        Ok(response
+            .user_note
+            .ok_or_else(|| GatewayError::missing_field("user note"))?
+            .into())
+    }
+
+    pub async fn delete_user_note(
+        &self,
+        token: Option<String>,
+        user_note_id: i32,
+    ) -> Result<DeleteUserNoteResponse, GatewayError> {
+        let response =
+            delete_user_note::delete_user_note(token, user_note_id, self.channel.clone()).await?;
+        Ok(DeleteUserNoteResponse {
+            success: response.success,
+        })
+    }
+}
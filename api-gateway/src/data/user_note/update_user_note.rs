@@ -0,0 +1,25 @@
+use schema::courses::{
+    courses_client::CoursesClient, UpdateUserNoteRequest, UpdateUserNoteResponse,
+};
+
+use crate::{data::forward_token, entities::UpdateUserNote, errors::GatewayError};
+
+pub async fn update_user_note(
+    token: Option<String>,
+    data: UpdateUserNote,
+    channel: tonic::transport::Channel,
+) -> Result<UpdateUserNoteResponse, GatewayError> {
+    let mut client = CoursesClient::new(channel);
+    let mut request = tonic::Request::new(UpdateUserNoteRequest {
+        id: data.id,
+        content: data.content,
+        page_id: data.page_id.unwrap_or(0),
+        position_top: data.position_top.unwrap_or(0.0) as f32,
+        position_left: data.position_left.unwrap_or(0.0) as f32,
+        track_id: data.track_id.unwrap_or(0),
+        track_time: data.track_time.unwrap_or(0.0) as f32,
+    });
+    forward_token(&mut request, token);
+    let response = client.update_user_note(request).await?.into_inner();
+    Ok(response)
+}
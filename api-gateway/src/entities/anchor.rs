@@ -1,9 +1,13 @@
-use std::convert::From;
+use std::convert::{From, TryFrom};
 
 use chrono::{DateTime, FixedOffset};
+use juniper::ID;
 
-use super::{Page, Track};
-use crate::graphql::schema::Context;
+use super::{
+    node::{NodeKind, NodeValue},
+    parse_timestamp, Page, Track, User,
+};
+use crate::{errors::GatewayError, graphql::schema::Context};
 
 #[derive(Debug, Clone)]
 /// Anchor for a page
@@ -44,18 +48,121 @@ pub struct CreateAnchor {
     pub track_id: i32,
 }
 
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+pub struct UpdateAnchor {
+    // ID of the anchor to update
+    pub id: i32,
+    // New title for the anchor
+    pub title: String,
+    // Track time that the anchor will point to
+    pub track_time: f64,
+    // Y position of the anchor on page
+    pub position_top: f64,
+    // X position of the anchor on page
+    pub position_left: f64,
+    // ID of the page for the anchor
+    pub page_id: i32,
+    // Track for the anchor
+    pub track_id: i32,
+}
+
 #[derive(juniper::GraphQLObject, Debug, Clone)]
 pub struct DeleteAnchorResponse {
     // Indicates whether deletion was successful
     pub success: bool,
 }
 
-#[juniper::graphql_object(Context = Context)]
+#[derive(juniper::GraphQLEnum, Debug, Clone, Copy)]
+pub enum AnchorFileFormat {
+    // A single JSON file containing every anchor of the document
+    Json,
+    // One WebVTT file per track, with cue payloads locating each anchor on its page
+    Webvtt,
+    // One LRC file per track with anchor titles only, placed like the existing anchors when
+    // imported
+    Lrc,
+}
+
+#[derive(juniper::GraphQLObject, Debug, Clone)]
+/// File of exported anchors
+pub struct AnchorFile {
+    // Suggested name for the file
+    pub file_name: String,
+    // Contents of the file
+    pub content: String,
+    // ID of the track that the file belongs to, 0 for JSON
+    pub track_id: i32,
+}
+
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+pub struct NewAnchorFile {
+    // Contents of the file
+    pub content: String,
+    // ID of the track that the anchors in a WebVTT or LRC file refer to
+    pub track_id: Option<i32>,
+}
+
+#[derive(juniper::GraphQLObject, Debug, Clone)]
+pub struct ImportAnchorsResponse {
+    // Number of anchors that were imported
+    pub imported: i32,
+}
+
+#[derive(juniper::GraphQLEnum, Debug, Clone, Copy)]
+pub enum AnchorChangeKind {
+    // The anchor was created or updated
+    Upserted,
+    // The anchor was deleted
+    Deleted,
+}
+
+#[derive(Debug, Clone)]
+/// Change to an approved anchor of a document
+pub struct AnchorChange {
+    // What happened to the anchor
+    pub kind: AnchorChangeKind,
+    // State of the anchor after the change, or before it for deletions
+    pub anchor: Anchor,
+}
+
+#[derive(juniper::GraphQLEnum, Debug, Clone, Copy)]
+pub enum AnchorEventKind {
+    Create,
+    // The anchor was edited or moved to another position
+    Move,
+    // The anchor was created from a user anchor
+    Promote,
+    Delete,
+    Restore,
+}
+
+#[derive(Debug, Clone)]
+/// Recorded change to an approved anchor
+pub struct AnchorEvent {
+    // ID of the event, used to restore the anchor to its state before the change
+    pub id: i32,
+    // What was done to the anchor
+    pub kind: AnchorEventKind,
+    // ID of the user who made the change
+    pub actor_id: i32,
+    // State of the anchor before the change, if it existed
+    pub before: Option<Anchor>,
+    // State of the anchor after the change, if it still existed
+    pub after: Option<Anchor>,
+    // Date of the change
+    pub created_at: String,
+}
+
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl Anchor {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::Anchor.global_id(self.id)
+    }
+
     pub fn title(&self) -> &str {
         self.title.as_str()
     }
@@ -72,30 +179,32 @@ impl Anchor {
         self.position_left
     }
 
-    pub async fn page(&self, context: &Context) -> Page {
+    pub async fn page(&self, context: &Context) -> Result<Page, GatewayError> {
         context
-            .page_data
-            .as_ref()
-            .unwrap()
+            .page_data()?
             .pages_by_id(self.page_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("page", self.page_id))
     }
 
-    pub async fn track(&self, context: &Context) -> Track {
+    pub async fn track(&self, context: &Context) -> Result<Track, GatewayError> {
         context
-            .track_data
-            .as_ref()
-            .unwrap()
+            .track_data()?
             .tracks_by_id(self.track_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("track", self.track_id))
     }
 
-    pub fn created_at(&self) -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339(self.created_at.as_str()).unwrap()
+    pub fn created_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.created_at)
     }
 
-    pub fn updated_at(&self) -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339(self.updated_at.as_str()).unwrap()
+    pub fn updated_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.updated_at)
+    }
+
+    pub async fn history(&self, context: &Context) -> Result<Vec<AnchorEvent>, GatewayError> {
+        context.anchor_data()?.anchor_history(self.id).await
     }
 }
 
@@ -114,3 +223,111 @@ impl From<schema::courses::Anchor> for Anchor {
         }
     }
 }
+
+#[juniper::graphql_object(Context = Context)]
+impl AnchorChange {
+    pub fn kind(&self) -> AnchorChangeKind {
+        self.kind
+    }
+
+    pub fn anchor(&self) -> &Anchor {
+        &self.anchor
+    }
+}
+
+impl TryFrom<schema::courses::AnchorChange> for AnchorChange {
+    type Error = GatewayError;
+
+    fn try_from(x: schema::courses::AnchorChange) -> Result<Self, GatewayError> {
+        Ok(Self {
+            kind: match schema::courses::AnchorChangeKind::from_i32(x.kind) {
+                Some(schema::courses::AnchorChangeKind::Deleted) => AnchorChangeKind::Deleted,
+                _ => AnchorChangeKind::Upserted,
+            },
+            anchor: x
+                .anchor
+                .ok_or_else(|| GatewayError::missing_field("anchor"))?
+                .into(),
+        })
+    }
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl AnchorEvent {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn kind(&self) -> AnchorEventKind {
+        self.kind
+    }
+
+    pub async fn actor(&self, context: &Context) -> Result<User, GatewayError> {
+        context
+            .user_data()?
+            .user_by_id(self.actor_id)
+            .await?
+            .ok_or_else(|| GatewayError::not_found("user", self.actor_id))
+    }
+
+    pub fn before(&self) -> Option<&Anchor> {
+        self.before.as_ref()
+    }
+
+    pub fn after(&self) -> Option<&Anchor> {
+        self.after.as_ref()
+    }
+
+    pub fn created_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.created_at)
+    }
+}
+
+impl From<schema::courses::AnchorEvent> for AnchorEvent {
+    fn from(x: schema::courses::AnchorEvent) -> Self {
+        Self {
+            id: x.id,
+            kind: match schema::courses::AnchorEventKind::from_i32(x.kind) {
+                Some(schema::courses::AnchorEventKind::Move) => AnchorEventKind::Move,
+                Some(schema::courses::AnchorEventKind::Promote) => AnchorEventKind::Promote,
+                Some(schema::courses::AnchorEventKind::Delete) => AnchorEventKind::Delete,
+                Some(schema::courses::AnchorEventKind::Restore) => AnchorEventKind::Restore,
+                _ => AnchorEventKind::Create,
+            },
+            actor_id: x.actor,
+            before: x.before.map(Anchor::from),
+            after: x.after.map(Anchor::from),
+            created_at: x.created_at,
+        }
+    }
+}
+
+impl From<AnchorFileFormat> for schema::courses::AnchorFileFormat {
+    fn from(x: AnchorFileFormat) -> Self {
+        match x {
+            AnchorFileFormat::Json => Self::Json,
+            AnchorFileFormat::Webvtt => Self::Webvtt,
+            AnchorFileFormat::Lrc => Self::Lrc,
+        }
+    }
+}
+
+impl From<schema::courses::AnchorFile> for AnchorFile {
+    fn from(x: schema::courses::AnchorFile) -> Self {
+        Self {
+            file_name: x.file_name,
+            content: x.content,
+            track_id: x.track_id,
+        }
+    }
+}
+
+impl From<NewAnchorFile> for schema::courses::AnchorFile {
+    fn from(x: NewAnchorFile) -> Self {
+        Self {
+            file_name: "".to_owned(),
+            content: x.content,
+            track_id: x.track_id.unwrap_or(0),
+        }
+    }
+}
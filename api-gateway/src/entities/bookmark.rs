@@ -1,7 +1,12 @@
 use std::convert::From;
 
-use super::{Document, Page};
-use crate::graphql::schema::Context;
+use juniper::ID;
+
+use super::{
+    node::{NodeKind, NodeValue},
+    Document, Page,
+};
+use crate::{errors::GatewayError, graphql::schema::Context};
 
 #[derive(Debug, Clone)]
 /// Bookmark for a document
@@ -23,35 +28,39 @@ pub struct DeleteBookmarkResponse {
     pub success: bool,
 }
 
-#[juniper::graphql_object(Context = Context)]
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl Bookmark {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::Bookmark.global_id(self.id)
+    }
+
     pub fn title(&self) -> &str {
         self.title.as_str()
     }
 
-    pub async fn page(&self, context: &Context) -> Page {
+    pub async fn page(&self, context: &Context) -> Result<Page, GatewayError> {
         context
-            .page_data
-            .as_ref()
-            .unwrap()
+            .page_data()?
             .pages_by_id(self.page_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("page", self.page_id))
     }
 
-    pub async fn document(&self, context: &Context) -> Document {
+    pub async fn document(&self, context: &Context) -> Result<Document, GatewayError> {
         context
-            .document_data
-            .as_ref()
-            .unwrap()
+            .document_data()?
             .documents_by_id(self.document_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("document", self.document_id))
     }
 }
 
+connection!(BookmarkConnection, BookmarkEdge, Bookmark);
+
 impl From<schema::courses::Bookmark> for Bookmark {
     fn from(x: schema::courses::Bookmark) -> Bookmark {
         Bookmark {
@@ -0,0 +1,143 @@
+use crate::errors::GatewayError;
+
+const CURSOR_PREFIX: &str = "offset:";
+
+/// Most items returned by one page of a connection, whatever `first` asks for
+pub const MAX_PAGE_SIZE: i32 = 100;
+
+/// Declares the edge and connection types of a cursor-paginated list of `$node`, where each edge
+/// is a node along with its cursor
+macro_rules! connection {
+    ($connection:ident, $edge:ident, $node:ident) => {
+        #[derive(Debug, Clone)]
+        pub struct $edge {
+            pub cursor: String,
+            pub node: $node,
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct $connection {
+            // Nodes in this slice of the list
+            pub edges: Vec<$edge>,
+            // Position of the slice within the list
+            pub page_info: crate::entities::PageInfo,
+            // Number of nodes in the full list
+            pub total_count: i32,
+        }
+
+        impl $connection {
+            pub fn new(nodes: Vec<$node>, offset: i32, total: i32) -> Self {
+                Self {
+                    page_info: crate::entities::PageInfo::new(offset, nodes.len() as i32, total),
+                    edges: nodes
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, node)| $edge {
+                            cursor: crate::entities::connection::encode_cursor(
+                                offset.saturating_add(i as i32),
+                            ),
+                            node,
+                        })
+                        .collect(),
+                    total_count: total,
+                }
+            }
+        }
+
+        #[juniper::graphql_object(Context = crate::graphql::schema::Context)]
+        impl $edge {
+            pub fn cursor(&self) -> &str {
+                self.cursor.as_str()
+            }
+
+            pub fn node(&self) -> &$node {
+                &self.node
+            }
+        }
+
+        #[juniper::graphql_object(Context = crate::graphql::schema::Context)]
+        impl $connection {
+            pub fn edges(&self) -> &Vec<$edge> {
+                &self.edges
+            }
+
+            pub fn page_info(&self) -> &crate::entities::PageInfo {
+                &self.page_info
+            }
+
+            pub fn total_count(&self) -> i32 {
+                self.total_count
+            }
+        }
+    };
+}
+
+#[derive(juniper::GraphQLObject, Debug, Clone)]
+/// Position of a connection's edges within the full list
+pub struct PageInfo {
+    // Whether there are items after the last edge
+    pub has_next_page: bool,
+    // Whether there are items before the first edge
+    pub has_previous_page: bool,
+    // Cursor of the first edge
+    pub start_cursor: Option<String>,
+    // Cursor of the last edge
+    pub end_cursor: Option<String>,
+}
+
+impl PageInfo {
+    /// Page info for `count` edges beginning at `offset` in a list of `total` items
+    pub fn new(offset: i32, count: i32, total: i32) -> Self {
+        Self {
+            has_next_page: offset.saturating_add(count) < total,
+            has_previous_page: offset > 0,
+            start_cursor: if count > 0 {
+                Some(encode_cursor(offset))
+            } else {
+                None
+            },
+            end_cursor: if count > 0 {
+                Some(encode_cursor(offset.saturating_add(count - 1)))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Opaque cursor for the item at the given position in a list
+pub fn encode_cursor(offset: i32) -> String {
+    base64::encode(format!("{}{}", CURSOR_PREFIX, offset))
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<i32, GatewayError> {
+    base64::decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|decoded| {
+            if decoded.starts_with(CURSOR_PREFIX) {
+                decoded[CURSOR_PREFIX.len()..].parse::<i32>().ok()
+            } else {
+                None
+            }
+        })
+        .filter(|offset| *offset >= 0)
+        .ok_or_else(|| GatewayError::BadCursor(cursor.to_owned()))
+}
+
+/// Limit and offset to request for the `first` items following the `after` cursor, asking for
+/// no more than a page's worth
+pub fn window(first: i32, after: Option<String>) -> Result<(i32, i32), GatewayError> {
+    if first < 0 {
+        return Err(GatewayError::InvalidArgument(
+            "first must not be negative".to_owned(),
+        ));
+    }
+    let offset = match after {
+        Some(cursor) => decode_cursor(&cursor)?
+            .checked_add(1)
+            .ok_or(GatewayError::BadCursor(cursor))?,
+        None => 0,
+    };
+    Ok((first.min(MAX_PAGE_SIZE), offset))
+}
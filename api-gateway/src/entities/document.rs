@@ -1,8 +1,17 @@
 use chrono::{DateTime, FixedOffset};
+use juniper::ID;
 use std::convert::From;
 
-use super::{Bookmark, Page, Track};
-use crate::graphql::schema::Context;
+use super::{
+    connection::{window, MAX_PAGE_SIZE},
+    node::{NodeKind, NodeValue},
+    parse_timestamp, Bookmark, BookmarkConnection, Page, PageConnection, Progress, TextMatch,
+    Track, TrackConnection, UserNote,
+};
+use crate::{errors::GatewayError, graphql::schema::Context};
+
+/// Number of text matches returned by a search when no limit is given
+pub const DEFAULT_TEXT_MATCHES: i32 = 20;
 
 #[derive(Debug, Clone)]
 /// A synchrotron course document
@@ -15,57 +24,215 @@ pub struct Document {
     pub created_at: String,
     // Timestamp for when the document was last updated
     pub updated_at: String,
+    // Language that the document teaches, empty if unspecified
+    pub language: String,
+    // Level of the document, empty if unspecified
+    pub level: String,
+    // Short description of the document
+    pub description: String,
+    // Whether the document's media is only served through signed URLs
+    pub private: bool,
+}
+
+#[derive(juniper::GraphQLEnum, Debug, Clone, Copy)]
+pub enum DocumentSort {
+    // Best title and description matches first
+    Relevance,
+    // Most recently updated first
+    RecentlyUpdated,
+    // Alphabetical by title
+    Title,
 }
 
-#[juniper::graphql_object(Context = Context)]
+impl Default for DocumentSort {
+    fn default() -> Self {
+        Self::Relevance
+    }
+}
+
+#[derive(juniper::GraphQLInputObject, Debug, Clone, Default)]
+/// Filters for searching documents
+pub struct DocumentFilters {
+    // Only include documents in this language
+    pub language: Option<String>,
+    // Only include documents of this level
+    pub level: Option<String>,
+    // Order of the results, by relevance if omitted
+    pub sort: Option<DocumentSort>,
+}
+
+#[derive(juniper::GraphQLObject, Debug, Clone)]
+/// Response to deleting a document
+pub struct DeleteDocumentResponse {
+    // Success flag
+    pub success: bool,
+}
+
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl Document {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::Document.global_id(self.id)
+    }
+
     pub fn title(&self) -> &str {
         self.title.as_str()
     }
 
-    pub fn created_at(&self) -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339(self.created_at.as_str()).unwrap()
+    pub fn language(&self) -> &str {
+        self.language.as_str()
     }
 
-    pub fn updated_at(&self) -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339(self.updated_at.as_str()).unwrap()
+    pub fn level(&self) -> &str {
+        self.level.as_str()
     }
 
-    pub async fn bookmarks(&self, limit: i32, offset: i32, context: &Context) -> Vec<Bookmark> {
-        context
-            .bookmark_data
-            .as_ref()
-            .unwrap()
+    pub fn description(&self) -> &str {
+        self.description.as_str()
+    }
+
+    pub fn private(&self) -> bool {
+        self.private
+    }
+
+    pub fn created_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.created_at)
+    }
+
+    pub fn updated_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.updated_at)
+    }
+
+    pub async fn bookmarks(
+        &self,
+        limit: i32,
+        offset: i32,
+        context: &Context,
+    ) -> Result<Vec<Bookmark>, GatewayError> {
+        Ok(context
+            .bookmark_data()?
+            .document_bookmarks(self.id, limit, offset)
+            .await?
+            .0)
+    }
+
+    pub async fn bookmarks_connection(
+        &self,
+        first: i32,
+        after: Option<String>,
+        context: &Context,
+    ) -> Result<BookmarkConnection, GatewayError> {
+        let (limit, offset) = window(first, after)?;
+        let (bookmarks, total) = context
+            .bookmark_data()?
             .document_bookmarks(self.id, limit, offset)
+            .await?;
+        Ok(BookmarkConnection::new(bookmarks, offset, total))
+    }
+
+    pub async fn pages(
+        &self,
+        limit: i32,
+        offset: i32,
+        context: &Context,
+    ) -> Result<Vec<Page>, GatewayError> {
+        Ok(context
+            .page_data()?
+            .document_pages(self.id, limit, offset)
+            .await?
+            .0)
+    }
+
+    pub async fn pages_connection(
+        &self,
+        first: i32,
+        after: Option<String>,
+        context: &Context,
+    ) -> Result<PageConnection, GatewayError> {
+        let (limit, offset) = window(first, after)?;
+        let (pages, total) = context
+            .page_data()?
+            .document_pages(self.id, limit, offset)
+            .await?;
+        Ok(PageConnection::new(pages, offset, total))
+    }
+
+    pub async fn tracks(
+        &self,
+        limit: i32,
+        offset: i32,
+        context: &Context,
+    ) -> Result<Vec<Track>, GatewayError> {
+        Ok(context
+            .track_data()?
+            .document_tracks(self.id, limit, offset)
+            .await?
+            .0)
+    }
+
+    pub async fn tracks_connection(
+        &self,
+        first: i32,
+        after: Option<String>,
+        context: &Context,
+    ) -> Result<TrackConnection, GatewayError> {
+        let (limit, offset) = window(first, after)?;
+        let (tracks, total) = context
+            .track_data()?
+            .document_tracks(self.id, limit, offset)
+            .await?;
+        Ok(TrackConnection::new(tracks, offset, total))
+    }
+
+    pub async fn my_progress(&self, context: &Context) -> Result<Option<Progress>, GatewayError> {
+        if context.user.is_none() {
+            return Ok(None);
+        }
+
+        context
+            .document_data()?
+            .progress(self.id, context.token.clone())
             .await
-            .unwrap()
     }
 
-    pub async fn pages(&self, limit: i32, offset: i32, context: &Context) -> Vec<Page> {
+    pub async fn my_notes(&self, context: &Context) -> Result<Vec<UserNote>, GatewayError> {
+        // Notes are private, so there's nothing to show anonymous users
+        if context.user.is_none() {
+            return Ok(vec![]);
+        }
+
         context
-            .page_data
-            .as_ref()
-            .unwrap()
-            .document_pages(self.id, limit, offset)
+            .user_note_data()?
+            .document_user_notes(context.token.clone(), self.id)
             .await
-            .unwrap()
     }
 
-    pub async fn tracks(&self, limit: i32, offset: i32, context: &Context) -> Vec<Track> {
+    pub async fn search(
+        &self,
+        query: String,
+        limit: Option<i32>,
+        context: &Context,
+    ) -> Result<Vec<TextMatch>, GatewayError> {
+        let limit = limit.unwrap_or(DEFAULT_TEXT_MATCHES);
+        if limit < 0 {
+            return Err(GatewayError::InvalidArgument(
+                "limit must not be negative".to_owned(),
+            ));
+        }
+
+        // Capped like the paginated lists, since every match is resolved along with its anchor
         context
-            .track_data
-            .as_ref()
-            .unwrap()
-            .document_tracks(self.id, limit, offset)
+            .document_data()?
+            .search_document_text(self.id, query, limit.min(MAX_PAGE_SIZE))
             .await
-            .unwrap()
     }
 }
 
+connection!(DocumentConnection, DocumentEdge, Document);
+
 impl From<schema::courses::Document> for Document {
     fn from(x: schema::courses::Document) -> Document {
         Document {
@@ -73,6 +240,20 @@ impl From<schema::courses::Document> for Document {
             title: x.title,
             created_at: x.created_at,
             updated_at: x.updated_at,
+            language: x.language,
+            level: x.level,
+            description: x.description,
+            private: x.private,
+        }
+    }
+}
+
+impl From<DocumentSort> for schema::courses::DocumentSort {
+    fn from(x: DocumentSort) -> Self {
+        match x {
+            DocumentSort::Relevance => Self::Relevance,
+            DocumentSort::RecentlyUpdated => Self::RecentlyUpdated,
+            DocumentSort::Title => Self::Title,
         }
     }
 }
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use schema::shared::{User, UserRole};
+
+use crate::{errors::GatewayError, graphql::schema::Context};
+
+/// How long a signed URL for private media stays valid
+const SIGNED_URL_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Resolves the URL that a document's media is served from, signing it if the document is private
+pub async fn media_url(
+    context: &Context,
+    document_id: i32,
+    path: &str,
+) -> Result<String, GatewayError> {
+    let storage = context.storage()?;
+    if check_media_access(context, document_id).await? {
+        Ok(storage.signed_url(path, SIGNED_URL_TTL))
+    } else {
+        Ok(storage.url(path))
+    }
+}
+
+/// Resolves the storage key of a document's media, which is withheld from users who may not
+/// read the document's media
+pub async fn media_path(
+    context: &Context,
+    document_id: i32,
+    path: &str,
+) -> Result<String, GatewayError> {
+    check_media_access(context, document_id).await?;
+    Ok(path.to_owned())
+}
+
+/// Checks that the user may read the media of a document, returning whether the document is
+/// private
+async fn check_media_access(context: &Context, document_id: i32) -> Result<bool, GatewayError> {
+    let document = context
+        .document_data()?
+        .documents_by_id(document_id)
+        .await?
+        .ok_or_else(|| GatewayError::not_found("document", document_id))?;
+
+    if document.private && !can_read_private(context.user.as_ref()) {
+        return Err(GatewayError::PermissionDenied(
+            "Only moderators and administrators may access the media of a private document."
+                .to_owned(),
+        ));
+    }
+    Ok(document.private)
+}
+
+/// Whether a user may read the media of private documents. There are no per-course enrollments,
+/// so private documents are limited to moderators and administrators.
+fn can_read_private(user: Option<&User>) -> bool {
+    user.map(|user| {
+        user.role == UserRole::Moderator as i32 || user.role == UserRole::Administrator as i32
+    })
+    .unwrap_or(false)
+}
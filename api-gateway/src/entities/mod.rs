@@ -1,21 +1,61 @@
+use chrono::{DateTime, FixedOffset};
+
+use crate::errors::GatewayError;
+
+// Declares the `connection!` macro used by the entities below it
+#[macro_use]
+mod connection;
+
 mod anchor;
 mod bookmark;
 mod document;
+mod media;
+mod node;
 mod page;
+mod progress;
+mod text_match;
+mod text_region;
 mod track;
 mod user;
 mod user_anchor;
+mod user_note;
+
+pub use user::{LoginResponse, LogoutResponse, NewUser, UpdateUserRoleResponse, User, UserRole};
+
+pub use connection::{window, PageInfo};
+
+pub use document::{
+    DeleteDocumentResponse, Document, DocumentConnection, DocumentEdge, DocumentFilters,
+    DocumentSort, DEFAULT_TEXT_MATCHES,
+};
+
+pub use bookmark::{Bookmark, BookmarkConnection, BookmarkEdge, DeleteBookmarkResponse};
+
+pub use node::{load_node, Node, NodeKind, NodeValue};
+
+pub use page::{NewPage, Page, PageConnection, PageEdge};
+
+pub use progress::{Progress, SaveProgress};
 
-pub use user::{LoginResponse, NewUser, UpdateUserRoleResponse, User, UserRole};
+pub use text_match::TextMatch;
 
-pub use document::Document;
+pub use text_region::TextRegion;
 
-pub use bookmark::{Bookmark, DeleteBookmarkResponse};
+pub use track::{NewTrack, Track, TrackConnection, TrackEdge};
 
-pub use page::Page;
+pub use anchor::{
+    Anchor, AnchorChange, AnchorChangeKind, AnchorEvent, AnchorEventKind, AnchorFile,
+    AnchorFileFormat, CreateAnchor, DeleteAnchorResponse, ImportAnchorsResponse, NewAnchorFile,
+    UpdateAnchor,
+};
 
-pub use track::Track;
+pub use user_anchor::{CreateUserAnchor, DeleteUserAnchorResponse, UpdateUserAnchor, UserAnchor};
 
-pub use anchor::{Anchor, CreateAnchor, DeleteAnchorResponse};
+pub use user_note::{CreateUserNote, DeleteUserNoteResponse, UpdateUserNote, UserNote};
 
-pub use user_anchor::{CreateUserAnchor, DeleteUserAnchorResponse, UserAnchor};
+/// Reads a timestamp sent by one of the services
+pub(crate) fn parse_timestamp(timestamp: &str) -> Result<DateTime<FixedOffset>, GatewayError> {
+    DateTime::parse_from_rfc3339(timestamp).map_err(|err| {
+        GatewayError::Internal(format!("Invalid timestamp \"{}\": {}", timestamp, err))
+    })
+}
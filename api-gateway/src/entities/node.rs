@@ -0,0 +1,165 @@
+use juniper::{graphql_interface, ID};
+
+use super::{Anchor, Bookmark, Document, Page, Track, User, UserAnchor};
+use crate::{errors::GatewayError, graphql::schema::Context};
+
+#[graphql_interface(for = [Anchor, Bookmark, Document, Page, Track, User, UserAnchor], context = Context)]
+/// Entity that can be refetched by its global ID. Unlike Relay's `Node`, the global ID is named
+/// `globalId`, because `id` already holds each entity's integer ID that clients rely on. Clients
+/// that expect Relay's convention need to alias it, as in `id: globalId`.
+pub trait Node {
+    // ID that is unique across every kind of entity
+    fn global_id(&self) -> ID;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Kind of entity that a global ID refers to
+pub enum NodeKind {
+    Anchor,
+    Bookmark,
+    Document,
+    Page,
+    Track,
+    User,
+    UserAnchor,
+}
+
+impl NodeKind {
+    const ALL: [NodeKind; 7] = [
+        NodeKind::Anchor,
+        NodeKind::Bookmark,
+        NodeKind::Document,
+        NodeKind::Page,
+        NodeKind::Track,
+        NodeKind::User,
+        NodeKind::UserAnchor,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            NodeKind::Anchor => "Anchor",
+            NodeKind::Bookmark => "Bookmark",
+            NodeKind::Document => "Document",
+            NodeKind::Page => "Page",
+            NodeKind::Track => "Track",
+            NodeKind::User => "User",
+            NodeKind::UserAnchor => "UserAnchor",
+        }
+    }
+
+    /// Opaque global ID of the entity of this kind with the given ID
+    pub fn global_id(self, id: i32) -> ID {
+        ID::new(base64::encode(format!("{}:{}", self.name(), id)))
+    }
+}
+
+/// Kind and ID of the entity that a global ID refers to
+pub fn decode_global_id(global_id: &ID) -> Result<(NodeKind, i32), GatewayError> {
+    let global_id: &str = global_id;
+    base64::decode(global_id)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|decoded| {
+            let (name, id) = decoded.split_at(decoded.find(':')?);
+            let kind = NodeKind::ALL.iter().find(|kind| kind.name() == name)?;
+            Some((*kind, id[1..].parse::<i32>().ok()?))
+        })
+        .ok_or_else(|| {
+            GatewayError::InvalidArgument(format!("invalid global ID \"{}\"", global_id))
+        })
+}
+
+/// Loads the entity that a global ID refers to, or nothing if it doesn't exist
+pub async fn load_node(
+    context: &Context,
+    global_id: &ID,
+) -> Result<Option<NodeValue>, GatewayError> {
+    let (kind, id) = decode_global_id(global_id)?;
+
+    Ok(match kind {
+        NodeKind::Anchor => context
+            .anchor_data()?
+            .anchors_by_id(id)
+            .await?
+            .map(NodeValue::from),
+        NodeKind::Bookmark => context
+            .bookmark_data()?
+            .bookmarks_by_id(id)
+            .await?
+            .map(NodeValue::from),
+        NodeKind::Document => context
+            .document_data()?
+            .documents_by_id(id)
+            .await?
+            .map(NodeValue::from),
+        NodeKind::Page => context
+            .page_data()?
+            .pages_by_id(id)
+            .await?
+            .map(NodeValue::from),
+        NodeKind::Track => context
+            .track_data()?
+            .tracks_by_id(id)
+            .await?
+            .map(NodeValue::from),
+        NodeKind::User => context
+            .user_data()?
+            .user_by_id(id)
+            .await?
+            .map(NodeValue::from),
+        NodeKind::UserAnchor => context
+            .user_anchor_data()?
+            .user_anchors_by_id(id)
+            .await?
+            .map(NodeValue::from),
+    })
+}
+
+#[graphql_interface]
+impl Node for Anchor {
+    fn global_id(&self) -> ID {
+        NodeKind::Anchor.global_id(self.id)
+    }
+}
+
+#[graphql_interface]
+impl Node for Bookmark {
+    fn global_id(&self) -> ID {
+        NodeKind::Bookmark.global_id(self.id)
+    }
+}
+
+#[graphql_interface]
+impl Node for Document {
+    fn global_id(&self) -> ID {
+        NodeKind::Document.global_id(self.id)
+    }
+}
+
+#[graphql_interface]
+impl Node for Page {
+    fn global_id(&self) -> ID {
+        NodeKind::Page.global_id(self.id)
+    }
+}
+
+#[graphql_interface]
+impl Node for Track {
+    fn global_id(&self) -> ID {
+        NodeKind::Track.global_id(self.id)
+    }
+}
+
+#[graphql_interface]
+impl Node for User {
+    fn global_id(&self) -> ID {
+        NodeKind::User.global_id(self.id)
+    }
+}
+
+#[graphql_interface]
+impl Node for UserAnchor {
+    fn global_id(&self) -> ID {
+        NodeKind::UserAnchor.global_id(self.id)
+    }
+}
@@ -1,7 +1,13 @@
 use std::convert::From;
 
-use super::{Anchor, Document, UserAnchor};
-use crate::graphql::schema::Context;
+use juniper::ID;
+
+use super::{
+    media::{media_path, media_url},
+    node::{NodeKind, NodeValue},
+    Anchor, Document, TextRegion, UserAnchor,
+};
+use crate::{errors::GatewayError, graphql::schema::Context};
 
 #[derive(Debug, Clone)]
 /// Page of a document
@@ -20,18 +26,37 @@ pub struct Page {
     pub document_id: i32,
 }
 
-#[juniper::graphql_object(Context = Context)]
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+// Adding a page image to a document
+pub struct NewPage {
+    // Path to the image for the page
+    pub image_path: String,
+    // Width of the image in pixels
+    pub width: i32,
+    // Height of the image in pixels
+    pub height: i32,
+}
+
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl Page {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::Page.global_id(self.id)
+    }
+
     pub fn page_number(&self) -> i32 {
         self.page_number
     }
 
-    pub fn image_path(&self) -> &str {
-        self.image_path.as_str()
+    pub async fn image_path(&self, context: &Context) -> Result<String, GatewayError> {
+        media_path(context, self.document_id, &self.image_path).await
+    }
+
+    pub async fn image_url(&self, context: &Context) -> Result<String, GatewayError> {
+        media_url(context, self.document_id, &self.image_path).await
     }
 
     pub fn aspect_ratio(&self) -> f64 {
@@ -42,34 +67,29 @@ impl Page {
         self.height
     }
 
-    pub async fn document(&self, context: &Context) -> Document {
+    pub async fn document(&self, context: &Context) -> Result<Document, GatewayError> {
         context
-            .document_data
-            .as_ref()
-            .unwrap()
+            .document_data()?
             .documents_by_id(self.document_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("document", self.document_id))
     }
 
-    pub async fn anchors(&self, context: &Context) -> Vec<Anchor> {
-        context
-            .anchor_data
-            .as_ref()
-            .unwrap()
-            .page_anchors(self.id)
-            .await
+    pub async fn anchors(&self, context: &Context) -> Result<Vec<Anchor>, GatewayError> {
+        context.anchor_data()?.page_anchors(self.id).await
     }
 
-    pub async fn user_anchors(&self, context: &Context) -> Vec<UserAnchor> {
-        context
-            .user_anchor_data
-            .as_ref()
-            .unwrap()
-            .page_user_anchors(self.id)
-            .await
+    pub async fn user_anchors(&self, context: &Context) -> Result<Vec<UserAnchor>, GatewayError> {
+        context.user_anchor_data()?.page_user_anchors(self.id).await
+    }
+
+    pub async fn text_regions(&self, context: &Context) -> Result<Vec<TextRegion>, GatewayError> {
+        context.page_data()?.page_text_regions(self.id).await
     }
 }
 
+connection!(PageConnection, PageEdge, Page);
+
 impl From<schema::courses::Page> for Page {
     fn from(x: schema::courses::Page) -> Page {
         Page {
@@ -82,3 +102,13 @@ impl From<schema::courses::Page> for Page {
         }
     }
 }
+
+impl From<NewPage> for schema::courses::NewPage {
+    fn from(x: NewPage) -> schema::courses::NewPage {
+        schema::courses::NewPage {
+            image_path: x.image_path,
+            width: x.width,
+            height: x.height,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::convert::From;
+
+use chrono::{DateTime, FixedOffset};
+
+use super::{parse_timestamp, Document, Page, Track};
+use crate::{errors::GatewayError, graphql::schema::Context};
+
+#[derive(Debug, Clone)]
+/// Where a user left off listening to a document
+pub struct Progress {
+    // ID of the document
+    pub document_id: i32,
+    // ID of the track that was last played
+    pub track_id: i32,
+    // Point in the track that was last played
+    pub track_time: f64,
+    // ID of the page that was last viewed
+    pub page_id: i32,
+    // Percentage of the document's audio that has been listened through
+    pub completion: f64,
+    // Timestamp for when the progress was last saved
+    pub updated_at: String,
+}
+
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+pub struct SaveProgress {
+    // ID of the document being listened to
+    pub document_id: i32,
+    // ID of the track being played
+    pub track_id: i32,
+    // Point in the track being played
+    pub track_time: f64,
+    // ID of the page being viewed
+    pub page_id: i32,
+    // Percentage of the document's audio that has been listened through
+    pub completion: f64,
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl Progress {
+    pub async fn document(&self, context: &Context) -> Result<Document, GatewayError> {
+        context
+            .document_data()?
+            .documents_by_id(self.document_id)
+            .await?
+            .ok_or_else(|| GatewayError::not_found("document", self.document_id))
+    }
+
+    pub async fn track(&self, context: &Context) -> Result<Track, GatewayError> {
+        context
+            .track_data()?
+            .tracks_by_id(self.track_id)
+            .await?
+            .ok_or_else(|| GatewayError::not_found("track", self.track_id))
+    }
+
+    pub fn track_time(&self) -> f64 {
+        self.track_time
+    }
+
+    pub async fn page(&self, context: &Context) -> Result<Page, GatewayError> {
+        context
+            .page_data()?
+            .pages_by_id(self.page_id)
+            .await?
+            .ok_or_else(|| GatewayError::not_found("page", self.page_id))
+    }
+
+    pub fn completion(&self) -> f64 {
+        self.completion
+    }
+
+    pub fn updated_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.updated_at)
+    }
+}
+
+impl From<schema::courses::Progress> for Progress {
+    fn from(x: schema::courses::Progress) -> Self {
+        Self {
+            document_id: x.document_id,
+            track_id: x.track_id,
+            track_time: x.track_time as f64,
+            page_id: x.page_id,
+            completion: x.completion as f64,
+            updated_at: x.updated_at,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::convert::From;
+
+use super::{Anchor, Page, Track};
+use crate::{errors::GatewayError, graphql::schema::Context};
+
+#[derive(Debug, Clone)]
+/// Place in a document's text that matched a search
+pub struct TextMatch {
+    // ID of the page the match is on
+    pub page_id: i32,
+    // Number of the page the match is on
+    pub page_number: i32,
+    // ID of the text region that matched
+    pub text_region_id: i32,
+    // Text of the region that matched
+    pub content: String,
+    // Y position of the match on its page
+    pub position_top: f64,
+    // X position of the match on its page
+    pub position_left: f64,
+    // ID of the nearest anchor on the page, if the page has any
+    pub anchor_id: Option<i32>,
+    // ID of the track that the nearest anchor cues
+    pub track_id: Option<i32>,
+    // Point in the track that the nearest anchor cues
+    pub track_time: Option<f64>,
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl TextMatch {
+    pub fn page_id(&self) -> i32 {
+        self.page_id
+    }
+
+    pub fn page_number(&self) -> i32 {
+        self.page_number
+    }
+
+    pub async fn page(&self, context: &Context) -> Result<Page, GatewayError> {
+        context
+            .page_data()?
+            .pages_by_id(self.page_id)
+            .await?
+            .ok_or_else(|| GatewayError::not_found("page", self.page_id))
+    }
+
+    pub fn text_region_id(&self) -> i32 {
+        self.text_region_id
+    }
+
+    pub fn content(&self) -> &str {
+        self.content.as_str()
+    }
+
+    pub fn position_top(&self) -> f64 {
+        self.position_top
+    }
+
+    pub fn position_left(&self) -> f64 {
+        self.position_left
+    }
+
+    pub async fn anchor(&self, context: &Context) -> Result<Option<Anchor>, GatewayError> {
+        match self.anchor_id {
+            Some(id) => context.anchor_data()?.anchors_by_id(id).await,
+            None => Ok(None),
+        }
+    }
+
+    pub async fn track(&self, context: &Context) -> Result<Option<Track>, GatewayError> {
+        match self.track_id {
+            Some(id) => context.track_data()?.tracks_by_id(id).await,
+            None => Ok(None),
+        }
+    }
+
+    pub fn track_time(&self) -> Option<f64> {
+        self.track_time
+    }
+}
+
+impl From<schema::courses::TextMatch> for TextMatch {
+    fn from(x: schema::courses::TextMatch) -> TextMatch {
+        let anchored = x.anchor_id != 0;
+        TextMatch {
+            page_id: x.page_id,
+            page_number: x.page_number,
+            text_region_id: x.text_region_id,
+            content: x.content,
+            position_top: x.position_top as f64,
+            position_left: x.position_left as f64,
+            anchor_id: Some(x.anchor_id).filter(|_| anchored),
+            track_id: Some(x.track_id).filter(|_| anchored),
+            track_time: Some(x.track_time as f64).filter(|_| anchored),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::convert::From;
+
+use crate::graphql::schema::Context;
+
+#[derive(Debug, Clone)]
+/// Line of text recognised on, or transcribed from, a page
+pub struct TextRegion {
+    // ID of the region
+    pub id: i32,
+    // ID of the page the region is on
+    pub page_id: i32,
+    // Text of the region
+    pub content: String,
+    // Y position of the top edge of the region on its page
+    pub position_top: f64,
+    // X position of the left edge of the region on its page
+    pub position_left: f64,
+    // Width of the region, relative to the page
+    pub width: f64,
+    // Height of the region, relative to the page
+    pub height: f64,
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl TextRegion {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn page_id(&self) -> i32 {
+        self.page_id
+    }
+
+    pub fn content(&self) -> &str {
+        self.content.as_str()
+    }
+
+    pub fn position_top(&self) -> f64 {
+        self.position_top
+    }
+
+    pub fn position_left(&self) -> f64 {
+        self.position_left
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+}
+
+impl From<schema::courses::TextRegion> for TextRegion {
+    fn from(x: schema::courses::TextRegion) -> TextRegion {
+        TextRegion {
+            id: x.id,
+            page_id: x.page_id,
+            content: x.content,
+            position_top: x.position_top as f64,
+            position_left: x.position_left as f64,
+            width: x.width as f64,
+            height: x.height as f64,
+        }
+    }
+}
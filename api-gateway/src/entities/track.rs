@@ -1,7 +1,13 @@
 use std::convert::From;
 
-use super::Document;
-use crate::graphql::schema::Context;
+use juniper::ID;
+
+use super::{
+    media::{media_path, media_url},
+    node::{NodeKind, NodeValue},
+    Document,
+};
+use crate::{errors::GatewayError, graphql::schema::Context};
 
 #[derive(Debug, Clone)]
 /// Track of a document
@@ -18,12 +24,25 @@ pub struct Track {
     pub document_id: i32,
 }
 
-#[juniper::graphql_object(Context = Context)]
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+// Adding an audio track to a document
+pub struct NewTrack {
+    // Title of the track
+    pub title: String,
+    // Path for the track's audio
+    pub audio_path: String,
+}
+
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl Track {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::Track.global_id(self.id)
+    }
+
     pub fn track_number(&self) -> i32 {
         self.track_number
     }
@@ -32,20 +51,25 @@ impl Track {
         self.title.as_str()
     }
 
-    pub fn audio_path(&self) -> &str {
-        self.audio_path.as_str()
+    pub async fn audio_path(&self, context: &Context) -> Result<String, GatewayError> {
+        media_path(context, self.document_id, &self.audio_path).await
     }
 
-    pub async fn document(&self, context: &Context) -> Document {
+    pub async fn audio_url(&self, context: &Context) -> Result<String, GatewayError> {
+        media_url(context, self.document_id, &self.audio_path).await
+    }
+
+    pub async fn document(&self, context: &Context) -> Result<Document, GatewayError> {
         context
-            .document_data
-            .as_ref()
-            .unwrap()
+            .document_data()?
             .documents_by_id(self.document_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("document", self.document_id))
     }
 }
 
+connection!(TrackConnection, TrackEdge, Track);
+
 impl From<schema::courses::Track> for Track {
     fn from(x: schema::courses::Track) -> Self {
         Self {
@@ -57,3 +81,12 @@ impl From<schema::courses::Track> for Track {
         }
     }
 }
+
+impl From<NewTrack> for schema::courses::NewTrack {
+    fn from(x: NewTrack) -> schema::courses::NewTrack {
+        schema::courses::NewTrack {
+            title: x.title,
+            audio_path: x.audio_path,
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use std::convert::From;
+use std::convert::{From, TryFrom};
 
-use crate::graphql::schema::Context;
+use juniper::ID;
+
+use super::node::{NodeKind, NodeValue};
+use crate::{errors::GatewayError, graphql::schema::Context};
 
 #[derive(Debug, Clone)]
 /// A Microbiome user
@@ -44,27 +47,50 @@ pub struct UpdateUserRoleResponse {
 pub struct LoginResponse {
     // Token
     pub token: String,
+    // Token used to obtain a new token once it expires
+    pub refresh_token: String,
+    // Seconds until the token expires
+    pub expires_in: i32,
     // User
     pub user: User,
 }
 
+#[derive(juniper::GraphQLObject, Debug, Clone)]
+/// Response to logging out
+pub struct LogoutResponse {
+    // Whether the refresh token was revoked
+    pub success: bool,
+}
+
 #[juniper::graphql_object(Context = Context)]
 impl LoginResponse {
     pub fn token(&self) -> &str {
         self.token.as_str()
     }
 
+    pub fn refresh_token(&self) -> &str {
+        self.refresh_token.as_str()
+    }
+
+    pub fn expires_in(&self) -> i32 {
+        self.expires_in
+    }
+
     pub fn user(&self) -> &User {
         &self.user
     }
 }
 
-#[juniper::graphql_object(Context = Context)]
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl User {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::User.global_id(self.id)
+    }
+
     pub fn username(&self) -> &str {
         self.username.as_str()
     }
@@ -87,3 +113,19 @@ impl From<schema::shared::User> for User {
         }
     }
 }
+
+impl TryFrom<schema::users::GetTokenResponse> for LoginResponse {
+    type Error = GatewayError;
+
+    fn try_from(x: schema::users::GetTokenResponse) -> Result<LoginResponse, GatewayError> {
+        Ok(LoginResponse {
+            token: x.token,
+            refresh_token: x.refresh_token,
+            expires_in: x.expires_in as i32,
+            user: x
+                .user
+                .ok_or_else(|| GatewayError::missing_field("user"))?
+                .into(),
+        })
+    }
+}
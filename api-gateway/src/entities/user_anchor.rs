@@ -1,9 +1,13 @@
 use std::convert::From;
 
 use chrono::{DateTime, FixedOffset};
+use juniper::ID;
 
-use super::{Page, Track, User};
-use crate::graphql::schema::Context;
+use super::{
+    node::{NodeKind, NodeValue},
+    parse_timestamp, Page, Track, User,
+};
+use crate::{errors::GatewayError, graphql::schema::Context};
 
 #[derive(Debug, Clone)]
 /// UserAnchor for a page
@@ -46,18 +50,40 @@ pub struct CreateUserAnchor {
     pub track_id: i32,
 }
 
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+pub struct UpdateUserAnchor {
+    // ID of the anchor to update
+    pub id: i32,
+    // New title for the anchor
+    pub title: String,
+    // Track time that the anchor will point to
+    pub track_time: f64,
+    // Y position of the anchor on page
+    pub position_top: f64,
+    // X position of the anchor on page
+    pub position_left: f64,
+    // ID of the page for the anchor
+    pub page_id: i32,
+    // Track for the anchor
+    pub track_id: i32,
+}
+
 #[derive(juniper::GraphQLObject, Debug, Clone)]
 pub struct DeleteUserAnchorResponse {
     // Indicates whether deletion was successful
     pub success: bool,
 }
 
-#[juniper::graphql_object(Context = Context)]
+#[juniper::graphql_object(Context = Context, impl = NodeValue)]
 impl UserAnchor {
     pub fn id(&self) -> i32 {
         self.id
     }
 
+    pub fn global_id(&self) -> ID {
+        NodeKind::UserAnchor.global_id(self.id)
+    }
+
     pub fn title(&self) -> &str {
         self.title.as_str()
     }
@@ -74,39 +100,36 @@ impl UserAnchor {
         self.position_left
     }
 
-    pub async fn page(&self, context: &Context) -> Page {
+    pub async fn page(&self, context: &Context) -> Result<Page, GatewayError> {
         context
-            .page_data
-            .as_ref()
-            .unwrap()
+            .page_data()?
             .pages_by_id(self.page_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("page", self.page_id))
     }
 
-    pub async fn track(&self, context: &Context) -> Track {
+    pub async fn track(&self, context: &Context) -> Result<Track, GatewayError> {
         context
-            .track_data
-            .as_ref()
-            .unwrap()
+            .track_data()?
             .tracks_by_id(self.track_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("track", self.track_id))
     }
 
-    pub fn created_at(&self) -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339(self.created_at.as_str()).unwrap()
+    pub fn created_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.created_at)
     }
 
-    pub fn updated_at(&self) -> DateTime<FixedOffset> {
-        DateTime::parse_from_rfc3339(self.updated_at.as_str()).unwrap()
+    pub fn updated_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.updated_at)
     }
 
-    pub async fn owner(&self, context: &Context) -> User {
+    pub async fn owner(&self, context: &Context) -> Result<User, GatewayError> {
         context
-            .user_data
-            .as_ref()
-            .unwrap()
+            .user_data()?
             .user_by_id(self.owner_id)
-            .await
+            .await?
+            .ok_or_else(|| GatewayError::not_found("user", self.owner_id))
     }
 }
 
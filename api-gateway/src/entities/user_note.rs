@@ -0,0 +1,144 @@
+use std::convert::From;
+
+use chrono::{DateTime, FixedOffset};
+
+use super::{parse_timestamp, Document, Page, Track};
+use crate::{errors::GatewayError, graphql::schema::Context};
+
+#[derive(Debug, Clone)]
+/// Private note of a user for a document
+pub struct UserNote {
+    // ID of the note
+    pub id: i32,
+    // ID of the document the note belongs to
+    pub document_id: i32,
+    // Text of the note
+    pub content: String,
+    // ID of the page the note is pinned to, if any
+    pub page_id: Option<i32>,
+    // Y position of the note on its page
+    pub position_top: f64,
+    // X position of the note on its page
+    pub position_left: f64,
+    // ID of the track the note is pinned to, if any
+    pub track_id: Option<i32>,
+    // Point in the track that the note refers to
+    pub track_time: f64,
+    // Date that this note was created
+    pub created_at: String,
+    // Date that this note was last updated
+    pub updated_at: String,
+}
+
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+pub struct CreateUserNote {
+    // ID of the document for the note
+    pub document_id: i32,
+    // Text of the note
+    pub content: String,
+    // ID of the page to pin the note to
+    pub page_id: Option<i32>,
+    // Y position of the note on the page
+    pub position_top: Option<f64>,
+    // X position of the note on the page
+    pub position_left: Option<f64>,
+    // ID of the track to pin the note to
+    pub track_id: Option<i32>,
+    // Point in the track that the note refers to
+    pub track_time: Option<f64>,
+}
+
+#[derive(juniper::GraphQLInputObject, Debug, Clone)]
+pub struct UpdateUserNote {
+    // ID of the note to update
+    pub id: i32,
+    // New text of the note
+    pub content: String,
+    // ID of the page to pin the note to
+    pub page_id: Option<i32>,
+    // Y position of the note on the page
+    pub position_top: Option<f64>,
+    // X position of the note on the page
+    pub position_left: Option<f64>,
+    // ID of the track to pin the note to
+    pub track_id: Option<i32>,
+    // Point in the track that the note refers to
+    pub track_time: Option<f64>,
+}
+
+#[derive(juniper::GraphQLObject, Debug, Clone)]
+pub struct DeleteUserNoteResponse {
+    // Indicates whether deletion was successful
+    pub success: bool,
+}
+
+#[juniper::graphql_object(Context = Context)]
+impl UserNote {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub async fn document(&self, context: &Context) -> Result<Document, GatewayError> {
+        context
+            .document_data()?
+            .documents_by_id(self.document_id)
+            .await?
+            .ok_or_else(|| GatewayError::not_found("document", self.document_id))
+    }
+
+    pub fn content(&self) -> &str {
+        self.content.as_str()
+    }
+
+    pub async fn page(&self, context: &Context) -> Result<Option<Page>, GatewayError> {
+        match self.page_id {
+            Some(page_id) => context.page_data()?.pages_by_id(page_id).await,
+            None => Ok(None),
+        }
+    }
+
+    pub fn position_top(&self) -> Option<f64> {
+        self.page_id.map(|_| self.position_top)
+    }
+
+    pub fn position_left(&self) -> Option<f64> {
+        self.page_id.map(|_| self.position_left)
+    }
+
+    pub async fn track(&self, context: &Context) -> Result<Option<Track>, GatewayError> {
+        match self.track_id {
+            Some(track_id) => context.track_data()?.tracks_by_id(track_id).await,
+            None => Ok(None),
+        }
+    }
+
+    pub fn track_time(&self) -> Option<f64> {
+        self.track_id.map(|_| self.track_time)
+    }
+
+    pub fn created_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.created_at)
+    }
+
+    pub fn updated_at(&self) -> Result<DateTime<FixedOffset>, GatewayError> {
+        parse_timestamp(&self.updated_at)
+    }
+}
+
+impl From<schema::courses::UserNote> for UserNote {
+    fn from(x: schema::courses::UserNote) -> Self {
+        Self {
+            id: x.id,
+            document_id: x.document_id,
+            content: x.content,
+            // The service uses 0 for notes that aren't pinned
+            page_id: Some(x.page_id).filter(|&id| id != 0),
+            position_top: x.position_top as f64,
+            position_left: x.position_left as f64,
+            track_id: Some(x.track_id).filter(|&id| id != 0),
+            track_time: x.track_time as f64,
+            created_at: x.created_at,
+            updated_at: x.updated_at,
+        }
+    }
+}
@@ -1,28 +1,135 @@
 use std::{error, fmt};
 
-#[derive(Debug)]
+use juniper::{graphql_value, FieldError, IntoFieldError};
+use schema::{AUTH_ERROR_METADATA_KEY, RETRY_AFTER_METADATA_KEY};
+
+#[derive(Debug, Clone)]
 pub enum GatewayError {
-    Grpc(tonic::Status),
+    // Nothing exists with the requested ID
+    NotFound(String),
+    // The user isn't allowed to see or change what they asked for
+    PermissionDenied(String),
+    // A service couldn't be reached or gave up on the request
+    Unavailable(String),
+    InvalidArgument(String),
+    // A pagination cursor wasn't one the gateway gave out
+    BadCursor(String),
+    // Credentials were rejected, with the reason code reported by the users service
+    Unauthenticated { code: String, message: String },
+    // Too many attempts, with the number of seconds to wait before trying again
+    RateLimited { retry_after: u64 },
+    // The query is nested too deeply or would cost too much to resolve
+    QueryTooComplex(String),
+    // A persisted query hash was sent without its query, and the gateway doesn't know it
+    PersistedQueryNotFound,
+    // Any other failure, which is logged rather than shown to the user
+    Internal(String),
 }
 
-impl fmt::Display for GatewayError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl GatewayError {
+    /// Error for a field that can't be null referencing something that doesn't exist
+    pub fn not_found(entity: &str, id: i32) -> Self {
+        Self::NotFound(format!("{} {} doesn't exist", entity, id))
+    }
+
+    /// Error for a service response that left out a field it always sets
+    pub fn missing_field(field: &str) -> Self {
+        Self::Internal(format!("Response is missing its {}", field))
+    }
+
+    /// Reason code sent in the extensions of the GraphQL error
+    fn code(&self) -> &str {
         match *self {
-            Self::Grpc(ref err) => write!(f, "GRPC error: {}", err),
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::PermissionDenied(_) => "PERMISSION_DENIED",
+            Self::Unavailable(_) => "UNAVAILABLE",
+            Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::BadCursor(_) => "BAD_CURSOR",
+            Self::Unauthenticated { ref code, .. } => code.as_str(),
+            Self::RateLimited { .. } => "RATE_LIMITED",
+            Self::QueryTooComplex(_) => "QUERY_TOO_COMPLEX",
+            Self::PersistedQueryNotFound => "PERSISTED_QUERY_NOT_FOUND",
+            Self::Internal(_) => "INTERNAL",
         }
     }
 }
 
-impl error::Error for GatewayError {
-    fn cause(&self) -> Option<&(dyn error::Error)> {
+impl fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Self::Grpc(ref err) => Some(err),
+            Self::NotFound(ref msg) => write!(f, "Not found: {}", msg),
+            Self::PermissionDenied(ref msg) => write!(f, "Permission denied: {}", msg),
+            Self::Unavailable(ref msg) => write!(f, "Service unavailable: {}", msg),
+            Self::InvalidArgument(ref msg) => write!(f, "Invalid argument: {}", msg),
+            Self::BadCursor(ref cursor) => write!(f, "Invalid cursor \"{}\"", cursor),
+            Self::Unauthenticated { ref message, .. } => write!(f, "Unauthenticated: {}", message),
+            Self::RateLimited { retry_after } => {
+                write!(f, "Too many attempts, try again in {} seconds", retry_after)
+            }
+            Self::QueryTooComplex(ref msg) => write!(f, "Query too complex: {}", msg),
+            // Automatic persisted query clients look for this exact message
+            Self::PersistedQueryNotFound => write!(f, "PersistedQueryNotFound"),
+            Self::Internal(ref msg) => write!(f, "Internal error: {}", msg),
         }
     }
 }
 
+impl error::Error for GatewayError {}
+
 impl From<tonic::Status> for GatewayError {
     fn from(err: tonic::Status) -> GatewayError {
-        GatewayError::Grpc(err)
+        let message = err.message().to_owned();
+
+        match err.code() {
+            tonic::Code::NotFound => GatewayError::NotFound(message),
+            tonic::Code::PermissionDenied => GatewayError::PermissionDenied(message),
+            tonic::Code::InvalidArgument
+            | tonic::Code::OutOfRange
+            | tonic::Code::FailedPrecondition
+            | tonic::Code::AlreadyExists => GatewayError::InvalidArgument(message),
+            tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::Cancelled => {
+                GatewayError::Unavailable(message)
+            }
+            tonic::Code::Unauthenticated => GatewayError::Unauthenticated {
+                code: err
+                    .metadata()
+                    .get(AUTH_ERROR_METADATA_KEY)
+                    .and_then(|code| code.to_str().ok())
+                    .unwrap_or("UNAUTHENTICATED")
+                    .to_owned(),
+                message,
+            },
+            tonic::Code::ResourceExhausted => match err
+                .metadata()
+                .get(RETRY_AFTER_METADATA_KEY)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+            {
+                Some(retry_after) => GatewayError::RateLimited { retry_after },
+                None => GatewayError::Unavailable(message),
+            },
+            code => GatewayError::Internal(format!("{:?}: {}", code, message)),
+        }
+    }
+}
+
+impl IntoFieldError for GatewayError {
+    fn into_field_error(self) -> FieldError {
+        let code = self.code().to_owned();
+
+        match self {
+            Self::Unauthenticated { message, .. } => {
+                FieldError::new(message, graphql_value!({ "code": code }))
+            }
+            Self::RateLimited { retry_after } => FieldError::new(
+                Self::RateLimited { retry_after },
+                graphql_value!({ "code": code, "retryAfter": (retry_after as i32) }),
+            ),
+            Self::Internal(msg) => {
+                log::error!("Internal error: {}", msg);
+                FieldError::new("Internal error", graphql_value!({ "code": code }))
+            }
+            err => FieldError::new(err, graphql_value!({ "code": code })),
+        }
     }
 }
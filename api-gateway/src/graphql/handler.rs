@@ -1,18 +1,171 @@
+use std::time::Duration;
+
 use actix_web::{
-    error::ErrorInternalServerError,
-    web::{Data, Json},
-    Error, HttpRequest, HttpResponse,
+    error::{ErrorBadRequest, ErrorInternalServerError},
+    web::{Data, Json, Payload},
+    Error, HttpMessage, HttpRequest, HttpResponse,
+};
+use graphql_parser::query::{parse_query, Definition, OperationDefinition, Selection};
+use juniper::{
+    http::{graphiql::graphiql_source, GraphQLBatchResponse, GraphQLRequest, GraphQLResponse},
+    IntoFieldError,
 };
-use juniper::http::{graphiql::graphiql_source, GraphQLRequest};
+use juniper_graphql_ws::ConnectionConfig;
+use schema::shared::User;
+use serde::Deserialize;
 
-use super::schema::Context;
+use super::{persisted::PersistedQuery, schema::Context, websocket};
 use crate::{
-    data::{AnchorData, BookmarkData, DocumentData, PageData, TrackData, UserAnchorData, UserData},
+    data::{
+        AnchorData, BookmarkData, DocumentData, PageData, TrackData, UserAnchorData, UserData,
+        UserNoteData,
+    },
+    errors::GatewayError,
+    rate_limit::ClientIp,
     AppData,
 };
 
+/// Context for resolving operations on behalf of the user
+fn context(
+    st: &AppData,
+    user: Option<User>,
+    token: Option<String>,
+    client_ip: Option<String>,
+) -> Context {
+    Context::new(
+        user,
+        token,
+        Some(UserData::new(st.user_channel.clone())),
+        Some(DocumentData::new(st.courses_channel.clone())),
+        Some(BookmarkData::new(st.courses_channel.clone())),
+        Some(PageData::new(
+            st.courses_channel.clone(),
+            st.page_cache.clone(),
+        )),
+        Some(TrackData::new(
+            st.courses_channel.clone(),
+            st.track_cache.clone(),
+        )),
+        Some(AnchorData::new(st.courses_channel.clone())),
+        Some(UserAnchorData::new(st.courses_channel.clone())),
+        Some(UserNoteData::new(st.courses_channel.clone())),
+        Some(st.storage.clone()),
+        client_ip,
+        Some(st.auth_limits.clone()),
+    )
+}
+
+/// Most operations that one request can batch together
+pub const MAX_BATCH_SIZE: usize = 10;
+
+/// Mutations that are still run, anonymously, when the request's token is rejected, since
+/// they're how a client replaces an expired token
+const CREDENTIAL_MUTATIONS: &[&str] = &["login", "refreshToken"];
+
+/// Operation sent to the GraphQL endpoint, whose query may be left out in favour of the hash of
+/// a persisted query
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationRequest {
+    query: Option<String>,
+    operation_name: Option<String>,
+    #[serde(default)]
+    variables: serde_json::Value,
+    #[serde(default)]
+    extensions: OperationExtensions,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OperationExtensions {
+    persisted_query: Option<PersistedQuery>,
+}
+
+/// Body of a request to the GraphQL endpoint, either one operation or an array of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BatchRequest {
+    Single(OperationRequest),
+    Batch(Vec<OperationRequest>),
+}
+
+/// Operation that passed the query limits, ready to be executed
+struct PreparedOperation {
+    request: GraphQLRequest,
+    // Whether the operation only runs credential mutations
+    renews_credentials: bool,
+}
+
+/// Whether the operation to be run is a mutation of nothing but credential mutations
+fn renews_credentials(query: &str, operation_name: Option<&str>) -> bool {
+    let document = match parse_query::<&str>(query) {
+        Ok(document) => document,
+        Err(_) => return false,
+    };
+    let operations = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let operation = match operation_name {
+        Some(name) => operations.into_iter().find(|operation| match operation {
+            OperationDefinition::Query(query) => query.name == Some(name),
+            OperationDefinition::Mutation(mutation) => mutation.name == Some(name),
+            OperationDefinition::Subscription(subscription) => subscription.name == Some(name),
+            OperationDefinition::SelectionSet(_) => false,
+        }),
+        None if operations.len() == 1 => operations.into_iter().next(),
+        None => None,
+    };
+
+    match operation {
+        Some(OperationDefinition::Mutation(mutation)) => {
+            mutation
+                .selection_set
+                .items
+                .iter()
+                .all(|selection| match selection {
+                    Selection::Field(field) => {
+                        field.name == "__typename" || CREDENTIAL_MUTATIONS.contains(&field.name)
+                    }
+                    _ => false,
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Looks up the query of an operation and checks that it is within the query limits before it
+/// is executed
+fn prepare(st: &AppData, operation: OperationRequest) -> Result<PreparedOperation, GatewayError> {
+    let persisted = operation.extensions.persisted_query;
+    let query = st
+        .persisted_queries
+        .resolve(operation.query, persisted.as_ref())?;
+    st.query_limits.check(&query, &operation.variables)?;
+    if let Some(persisted) = persisted {
+        st.persisted_queries.register(&persisted, &query);
+    }
+
+    let variables = if operation.variables.is_null() {
+        None
+    } else {
+        let variables = serde_json::from_value(operation.variables)
+            .map_err(|err| GatewayError::InvalidArgument(format!("Invalid variables: {}", err)))?;
+        Some(variables)
+    };
+    let renews_credentials = renews_credentials(&query, operation.operation_name.as_deref());
+    Ok(PreparedOperation {
+        request: GraphQLRequest::new(query, operation.operation_name, variables),
+        renews_credentials,
+    })
+}
+
 pub async fn graphiql() -> HttpResponse {
-    let html = graphiql_source("/graphql", None);
+    let html = graphiql_source("/graphql", Some("/subscriptions"));
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(html)
@@ -21,25 +174,67 @@ pub async fn graphiql() -> HttpResponse {
 pub async fn graphql(
     req: HttpRequest,
     st: Data<AppData>,
-    data: Json<GraphQLRequest>,
+    data: Json<BatchRequest>,
 ) -> Result<HttpResponse, Error> {
+    let (operations, batch_size) = match data.into_inner() {
+        BatchRequest::Single(operation) => (vec![operation], None),
+        BatchRequest::Batch(operations) => {
+            let size = operations.len();
+            (operations, Some(size))
+        }
+    };
+    if batch_size.map_or(false, |size| size > MAX_BATCH_SIZE) {
+        return Err(ErrorBadRequest(format!(
+            "At most {} operations can be batched in one request",
+            MAX_BATCH_SIZE
+        )));
+    }
+    let requests = operations
+        .into_iter()
+        .map(|operation| prepare(&st, operation))
+        .collect::<Vec<_>>();
+
     let user_data = UserData::new(st.user_channel.clone());
-    let document_data = DocumentData::new(st.courses_channel.clone());
-    let bookmark_data = BookmarkData::new(st.courses_channel.clone());
-    let page_data = PageData::new(st.courses_channel.clone());
-    let track_data = TrackData::new(st.courses_channel.clone());
-    let anchor_data = AnchorData::new(st.courses_channel.clone());
-    let user_anchor_data = UserAnchorData::new(st.courses_channel.clone());
 
     let token = req
         .headers()
         .get("Authorization")
         .and_then(|header| header.to_str().ok());
 
-    let user = if token.is_some() {
-        user_data.authenticate(token.unwrap().to_owned()).await.ok()
+    let (user, token) = if let Some(token) = token {
+        match user_data.authenticate(token.to_owned()).await {
+            Ok(user) => (Some(user), Some(token)),
+            // The token is left out of requests that only log in again, since it's what they
+            // replace
+            Err(GatewayError::Unauthenticated { .. })
+                if requests.iter().all(|request| {
+                    request
+                        .as_ref()
+                        .map_or(true, |operation| operation.renews_credentials)
+                }) =>
+            {
+                (None, None)
+            }
+            // A rejected token fails the whole request so the client can prompt a new login
+            Err(err @ GatewayError::Unauthenticated { .. }) => {
+                let error = || GraphQLResponse::error(err.clone().into_field_error());
+                let res = match batch_size {
+                    None => GraphQLBatchResponse::Single(error()),
+                    Some(size) => GraphQLBatchResponse::Batch((0..size).map(|_| error()).collect()),
+                };
+                let json = serde_json::to_string(&res).map_err(ErrorInternalServerError)?;
+
+                return Ok(HttpResponse::Ok()
+                    .content_type("application/json")
+                    .body(json));
+            }
+            Err(err) => {
+                log::warn!("Failed to authenticate request: {}", err);
+                (None, Some(token))
+            }
+        }
     } else {
-        None
+        (None, None)
     };
 
     log::info!(
@@ -49,20 +244,51 @@ pub async fn graphql(
             .unwrap_or("Anonymous".to_owned())
     );
 
-    let ctx = Context::new(
-        user,
-        Some(user_data),
-        Some(document_data),
-        Some(bookmark_data),
-        Some(page_data),
-        Some(track_data),
-        Some(anchor_data),
-        Some(user_anchor_data),
-    );
-    let res = data.execute(&st.schema, &ctx).await;
+    // Every operation of a batch shares the context, and with it the loaders' caches, which the
+    // mutations refresh for whatever they change. Operations are executed in order, so that an
+    // operation sees the changes made by the mutations before it.
+    let client_ip = req.extensions().get::<ClientIp>().map(|ip| ip.0.clone());
+    let ctx = context(&st, user, token.map(str::to_owned), client_ip);
+    let mut responses = Vec::with_capacity(requests.len());
+    for request in requests.iter() {
+        let response = match request {
+            Ok(operation) => operation.request.execute(&st.schema, &ctx).await,
+            Err(err) => GraphQLResponse::error(err.clone().into_field_error()),
+        };
+        responses.push(response);
+    }
+    let res = match batch_size {
+        None => GraphQLBatchResponse::Single(responses.remove(0)),
+        Some(_) => GraphQLBatchResponse::Batch(responses),
+    };
     let json = serde_json::to_string(&res).map_err(ErrorInternalServerError)?;
 
     Ok(HttpResponse::Ok()
         .content_type("application/json")
         .body(json))
 }
+
+/// Serves subscriptions over WebSocket using the graphql-ws protocol, with the same query limits
+/// as the GraphQL endpoint
+pub async fn subscriptions(
+    req: HttpRequest,
+    st: Data<AppData>,
+    stream: Payload,
+) -> Result<HttpResponse, Error> {
+    // Anchor changes are public, so subscriptions are resolved anonymously. Every operation sent
+    // over the socket is charged to the client's address like a request to the endpoint.
+    let client_ip = req.extensions().get::<ClientIp>().map(|ip| ip.0.clone());
+    let ctx = context(&st, None, None, client_ip.clone());
+    let config =
+        ConnectionConfig::new(ctx.clone()).with_keep_alive_interval(Duration::from_secs(15));
+    websocket::start(
+        &req,
+        stream,
+        st.schema.clone(),
+        config,
+        ctx,
+        st.query_limits,
+        st.graphql_limiter.clone(),
+        client_ip,
+    )
+}
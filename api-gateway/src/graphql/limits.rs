@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use graphql_parser::query::{
+    parse_query, Definition, Field, FragmentDefinition, OperationDefinition, Selection,
+    SelectionSet, Value,
+};
+
+use crate::{entities::DEFAULT_TEXT_MATCHES, errors::GatewayError};
+
+/// Largest page size counted for a list field, since the services cap what they return
+const MAX_COUNTED_PAGE: u64 = 100;
+
+/// Page sizes that the fields whose `limit` can be left out use when it is
+const DEFAULT_PAGE_SIZES: &[(&str, i32)] = &[("search", DEFAULT_TEXT_MATCHES)];
+
+/// Deepest nesting of braces that is parsed at all, since the parser recurses once per level
+const MAX_NESTING: usize = 100;
+
+/// Depth and cost that operations are allowed before they're rejected without being executed.
+/// Every field costs one, and the fields under a paginated or batched field cost as many times
+/// over as the number of items it asks for.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryLimits {
+    pub max_depth: usize,
+    pub max_complexity: u64,
+    // Whether clients may query the schema with `__schema` and `__type`, on every transport
+    pub introspection: bool,
+}
+
+impl QueryLimits {
+    /// Checks every operation in the query, and that it doesn't use introspection where it's
+    /// disabled. A query that can't be parsed can't be checked, so it's rejected as well.
+    pub fn check(&self, query: &str, variables: &serde_json::Value) -> Result<(), GatewayError> {
+        if nesting(query) > MAX_NESTING {
+            return Err(GatewayError::QueryTooComplex(format!(
+                "braces nested more than {} levels deep",
+                MAX_NESTING
+            )));
+        }
+
+        let document = parse_query::<&str>(query).map_err(|err| {
+            GatewayError::QueryTooComplex(format!("can't be parsed to be checked: {}", err))
+        })?;
+
+        let fragments = document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Fragment(fragment) => Some((fragment.name, fragment)),
+                _ => None,
+            })
+            .collect();
+        let analysis = Analysis {
+            limits: self,
+            fragments,
+            variables,
+        };
+
+        for definition in document.definitions.iter() {
+            let selection_set = match definition {
+                Definition::Operation(OperationDefinition::SelectionSet(set)) => set,
+                Definition::Operation(OperationDefinition::Query(query)) => &query.selection_set,
+                Definition::Operation(OperationDefinition::Mutation(mutation)) => {
+                    &mutation.selection_set
+                }
+                Definition::Operation(OperationDefinition::Subscription(subscription)) => {
+                    &subscription.selection_set
+                }
+                Definition::Fragment(_) => continue,
+            };
+
+            let cost = analysis.selection_set(selection_set, 1, &mut vec![])?;
+            if cost > self.max_complexity {
+                return Err(GatewayError::QueryTooComplex(format!(
+                    "costs {}, more than the limit of {}",
+                    cost, self.max_complexity
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Analysis<'a> {
+    limits: &'a QueryLimits,
+    fragments: HashMap<&'a str, &'a FragmentDefinition<'a, &'a str>>,
+    variables: &'a serde_json::Value,
+}
+
+impl<'a> Analysis<'a> {
+    /// Cost of the fields in a selection set at the given depth
+    fn selection_set(
+        &self,
+        set: &'a SelectionSet<'a, &'a str>,
+        depth: usize,
+        spreads: &mut Vec<&'a str>,
+    ) -> Result<u64, GatewayError> {
+        let mut cost: u64 = 0;
+
+        for selection in set.items.iter() {
+            let selection_cost = match selection {
+                Selection::Field(field)
+                    if !self.limits.introspection
+                        && (field.name == "__schema" || field.name == "__type") =>
+                {
+                    return Err(GatewayError::PermissionDenied(
+                        "Introspection is disabled".to_owned(),
+                    ))
+                }
+                // Introspection is answered from the schema without calling any service
+                Selection::Field(field) if field.name.starts_with("__") => 0,
+                Selection::Field(field) => {
+                    if depth > self.limits.max_depth {
+                        return Err(GatewayError::QueryTooComplex(format!(
+                            "nested deeper than the limit of {}",
+                            self.limits.max_depth
+                        )));
+                    }
+                    let children = self.selection_set(&field.selection_set, depth + 1, spreads)?;
+                    children.saturating_mul(self.items(field)).saturating_add(1)
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.selection_set(&fragment.selection_set, depth, spreads)?
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.fragment_name;
+                    // Fragments that spread themselves are rejected by validation, but would
+                    // recurse forever here
+                    let fragment = match self.fragments.get(name) {
+                        Some(fragment) if !spreads.contains(&name) => fragment,
+                        _ => continue,
+                    };
+                    spreads.push(name);
+                    let cost = self.selection_set(&fragment.selection_set, depth, spreads);
+                    spreads.pop();
+                    cost?
+                }
+            };
+            cost = cost.saturating_add(selection_cost);
+        }
+
+        Ok(cost)
+    }
+
+    /// Number of items a field asks for, from its page size or the IDs it's given, or else the
+    /// page size it defaults to
+    fn items(&self, field: &Field<'a, &'a str>) -> u64 {
+        let count = field
+            .arguments
+            .iter()
+            .filter_map(|(name, value)| match *name {
+                "limit" | "first" => self.int(value),
+                "ids" => self.list_len(value),
+                _ => None,
+            })
+            .max()
+            .or_else(|| {
+                DEFAULT_PAGE_SIZES
+                    .iter()
+                    .find(|(name, _)| *name == field.name)
+                    .map(|(_, size)| *size as u64)
+            })
+            .unwrap_or(1);
+
+        count.max(1).min(MAX_COUNTED_PAGE)
+    }
+
+    fn int(&self, value: &Value<'a, &'a str>) -> Option<u64> {
+        match value {
+            Value::Int(n) => n.as_i64().map(|n| n.max(0) as u64),
+            Value::Variable(name) => self.variables.get(name)?.as_i64().map(|n| n.max(0) as u64),
+            _ => None,
+        }
+    }
+
+    fn list_len(&self, value: &Value<'a, &'a str>) -> Option<u64> {
+        match value {
+            Value::List(items) => Some(items.len() as u64),
+            Value::Variable(name) => Some(self.variables.get(name)?.as_array()?.len() as u64),
+            _ => None,
+        }
+    }
+}
+
+/// Deepest nesting of braces in the query text
+fn nesting(query: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut deepest = 0;
+    for c in query.chars() {
+        match c {
+            '{' => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: QueryLimits = QueryLimits {
+        max_depth: 5,
+        max_complexity: 1000,
+        introspection: false,
+    };
+
+    #[test]
+    fn accepts_a_query_within_the_limits() {
+        let query =
+            "query { documentById(id: 1) { title pagesConnection(first: 10) { totalCount } } }";
+
+        assert!(LIMITS.check(query, &serde_json::Value::Null).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_query_that_cant_be_parsed() {
+        let query = "query { documentById(id: 1) { title } ";
+
+        assert!(matches!(
+            LIMITS.check(query, &serde_json::Value::Null),
+            Err(GatewayError::QueryTooComplex(_))
+        ));
+    }
+
+    #[test]
+    fn counts_the_default_page_size_when_the_limit_is_left_out() {
+        let limits = QueryLimits {
+            max_complexity: 10,
+            ..LIMITS
+        };
+        let query = r#"{ documentById(id: 1) { search(query: "a") { text } } }"#;
+        let limited = r#"{ documentById(id: 1) { search(query: "a", limit: 5) { text } } }"#;
+
+        assert!(matches!(
+            limits.check(query, &serde_json::Value::Null),
+            Err(GatewayError::QueryTooComplex(_))
+        ));
+        assert!(limits.check(limited, &serde_json::Value::Null).is_ok());
+    }
+
+    #[test]
+    fn rejects_introspection_when_disabled() {
+        let query = "{ __schema { types { name } } }";
+
+        assert!(matches!(
+            LIMITS.check(query, &serde_json::Value::Null),
+            Err(GatewayError::PermissionDenied(_))
+        ));
+    }
+}
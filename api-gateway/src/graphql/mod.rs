@@ -1,4 +1,8 @@
 pub mod handler;
+pub mod limits;
 pub mod mutation;
+pub mod persisted;
 pub mod query;
 pub mod schema;
+pub mod subscription;
+pub mod websocket;
@@ -1,65 +1,173 @@
 use super::schema::Context;
-use crate::entities::{
-    Anchor, Bookmark, CreateAnchor, CreateUserAnchor, DeleteAnchorResponse, DeleteBookmarkResponse,
-    DeleteUserAnchorResponse, LoginResponse, NewUser, Track, UserRole,
-    UpdateUserRoleResponse, User, UserAnchor,
+use crate::{
+    entities::{
+        Anchor, AnchorFileFormat, Bookmark, CreateAnchor, CreateUserAnchor, CreateUserNote,
+        DeleteAnchorResponse, DeleteBookmarkResponse, DeleteDocumentResponse,
+        DeleteUserAnchorResponse, DeleteUserNoteResponse, Document, ImportAnchorsResponse,
+        LoginResponse, LogoutResponse, NewAnchorFile, NewPage, NewTrack, NewUser, Page, Progress,
+        SaveProgress, Track, UpdateAnchor, UpdateUserAnchor, UpdateUserNote,
+        UpdateUserRoleResponse, User, UserAnchor, UserNote, UserRole,
+    },
+    errors::GatewayError,
 };
-use juniper::FieldResult;
 
 pub struct Mutation {}
 
 #[juniper::graphql_object(Context = Context)]
 impl Mutation {
-    pub async fn create_user(ctx: &Context, data: NewUser) -> FieldResult<User> {
-        Ok(ctx.user_data.as_ref().unwrap().create_user(data).await?)
+    pub async fn create_user(ctx: &Context, data: NewUser) -> Result<User, GatewayError> {
+        check_auth_rate(ctx, &data.username)?;
+        ctx.user_data()?.create_user(data).await
     }
 
     pub async fn login(
         ctx: &Context,
         username: String,
         password: String,
-    ) -> FieldResult<LoginResponse> {
-        let (token, user) = ctx
-            .user_data
-            .as_ref()
-            .unwrap()
-            .login(username, password)
-            .await?;
-        Ok(LoginResponse { token, user })
+    ) -> Result<LoginResponse, GatewayError> {
+        check_auth_rate(ctx, &username)?;
+        ctx.user_data()?.login(username, password).await
+    }
+
+    pub async fn refresh_token(
+        ctx: &Context,
+        refresh_token: String,
+    ) -> Result<LoginResponse, GatewayError> {
+        ctx.user_data()?.refresh_token(refresh_token).await
+    }
+
+    pub async fn logout(
+        ctx: &Context,
+        refresh_token: String,
+    ) -> Result<LogoutResponse, GatewayError> {
+        ctx.user_data()?.logout(refresh_token).await
     }
 
     pub async fn update_user_role(
         ctx: &Context,
         user_id: i32,
-        new_role: UserRole
-    ) -> FieldResult<UpdateUserRoleResponse> {
+        new_role: UserRole,
+    ) -> Result<UpdateUserRoleResponse, GatewayError> {
         let response = ctx
-            .user_data
-            .as_ref()
-            .unwrap()
-            .update_user_role(user_id, new_role, ctx.user.clone())
+            .user_data()?
+            .update_user_role(user_id, new_role, ctx.token.clone())
             .await?;
         Ok(UpdateUserRoleResponse {
             success: response.success,
         })
     }
 
-    pub async fn create_anchor(ctx: &Context, anchor: CreateAnchor) -> FieldResult<Anchor> {
+    pub async fn create_document(
+        ctx: &Context,
+        title: String,
+        language: Option<String>,
+        level: Option<String>,
+        description: Option<String>,
+        private: Option<bool>,
+    ) -> Result<Document, GatewayError> {
+        let response = ctx
+            .document_data()?
+            .create_document(
+                title,
+                language,
+                level,
+                description,
+                private,
+                ctx.token.clone(),
+            )
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn delete_document(
+        ctx: &Context,
+        document_id: i32,
+    ) -> Result<DeleteDocumentResponse, GatewayError> {
         let response = ctx
-            .anchor_data
-            .as_ref()
-            .unwrap()
-            .create_anchor(ctx.user.clone(), anchor)
+            .document_data()?
+            .delete_document(document_id, ctx.token.clone())
             .await?;
+        ctx.page_data()?.forget_document(document_id);
+        ctx.track_data()?.forget_document(document_id);
         Ok(response)
     }
 
-    pub async fn delete_anchor(ctx: &Context, anchor_id: i32) -> FieldResult<DeleteAnchorResponse> {
+    pub async fn save_progress(
+        ctx: &Context,
+        data: SaveProgress,
+    ) -> Result<Progress, GatewayError> {
         let response = ctx
-            .anchor_data
-            .as_ref()
-            .unwrap()
-            .delete_anchor(ctx.user.clone(), anchor_id)
+            .document_data()?
+            .save_progress(data, ctx.token.clone())
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn add_pages(
+        ctx: &Context,
+        document_id: i32,
+        pages: Vec<NewPage>,
+    ) -> Result<Vec<Page>, GatewayError> {
+        let response = ctx
+            .page_data()?
+            .add_pages(document_id, pages, ctx.token.clone())
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn reorder_pages(
+        ctx: &Context,
+        document_id: i32,
+        page_ids: Vec<i32>,
+    ) -> Result<Vec<Page>, GatewayError> {
+        let response = ctx
+            .page_data()?
+            .reorder_pages(document_id, page_ids, ctx.token.clone())
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn add_tracks(
+        ctx: &Context,
+        document_id: i32,
+        tracks: Vec<NewTrack>,
+    ) -> Result<Vec<Track>, GatewayError> {
+        let response = ctx
+            .track_data()?
+            .add_tracks(document_id, tracks, ctx.token.clone())
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn create_anchor(
+        ctx: &Context,
+        anchor: CreateAnchor,
+    ) -> Result<Anchor, GatewayError> {
+        let response = ctx
+            .anchor_data()?
+            .create_anchor(ctx.token.clone(), anchor)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn update_anchor(
+        ctx: &Context,
+        anchor: UpdateAnchor,
+    ) -> Result<Anchor, GatewayError> {
+        let response = ctx
+            .anchor_data()?
+            .update_anchor(ctx.token.clone(), anchor)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn delete_anchor(
+        ctx: &Context,
+        anchor_id: i32,
+    ) -> Result<DeleteAnchorResponse, GatewayError> {
+        let response = ctx
+            .anchor_data()?
+            .delete_anchor(ctx.token.clone(), anchor_id)
             .await?;
         Ok(response)
     }
@@ -67,12 +175,21 @@ impl Mutation {
     pub async fn create_user_anchor(
         ctx: &Context,
         data: CreateUserAnchor,
-    ) -> FieldResult<UserAnchor> {
+    ) -> Result<UserAnchor, GatewayError> {
         let response = ctx
-            .user_anchor_data
-            .as_ref()
-            .unwrap()
-            .create_user_anchor(ctx.user.clone(), data)
+            .user_anchor_data()?
+            .create_user_anchor(ctx.token.clone(), data)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn update_user_anchor(
+        ctx: &Context,
+        data: UpdateUserAnchor,
+    ) -> Result<UserAnchor, GatewayError> {
+        let response = ctx
+            .user_anchor_data()?
+            .update_user_anchor(ctx.token.clone(), data)
             .await?;
         Ok(response)
     }
@@ -80,12 +197,86 @@ impl Mutation {
     pub async fn delete_user_anchor(
         ctx: &Context,
         user_anchor_id: i32,
-    ) -> FieldResult<DeleteUserAnchorResponse> {
+    ) -> Result<DeleteUserAnchorResponse, GatewayError> {
+        let response = ctx
+            .user_anchor_data()?
+            .delete_user_anchor(ctx.token.clone(), user_anchor_id)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn create_user_note(
+        ctx: &Context,
+        data: CreateUserNote,
+    ) -> Result<UserNote, GatewayError> {
+        let response = ctx
+            .user_note_data()?
+            .create_user_note(ctx.token.clone(), data)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn update_user_note(
+        ctx: &Context,
+        data: UpdateUserNote,
+    ) -> Result<UserNote, GatewayError> {
+        let response = ctx
+            .user_note_data()?
+            .update_user_note(ctx.token.clone(), data)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn delete_user_note(
+        ctx: &Context,
+        user_note_id: i32,
+    ) -> Result<DeleteUserNoteResponse, GatewayError> {
+        let response = ctx
+            .user_note_data()?
+            .delete_user_note(ctx.token.clone(), user_note_id)
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn promote_user_anchor(
+        ctx: &Context,
+        user_anchor_id: i32,
+    ) -> Result<Anchor, GatewayError> {
         let response = ctx
-            .user_anchor_data
-            .as_ref()
-            .unwrap()
-            .delete_user_anchor(ctx.user.clone(), user_anchor_id)
+            .anchor_data()?
+            .promote_user_anchor(ctx.token.clone(), user_anchor_id)
+            .await?;
+        // The user anchor was on the page that the new anchor is on
+        ctx.user_anchor_data()?
+            .forget(user_anchor_id, Some(response.page_id))
+            .await;
+        Ok(response)
+    }
+
+    pub async fn restore_anchor(
+        ctx: &Context,
+        event_id: i32,
+    ) -> Result<Option<Anchor>, GatewayError> {
+        let (anchor, user_anchor) = ctx
+            .anchor_data()?
+            .restore_anchor(ctx.token.clone(), event_id)
+            .await?;
+        if let Some(user_anchor) = user_anchor {
+            ctx.user_anchor_data()?.refresh(&user_anchor, None).await;
+        }
+        Ok(anchor)
+    }
+
+    pub async fn import_document_anchors(
+        ctx: &Context,
+        document_id: i32,
+        format: AnchorFileFormat,
+        files: Vec<NewAnchorFile>,
+        replace: bool,
+    ) -> Result<ImportAnchorsResponse, GatewayError> {
+        let response = ctx
+            .anchor_data()?
+            .import_document_anchors(ctx.token.clone(), document_id, format, files, replace)
             .await?;
         Ok(response)
     }
@@ -94,12 +285,10 @@ impl Mutation {
         ctx: &Context,
         track_id: i32,
         title: String,
-    ) -> FieldResult<Track> {
+    ) -> Result<Track, GatewayError> {
         let response = ctx
-            .track_data
-            .as_ref()
-            .unwrap()
-            .update_track_title(track_id, title, ctx.user.clone())
+            .track_data()?
+            .update_track_title(track_id, title, ctx.token.clone())
             .await?;
         Ok(response)
     }
@@ -109,12 +298,10 @@ impl Mutation {
         title: String,
         page_id: i32,
         document_id: i32,
-    ) -> FieldResult<Bookmark> {
+    ) -> Result<Bookmark, GatewayError> {
         let response = ctx
-            .bookmark_data
-            .as_ref()
-            .unwrap()
-            .create_bookmark(title, page_id, document_id, ctx.user.clone())
+            .bookmark_data()?
+            .create_bookmark(title, page_id, document_id, ctx.token.clone())
             .await?;
         Ok(response)
     }
@@ -122,13 +309,19 @@ impl Mutation {
     pub async fn delete_bookmark(
         ctx: &Context,
         bookmark_id: i32,
-    ) -> FieldResult<DeleteBookmarkResponse> {
+    ) -> Result<DeleteBookmarkResponse, GatewayError> {
         let response = ctx
-            .bookmark_data
-            .as_ref()
-            .unwrap()
-            .delete_bookmark(bookmark_id, ctx.user.clone())
+            .bookmark_data()?
+            .delete_bookmark(bookmark_id, ctx.token.clone())
             .await?;
         Ok(response)
     }
 }
+
+/// Throttles attempts to log in or register, both per client and per username
+fn check_auth_rate(ctx: &Context, username: &str) -> Result<(), GatewayError> {
+    match ctx.auth_limits.as_ref() {
+        Some(limits) => limits.check(ctx.client_ip.as_deref(), username),
+        None => Ok(()),
+    }
+}
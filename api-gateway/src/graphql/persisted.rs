@@ -0,0 +1,105 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::errors::GatewayError;
+
+/// `persistedQuery` extension of an Automatic Persisted Query request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedQuery {
+    pub version: i32,
+    pub sha256_hash: String,
+}
+
+/// Queries that clients have registered by their SHA-256 hash, so that they can send the hash
+/// in place of the query. Once full, the queries registered first are forgotten first, and
+/// clients register them again on their next request.
+pub struct PersistedQueries {
+    capacity: usize,
+    store: Mutex<Store>,
+}
+
+#[derive(Default)]
+struct Store {
+    queries: HashMap<String, String>,
+    order: VecDeque<String>,
+}
+
+impl PersistedQueries {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            store: Mutex::new(Store::default()),
+        }
+    }
+
+    /// Query text for a request, given by the client or looked up by its hash. A hash on its own
+    /// that isn't known asks the client to send the query along with it.
+    pub fn resolve(
+        &self,
+        query: Option<String>,
+        persisted: Option<&PersistedQuery>,
+    ) -> Result<String, GatewayError> {
+        let persisted = match persisted {
+            Some(persisted) => persisted,
+            None => {
+                return query.ok_or_else(|| {
+                    GatewayError::InvalidArgument("Request has no query".to_owned())
+                })
+            }
+        };
+        if persisted.version != 1 {
+            return Err(GatewayError::InvalidArgument(format!(
+                "Unsupported persisted query version {}",
+                persisted.version
+            )));
+        }
+
+        match query {
+            Some(query) => {
+                if hash(&query) != persisted.sha256_hash.to_lowercase() {
+                    return Err(GatewayError::InvalidArgument(
+                        "Persisted query hash doesn't match the query".to_owned(),
+                    ));
+                }
+                Ok(query)
+            }
+            None => self
+                .store
+                .lock()
+                .unwrap()
+                .queries
+                .get(&persisted.sha256_hash.to_lowercase())
+                .cloned()
+                .ok_or(GatewayError::PersistedQueryNotFound),
+        }
+    }
+
+    /// Remembers a query that has been accepted, so that later requests can send its hash alone
+    pub fn register(&self, persisted: &PersistedQuery, query: &str) {
+        let hash = persisted.sha256_hash.to_lowercase();
+        let mut store = self.store.lock().unwrap();
+        if store.queries.contains_key(&hash) {
+            return;
+        }
+
+        while store.order.len() >= self.capacity {
+            match store.order.pop_front() {
+                Some(oldest) => store.queries.remove(&oldest),
+                None => break,
+            };
+        }
+        store.order.push_back(hash.clone());
+        store.queries.insert(hash, query.to_owned());
+    }
+}
+
+/// Hex encoded SHA-256 hash of a query, as sent by clients
+pub fn hash(query: &str) -> String {
+    hex::encode(Sha256::digest(query.as_bytes()))
+}
@@ -1,43 +1,98 @@
-use juniper::FieldResult;
+use futures::future::try_join_all;
+use juniper::ID;
 
 use super::schema::Context;
-use crate::entities::{Anchor, Document, Page, User};
+use crate::{
+    entities::{
+        load_node, window, Anchor, AnchorFile, AnchorFileFormat, Document, DocumentConnection,
+        DocumentFilters, NodeValue, Page, User,
+    },
+    errors::GatewayError,
+};
+
+/// Most global IDs that can be looked up by one `nodes` field
+const MAX_NODES: usize = 100;
 
 pub struct Query;
 
 #[juniper::graphql_object(Context = Context)]
 impl Query {
-    async fn user_by_id(ctx: &Context, id: i32) -> FieldResult<User> {
-        Ok(ctx.user_data.as_ref().unwrap().user_by_id(id).await)
+    async fn user_by_id(ctx: &Context, id: i32) -> Result<Option<User>, GatewayError> {
+        ctx.user_data()?.user_by_id(id).await
+    }
+
+    async fn users(ctx: &Context) -> Result<Vec<User>, GatewayError> {
+        ctx.user_data()?.all_users().await
+    }
+
+    async fn document_by_id(ctx: &Context, id: i32) -> Result<Option<Document>, GatewayError> {
+        ctx.document_data()?.documents_by_id(id).await
+    }
+
+    async fn documents(
+        ctx: &Context,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<Document>, GatewayError> {
+        Ok(ctx.document_data()?.all_documents(limit, offset).await?.0)
+    }
+
+    async fn documents_connection(
+        ctx: &Context,
+        first: i32,
+        after: Option<String>,
+    ) -> Result<DocumentConnection, GatewayError> {
+        let (limit, offset) = window(first, after)?;
+        let (documents, total) = ctx.document_data()?.all_documents(limit, offset).await?;
+        Ok(DocumentConnection::new(documents, offset, total))
     }
 
-    async fn users(ctx: &Context) -> FieldResult<Vec<User>> {
-        Ok(ctx.user_data.as_ref().unwrap().all_users().await?)
+    async fn search_documents(
+        ctx: &Context,
+        query: String,
+        filters: Option<DocumentFilters>,
+        first: i32,
+        after: Option<String>,
+    ) -> Result<DocumentConnection, GatewayError> {
+        let (limit, offset) = window(first, after)?;
+        let (documents, total) = ctx
+            .document_data()?
+            .search_documents(query, filters.unwrap_or_default(), limit, offset)
+            .await?;
+        Ok(DocumentConnection::new(documents, offset, total))
     }
 
-    async fn document_by_id(ctx: &Context, id: i32) -> FieldResult<Document> {
-        Ok(ctx
-            .document_data
-            .as_ref()
-            .unwrap()
-            .documents_by_id(id)
-            .await)
+    async fn page_by_id(ctx: &Context, id: i32) -> Result<Option<Page>, GatewayError> {
+        ctx.page_data()?.pages_by_id(id).await
     }
 
-    async fn documents(ctx: &Context, limit: i32, offset: i32) -> FieldResult<Vec<Document>> {
-        Ok(ctx
-            .document_data
-            .as_ref()
-            .unwrap()
-            .all_documents(limit, offset)
-            .await?)
+    async fn anchor_by_id(ctx: &Context, id: i32) -> Result<Option<Anchor>, GatewayError> {
+        ctx.anchor_data()?.anchors_by_id(id).await
     }
 
-    async fn page_by_id(ctx: &Context, id: i32) -> FieldResult<Page> {
-        Ok(ctx.page_data.as_ref().unwrap().pages_by_id(id).await)
+    async fn node(ctx: &Context, id: ID) -> Result<Option<NodeValue>, GatewayError> {
+        load_node(ctx, &id).await
+    }
+
+    async fn nodes(ctx: &Context, ids: Vec<ID>) -> Result<Vec<Option<NodeValue>>, GatewayError> {
+        if ids.len() > MAX_NODES {
+            return Err(GatewayError::InvalidArgument(format!(
+                "at most {} nodes can be fetched at once",
+                MAX_NODES
+            )));
+        }
+
+        // Loading the nodes concurrently lets the loaders batch them
+        try_join_all(ids.iter().map(|id| load_node(ctx, id))).await
     }
 
-    async fn anchor_by_id(ctx: &Context, id: i32) -> FieldResult<Anchor> {
-        Ok(ctx.anchor_data.as_ref().unwrap().anchors_by_id(id).await)
+    async fn export_document_anchors(
+        ctx: &Context,
+        document_id: i32,
+        format: AnchorFileFormat,
+    ) -> Result<Vec<AnchorFile>, GatewayError> {
+        ctx.anchor_data()?
+            .export_document_anchors(document_id, format)
+            .await
     }
 }
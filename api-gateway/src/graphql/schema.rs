@@ -1,14 +1,20 @@
-use juniper::EmptySubscription;
+use std::sync::Arc;
 
-use super::{mutation::Mutation, query::Query};
+use super::{mutation::Mutation, query::Query, subscription::Subscription};
 use crate::data::{
     AnchorData, BookmarkData, DocumentData, PageData, TrackData, UserAnchorData, UserData,
+    UserNoteData,
 };
+use crate::errors::GatewayError;
+use crate::rate_limit::AuthRateLimits;
+use crate::storage::Storage;
 use schema::shared::User;
 
 #[derive(Clone)]
 pub struct Context {
     pub user: Option<User>,
+    // Access token of the request, forwarded to the services so they can verify the user
+    pub token: Option<String>,
     pub user_data: Option<UserData>,
     pub document_data: Option<DocumentData>,
     pub bookmark_data: Option<BookmarkData>,
@@ -16,6 +22,11 @@ pub struct Context {
     pub track_data: Option<TrackData>,
     pub anchor_data: Option<AnchorData>,
     pub user_anchor_data: Option<UserAnchorData>,
+    pub user_note_data: Option<UserNoteData>,
+    pub storage: Option<Arc<dyn Storage>>,
+    // Address of the client, for throttling logins and registrations
+    pub client_ip: Option<String>,
+    pub auth_limits: Option<Arc<AuthRateLimits>>,
 }
 
 impl juniper::Context for Context {}
@@ -23,6 +34,7 @@ impl juniper::Context for Context {}
 impl Context {
     pub fn new(
         user: Option<User>,
+        token: Option<String>,
         user_data: Option<UserData>,
         document_data: Option<DocumentData>,
         bookmark_data: Option<BookmarkData>,
@@ -30,9 +42,14 @@ impl Context {
         track_data: Option<TrackData>,
         anchor_data: Option<AnchorData>,
         user_anchor_data: Option<UserAnchorData>,
+        user_note_data: Option<UserNoteData>,
+        storage: Option<Arc<dyn Storage>>,
+        client_ip: Option<String>,
+        auth_limits: Option<Arc<AuthRateLimits>>,
     ) -> Self {
         Self {
             user,
+            token,
             user_data,
             document_data,
             bookmark_data,
@@ -40,12 +57,84 @@ impl Context {
             track_data,
             anchor_data,
             user_anchor_data,
+            user_note_data,
+            storage,
+            client_ip,
+            auth_limits,
         }
     }
+
+    pub fn user_data(&self) -> Result<&UserData, GatewayError> {
+        configured(&self.user_data, "user_data")
+    }
+
+    pub fn document_data(&self) -> Result<&DocumentData, GatewayError> {
+        configured(&self.document_data, "document_data")
+    }
+
+    pub fn bookmark_data(&self) -> Result<&BookmarkData, GatewayError> {
+        configured(&self.bookmark_data, "bookmark_data")
+    }
+
+    pub fn page_data(&self) -> Result<&PageData, GatewayError> {
+        configured(&self.page_data, "page_data")
+    }
+
+    pub fn track_data(&self) -> Result<&TrackData, GatewayError> {
+        configured(&self.track_data, "track_data")
+    }
+
+    pub fn anchor_data(&self) -> Result<&AnchorData, GatewayError> {
+        configured(&self.anchor_data, "anchor_data")
+    }
+
+    pub fn user_anchor_data(&self) -> Result<&UserAnchorData, GatewayError> {
+        configured(&self.user_anchor_data, "user_anchor_data")
+    }
+
+    pub fn user_note_data(&self) -> Result<&UserNoteData, GatewayError> {
+        configured(&self.user_note_data, "user_note_data")
+    }
+
+    pub fn storage(&self) -> Result<&Arc<dyn Storage>, GatewayError> {
+        configured(&self.storage, "storage")
+    }
+
+    /// Drops everything the loaders have cached, for a context that outlives a single operation
+    pub fn reset_loaders(&self) {
+        if let Some(data) = self.user_data.as_ref() {
+            data.reset();
+        }
+        if let Some(data) = self.document_data.as_ref() {
+            data.reset();
+        }
+        if let Some(data) = self.bookmark_data.as_ref() {
+            data.reset();
+        }
+        if let Some(data) = self.page_data.as_ref() {
+            data.reset();
+        }
+        if let Some(data) = self.track_data.as_ref() {
+            data.reset();
+        }
+        if let Some(data) = self.anchor_data.as_ref() {
+            data.reset();
+        }
+        if let Some(data) = self.user_anchor_data.as_ref() {
+            data.reset();
+        }
+    }
+}
+
+/// Data sources are only left out of contexts that never resolve anything, such as the one used
+/// to print the schema, so a missing one is reported as an internal error rather than a panic
+fn configured<'a, T>(data: &'a Option<T>, name: &str) -> Result<&'a T, GatewayError> {
+    data.as_ref()
+        .ok_or_else(|| GatewayError::Internal(format!("Context has no {}", name)))
 }
 
-pub type Schema = juniper::RootNode<'static, Query, Mutation, EmptySubscription<Context>>;
+pub type Schema = juniper::RootNode<'static, Query, Mutation, Subscription>;
 
 pub fn create_schema() -> Schema {
-    Schema::new(Query {}, Mutation {}, juniper::EmptySubscription::new())
+    Schema::new(Query {}, Mutation {}, Subscription {})
 }
@@ -0,0 +1,30 @@
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
+use juniper::{FieldError, FieldResult, IntoFieldError};
+
+use super::schema::Context;
+use crate::entities::AnchorChange;
+
+type AnchorChangeStream = Pin<Box<dyn Stream<Item = Result<AnchorChange, FieldError>> + Send>>;
+
+pub struct Subscription;
+
+#[juniper::graphql_subscription(Context = Context)]
+impl Subscription {
+    /// Changes to the approved anchors on any page of the document
+    async fn page_anchors_changed(
+        ctx: &Context,
+        document_id: i32,
+    ) -> FieldResult<AnchorChangeStream> {
+        let changes = ctx
+            .anchor_data()
+            .map_err(IntoFieldError::into_field_error)?
+            .watch_document_anchors(document_id)
+            .await
+            .map_err(IntoFieldError::into_field_error)?;
+        Ok(Box::pin(changes.map(|change| {
+            change.map_err(|err| err.into_field_error())
+        })))
+    }
+}
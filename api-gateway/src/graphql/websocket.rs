@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler, WrapFuture};
+use actix_web::{web::Payload, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use futures::{
+    lock::Mutex,
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
+use juniper::{DefaultScalarValue, IntoFieldError};
+use juniper_graphql_ws::{
+    ArcSchema, ClientMessage, Connection, ConnectionConfig, ServerMessage, StartPayload,
+};
+
+use crate::{
+    errors::GatewayError,
+    rate_limit::{retry_after, RateLimiter},
+};
+
+use super::{
+    limits::QueryLimits,
+    mutation::Mutation,
+    query::Query,
+    schema::{Context, Schema},
+    subscription::Subscription,
+};
+
+type GraphQLConnection = Connection<
+    ArcSchema<Query, Mutation, Subscription, Context, DefaultScalarValue>,
+    ConnectionConfig<Context>,
+>;
+
+/// WebSocket speaking the graphql-ws protocol, which charges every operation it's sent to the
+/// client's rate limit and checks it against the query limits before passing it on to the
+/// executor
+struct GraphQLSocket {
+    sink: Arc<Mutex<SplitSink<GraphQLConnection, ClientMessage<DefaultScalarValue>>>>,
+    stream: Option<SplitStream<GraphQLConnection>>,
+    limits: QueryLimits,
+    // Shared with the GraphQL endpoint, so that operations sent over the socket draw from the
+    // same budget as requests
+    limiter: Arc<RateLimiter>,
+    // Unknown for clients that aren't connected over TCP, which aren't limited
+    client_ip: Option<String>,
+    // Shares its loaders with the context that every operation over the socket is resolved in
+    context: Context,
+}
+
+impl GraphQLSocket {
+    /// Checks an operation before it is started
+    fn check(&self, payload: &StartPayload<DefaultScalarValue>) -> Result<(), GatewayError> {
+        if let Some(ip) = self.client_ip.as_ref() {
+            self.limiter
+                .take(ip)
+                .map_err(|wait| GatewayError::RateLimited {
+                    retry_after: retry_after(wait),
+                })?;
+        }
+        let variables = serde_json::to_value(&payload.variables).unwrap_or_default();
+        self.limits.check(&payload.query, &variables)
+    }
+}
+
+impl Actor for GraphQLSocket {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        if let Some(stream) = self.stream.take() {
+            ctx.add_stream(stream);
+        }
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for GraphQLSocket {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        let text = match msg {
+            Ok(ws::Message::Text(text)) => text,
+            Ok(ws::Message::Ping(bytes)) => return ctx.pong(&bytes),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                return ctx.stop();
+            }
+            Ok(_) => return,
+            Err(err) => {
+                log::warn!("Closing subscriptions socket: {}", err);
+                return ctx.stop();
+            }
+        };
+
+        let message = match serde_json::from_str::<ClientMessage<DefaultScalarValue>>(&text) {
+            Ok(message) => message,
+            Err(err) => {
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Protocol,
+                    description: Some(format!("Invalid message: {}", err)),
+                }));
+                return ctx.stop();
+            }
+        };
+        if let ClientMessage::Start {
+            ref id,
+            ref payload,
+        } = message
+        {
+            if let Err(err) = self.check(payload) {
+                let error = err.into_field_error();
+                let rejection = serde_json::json!({
+                    "type": "error",
+                    "id": id,
+                    "payload": {
+                        "message": error.message(),
+                        "extensions": error.extensions(),
+                    },
+                });
+                return ctx.text(rejection.to_string());
+            }
+            // The context lives as long as the socket, so its loaders are emptied before each
+            // operation to keep it from seeing what was loaded for the ones before
+            self.context.reset_loaders();
+        }
+
+        // Messages are passed on one at a time, so that an operation is never started before the
+        // connection is initialised
+        let sink = self.sink.clone();
+        ctx.wait(
+            async move {
+                let _ = sink.lock().await.send(message).await;
+            }
+            .into_actor(self),
+        );
+    }
+}
+
+impl StreamHandler<ServerMessage<DefaultScalarValue>> for GraphQLSocket {
+    fn handle(&mut self, msg: ServerMessage<DefaultScalarValue>, ctx: &mut Self::Context) {
+        match serde_json::to_string(&msg) {
+            Ok(text) => ctx.text(text),
+            Err(err) => log::error!("Failed to serialize subscription message: {}", err),
+        }
+    }
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        ctx.close(None);
+        ctx.stop();
+    }
+}
+
+/// Upgrades the request to a WebSocket serving operations with the given schema and context,
+/// where `context` is a clone of the context in `config`
+pub fn start(
+    req: &HttpRequest,
+    payload: Payload,
+    schema: Arc<Schema>,
+    config: ConnectionConfig<Context>,
+    context: Context,
+    limits: QueryLimits,
+    limiter: Arc<RateLimiter>,
+    client_ip: Option<String>,
+) -> Result<HttpResponse, Error> {
+    let (sink, stream) = Connection::new(ArcSchema(schema), config).split();
+    let socket = GraphQLSocket {
+        sink: Arc::new(Mutex::new(sink)),
+        stream: Some(stream),
+        limits,
+        limiter,
+        client_ip,
+        context,
+    };
+
+    ws::start_with_protocols(socket, &["graphql-ws"], req, payload)
+}
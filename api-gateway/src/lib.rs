@@ -1,12 +1,26 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
+pub mod config;
 pub mod data;
 pub mod entities;
 pub mod errors;
 pub mod graphql;
+pub mod rate_limit;
+pub mod storage;
 
 pub struct AppData {
     pub schema: Arc<graphql::schema::Schema>,
     pub user_channel: tonic::transport::Channel,
     pub courses_channel: tonic::transport::Channel,
+    pub storage: Arc<dyn storage::Storage>,
+    pub auth_limits: Arc<rate_limit::AuthRateLimits>,
+    // Budget of GraphQL requests and subscription operations per client
+    pub graphql_limiter: Arc<rate_limit::RateLimiter>,
+    // Pages and tracks shared between requests, since they rarely change once a course is imported
+    pub page_cache: Arc<data::EntityCache<entities::Page>>,
+    pub track_cache: Arc<data::EntityCache<entities::Track>>,
+    pub query_limits: graphql::limits::QueryLimits,
+    pub persisted_queries: Arc<graphql::persisted::PersistedQueries>,
+    // Directory that the frontend bundle is served from
+    pub static_root: PathBuf,
 }
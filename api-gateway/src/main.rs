@@ -1,78 +1,200 @@
-use std::{env, io, path::PathBuf, sync::Arc};
+use std::{
+    fmt,
+    io::{self, Write},
+    process,
+    sync::Arc,
+    time::Duration,
+};
 
+use actix_cors::Cors;
 use actix_files::NamedFile;
 use actix_web::{
-    middleware::Logger,
-    web::{get, post},
-    App, HttpRequest, HttpServer,
+    error::ErrorNotFound,
+    guard,
+    middleware::{Compress, Condition, Logger},
+    web::{get, post, resource, Data, PayloadConfig},
+    App, Error, HttpRequest, HttpResponse, HttpServer,
 };
 use dotenv::dotenv;
-use structopt::StructOpt;
+use tonic::transport::{Channel, Endpoint};
 
-use gateway::{graphql, AppData};
+use gateway::{
+    config::{Config, ConfigError, LogFormat},
+    data::EntityCache,
+    graphql::{self, limits::QueryLimits, persisted::PersistedQueries},
+    rate_limit::{AuthRateLimits, RateLimit, RateLimiter},
+    storage::{self, handler, PRIVATE_PREFIX},
+    AppData,
+};
 
-#[derive(Debug, StructOpt)]
-#[structopt(name = "microbiome_server")]
-struct Opt {
-    #[structopt(short = "s", long = "socket", default_value = "0.0.0.0:8000")]
-    socket: String,
-}
+/// Requests a client can make to the GraphQL endpoint in a burst
+const GRAPHQL_BURST: u32 = 100;
+
+/// How often a client regains a GraphQL request once its burst is spent
+const GRAPHQL_REFILL: Duration = Duration::from_millis(50);
+
+/// How long pages and tracks are cached between requests
+const ENTITY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Persisted queries remembered before the oldest are forgotten
+const PERSISTED_QUERIES_CAPACITY: usize = 1000;
 
-async fn index(req: HttpRequest) -> io::Result<NamedFile> {
-    let mut path = req
-        .match_info()
-        .query("path")
-        .split("/")
-        .collect::<Vec<&str>>();
-    let seg = path.pop().unwrap_or("");
+async fn index(req: HttpRequest, st: Data<AppData>) -> Result<HttpResponse, Error> {
+    let path = req.match_info().query("path");
+    // Private media is only served through the media route, which checks its signature
+    if path.starts_with(PRIVATE_PREFIX) {
+        return Err(ErrorNotFound("Not found."));
+    }
+
+    let seg = path.rsplit('/').next().unwrap_or("");
     if seg.find('.').is_some() {
-        Ok(NamedFile::open::<PathBuf>(
-            format!("./static/{}/{}", path.join("/"), seg).parse().unwrap(),
-        )?)
+        // `.` and empty segments would let a path reach `private/` without starting with it
+        storage::validate_key(path).map_err(|_| ErrorNotFound("Not found."))?;
+        let file = NamedFile::open(storage::resolve_within(&st.static_root, path)?)?;
+        handler::bundle_file(&req, file)
+    } else {
+        handler::bundle_file(&req, NamedFile::open(st.static_root.join("index.html"))?)
+    }
+}
+
+/// Reports a startup failure and exits, since nothing can be served without it
+fn exit_with(err: impl fmt::Display) -> ! {
+    eprintln!("Failed to start Microbiome: {}", err);
+    process::exit(1)
+}
+
+fn init_logger(format: LogFormat) {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = serde_json::json!({
+                "time": chrono::Utc::now().to_rfc3339(),
+                "level": record.level().to_string(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.init();
+}
+
+async fn connect(service: &str, uri: &str, endpoint: Result<Endpoint, ConfigError>) -> Channel {
+    let endpoint = endpoint.unwrap_or_else(|err| exit_with(err));
+    endpoint.connect().await.unwrap_or_else(|err| {
+        exit_with(format!(
+            "Couldn't connect to the {} at {}: {}",
+            service, uri, err
+        ))
+    })
+}
+
+/// Lets browsers on the configured origins call the gateway, or any origin given `*`
+fn cors(origins: &[String]) -> Cors {
+    let cors = Cors::default()
+        .allowed_methods(vec!["GET", "HEAD", "POST", "PUT"])
+        .allow_any_header()
+        .expose_headers(vec!["Retry-After"])
+        .max_age(3600);
+
+    if origins.iter().any(|origin| origin == "*") {
+        cors.allow_any_origin()
     } else {
-        Ok(NamedFile::open::<PathBuf>(
-            "./static/index.html".parse().unwrap(),
-        )?)
+        origins
+            .iter()
+            .fold(cors, |cors, origin| cors.allowed_origin(origin))
     }
 }
 
 #[actix_rt::main]
 async fn main() -> io::Result<()> {
     dotenv().ok();
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
-    let opt = Opt::from_args();
-    let url = opt.socket;
-
-    let user_channel =
-        tonic::transport::Channel::from_shared(env::var("USERS_SERVICE_URI").unwrap())
-            .unwrap()
-            .connect()
-            .await
-            .unwrap();
-    let courses_channel =
-        tonic::transport::Channel::from_shared(env::var("COURSES_SERVICE_URI").unwrap())
-            .unwrap()
-            .connect()
-            .await
-            .unwrap();
+    let config = Config::load().unwrap_or_else(|err| exit_with(err));
+    init_logger(config.log_format);
+
+    let user_channel = connect(
+        "users service",
+        &config.users_service_uri,
+        config.users_endpoint(),
+    )
+    .await;
+    let courses_channel = connect(
+        "courses service",
+        &config.courses_service_uri,
+        config.courses_endpoint(),
+    )
+    .await;
 
     let schema = Arc::new(graphql::schema::create_schema());
+    let storage = storage::from_env(&config.static_root).unwrap_or_else(|err| exit_with(err));
+    let auth_limits = Arc::new(AuthRateLimits::new());
+    // Shared by the endpoint and the subscriptions socket, so that a client has a single budget
+    let graphql_limiter = Arc::new(RateLimiter::new(GRAPHQL_BURST, GRAPHQL_REFILL));
+    let page_cache = Arc::new(EntityCache::new(ENTITY_CACHE_TTL));
+    let track_cache = Arc::new(EntityCache::new(ENTITY_CACHE_TTL));
+    let query_limits = QueryLimits {
+        max_depth: config.max_depth,
+        max_complexity: config.max_complexity,
+        introspection: config.introspection,
+    };
+    let persisted_queries = Arc::new(PersistedQueries::new(PERSISTED_QUERIES_CAPACITY));
 
+    let url = config.socket.clone();
     log::info!("Microbiome running at: http://{}", url);
     HttpServer::new(move || {
+        let graphiql = config.graphiql;
+
         App::new()
+            .wrap(Condition::new(
+                !config.cors_origins.is_empty(),
+                cors(&config.cors_origins),
+            ))
+            .wrap(Compress::default())
             .wrap(Logger::default())
             .data(AppData {
                 schema: schema.clone(),
                 user_channel: user_channel.clone(),
                 courses_channel: courses_channel.clone(),
+                storage: storage.clone(),
+                auth_limits: auth_limits.clone(),
+                graphql_limiter: graphql_limiter.clone(),
+                page_cache: page_cache.clone(),
+                track_cache: track_cache.clone(),
+                query_limits,
+                persisted_queries: persisted_queries.clone(),
+                static_root: config.static_root.clone(),
+            })
+            .service(
+                resource("/graphql")
+                    .wrap(RateLimit::new(graphql_limiter.clone()))
+                    .route(post().to(graphql::handler::graphql)),
+            )
+            .configure(|cfg| {
+                if graphiql {
+                    cfg.route("/graphiql", get().to(graphql::handler::graphiql));
+                }
             })
-            .route("/graphql", post().to(graphql::handler::graphql))
-            .route("/graphiql", get().to(graphql::handler::graphiql))
+            .service(
+                resource("/subscriptions")
+                    .wrap(RateLimit::new(graphql_limiter.clone()))
+                    .route(get().to(graphql::handler::subscriptions)),
+            )
+            .service(
+                resource("/media/{document_id}/{name}")
+                    .guard(guard::Put())
+                    .app_data(PayloadConfig::new(handler::MAX_UPLOAD_SIZE))
+                    .to(handler::upload),
+            )
+            .service(
+                resource("/media/{key:.*}")
+                    .guard(guard::Any(guard::Get()).or(guard::Head()))
+                    .to(handler::media),
+            )
             .route("{path:.*}", get().to(index))
     })
     .bind(&url)
-    .expect("Failed to start Microbiome")
+    .unwrap_or_else(|err| exit_with(format!("Couldn't listen on {}: {}", url, err)))
     .run()
     .await
 }
@@ -0,0 +1,272 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, HttpMessage, HttpResponse, ResponseError,
+};
+use futures::future::{err, ok, Either, Ready};
+
+use crate::errors::GatewayError;
+
+/// How often buckets are pruned, so that pruning isn't paid for by every request
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of keys kept by a prune, dropping those used least recently beyond it
+const MAX_KEYS: usize = 100_000;
+
+/// Token bucket for one key
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    pruned: Instant,
+}
+
+/// Token buckets keyed by client IP, username or anything else worth throttling
+pub struct RateLimiter {
+    capacity: f64,
+    refill_every: Duration,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    /// Allows bursts of `capacity` requests per key, regaining one every `refill_every`
+    pub fn new(capacity: u32, refill_every: Duration) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_every,
+            buckets: Mutex::new(Buckets {
+                by_key: HashMap::new(),
+                pruned: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token from the key's bucket, or returns how long until one is available
+    pub fn take(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let refill_seconds = self.refill_every.as_secs_f64();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if now.duration_since(buckets.pruned) >= PRUNE_INTERVAL {
+            self.prune(&mut buckets, now);
+        }
+
+        let bucket = buckets.by_key.entry(key.to_owned()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.updated).as_secs_f64() / refill_seconds)
+            .min(self.capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) * refill_seconds,
+            ))
+        }
+    }
+
+    /// Drops the buckets that have been idle long enough to refill, which are no different from
+    /// new ones, then the least recently used beyond `MAX_KEYS`
+    fn prune(&self, buckets: &mut Buckets, now: Instant) {
+        let refilled_after = self.refill_every.mul_f64(self.capacity);
+        buckets
+            .by_key
+            .retain(|_, b| now.duration_since(b.updated) < refilled_after);
+
+        let excess = buckets.by_key.len().saturating_sub(MAX_KEYS);
+        if excess > 0 {
+            let mut updated = buckets
+                .by_key
+                .values()
+                .map(|b| b.updated)
+                .collect::<Vec<Instant>>();
+            let (_, &mut oldest_kept, _) = updated.select_nth_unstable(excess);
+            buckets.by_key.retain(|_, b| b.updated >= oldest_kept);
+        }
+        buckets.pruned = now;
+    }
+}
+
+/// Stricter limits for logging in and registering, which run bcrypt on every attempt
+pub struct AuthRateLimits {
+    by_ip: RateLimiter,
+    by_username: RateLimiter,
+}
+
+impl AuthRateLimits {
+    pub fn new() -> Self {
+        Self {
+            by_ip: RateLimiter::new(10, Duration::from_secs(6)),
+            by_username: RateLimiter::new(5, Duration::from_secs(60)),
+        }
+    }
+
+    /// Counts an attempt against both the client and the username it targets. Clients whose
+    /// address is unknown are only limited by username, rather than sharing one bucket.
+    pub fn check(&self, client_ip: Option<&str>, username: &str) -> Result<(), GatewayError> {
+        let by_ip = client_ip.map_or(Ok(()), |ip| self.by_ip.take(ip));
+        let by_username = self.by_username.take(&username.to_lowercase());
+
+        let wait = match (by_ip, by_username) {
+            (Ok(()), Ok(())) => return Ok(()),
+            (Err(wait), Ok(())) | (Ok(()), Err(wait)) => wait,
+            (Err(a), Err(b)) => a.max(b),
+        };
+        Err(GatewayError::RateLimited {
+            retry_after: retry_after(wait),
+        })
+    }
+}
+
+impl Default for AuthRateLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Address of the client that sent a request, as seen by the rate limiting middleware
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+/// Response to a client that has run out of tokens
+#[derive(Debug)]
+pub struct TooManyRequests(Duration);
+
+impl fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Too many requests, try again in {} seconds",
+            retry_after(self.0)
+        )
+    }
+}
+
+impl ResponseError for TooManyRequests {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::TooManyRequests()
+            .header("Retry-After", retry_after(self.0).to_string())
+            .body(self.to_string())
+    }
+}
+
+/// Whole seconds to wait before trying again, rounded up and at least one
+pub fn retry_after(wait: Duration) -> u64 {
+    (wait.as_secs_f64().ceil() as u64).max(1)
+}
+
+/// Middleware limiting the requests of each client IP with a token bucket. Forwarded headers are
+/// only trusted when `TRUST_PROXY_HEADERS` is set, since clients can send them themselves. The
+/// limiter is shared rather than owned, so that every worker draws from the same buckets.
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+    trust_proxy_headers: bool,
+}
+
+impl RateLimit {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            limiter,
+            trust_proxy_headers: std::env::var("TRUST_PROXY_HEADERS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl<S, B> Transform<S> for RateLimit
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitMiddleware {
+            service,
+            limiter: self.limiter.clone(),
+            trust_proxy_headers: self.trust_proxy_headers,
+        })
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    limiter: Arc<RateLimiter>,
+    trust_proxy_headers: bool,
+}
+
+impl<S> RateLimitMiddleware<S> {
+    fn client_ip(&self, req: &ServiceRequest) -> Option<String> {
+        let addr = if self.trust_proxy_headers {
+            req.connection_info()
+                .realip_remote_addr()
+                .map(str::to_owned)
+        } else {
+            req.peer_addr().map(|addr| addr.to_string())
+        };
+
+        // Drop the port so that every connection from a client shares its bucket
+        addr.map(|addr| {
+            addr.parse::<SocketAddr>()
+                .map(|addr| addr.ip().to_string())
+                .unwrap_or(addr)
+        })
+        .filter(|addr| !addr.is_empty())
+    }
+}
+
+impl<S, B> Service for RateLimitMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        // Requests without an address, which don't come over TCP, aren't limited rather than
+        // all sharing one bucket
+        if let Some(ip) = self.client_ip(&req) {
+            if let Err(wait) = self.limiter.take(&ip) {
+                log::warn!("Rate limited requests from {}", ip);
+                return Either::Right(err(TooManyRequests(wait).into()));
+            }
+            req.extensions_mut().insert(ClientIp(ip));
+        }
+
+        Either::Left(self.service.call(req))
+    }
+}
@@ -0,0 +1,170 @@
+use actix_files::NamedFile;
+use actix_web::{
+    error::{ErrorBadRequest, ErrorForbidden, ErrorInternalServerError, ErrorNotFound},
+    http::{
+        header::{HeaderValue, CACHE_CONTROL, RANGE},
+        ContentEncoding,
+    },
+    web::{Bytes, Data, Path, Query},
+    Error, HttpRequest, HttpResponse,
+};
+use schema::shared::UserRole;
+
+use super::{validate_key, SignedQuery, PRIVATE_PREFIX};
+use crate::{
+    data::{DocumentData, UserData},
+    AppData,
+};
+
+/// Cache policy for files whose name changes whenever their content does
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Cache policy for public media, which browsers revalidate with its ETag once it goes stale
+const PUBLIC_MEDIA: &str = "public, max-age=86400";
+
+/// Cache policy for private media, which must not be kept by shared caches
+const PRIVATE_MEDIA: &str = "private, max-age=3600";
+
+/// Cache policy for files that should be revalidated on every use
+const REVALIDATE: &str = "no-cache";
+
+/// Largest media file that can be uploaded
+pub const MAX_UPLOAD_SIZE: usize = 256 * 1024 * 1024;
+
+/// Uploads a page image or track audio file for a document, responding with the path to use
+/// when adding the page or track
+pub async fn upload(
+    req: HttpRequest,
+    st: Data<AppData>,
+    path: Path<(i32, String)>,
+    body: Bytes,
+) -> Result<HttpResponse, Error> {
+    let (document_id, name) = path.into_inner();
+
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|header| header.to_str().ok())
+        .ok_or_else(|| ErrorForbidden("You must be logged in to upload media."))?;
+    let user = UserData::new(st.user_channel.clone())
+        .authenticate(token.to_owned())
+        .await
+        .map_err(ErrorForbidden)?;
+    if user.role != UserRole::Administrator as i32 {
+        return Err(ErrorForbidden("Only administrators may upload media."));
+    }
+
+    let document = DocumentData::new(st.courses_channel.clone())
+        .documents_by_id(document_id)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound(format!("Document {} does not exist.", document_id)))?;
+
+    let key = format!(
+        "{}documents/{}/{}",
+        if document.private { PRIVATE_PREFIX } else { "" },
+        document_id,
+        name
+    );
+    validate_key(&key).map_err(ErrorBadRequest)?;
+
+    let content_type = req
+        .headers()
+        .get("Content-Type")
+        .and_then(|header| header.to_str().ok())
+        .unwrap_or("application/octet-stream");
+    st.storage
+        .put(&key, body.to_vec(), content_type)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "path": key })))
+}
+
+/// Serves stored media, checking the signature of private media. Byte ranges, ETags and
+/// conditional requests are handled by `NamedFile`.
+pub async fn media(
+    req: HttpRequest,
+    st: Data<AppData>,
+    key: Path<String>,
+) -> Result<HttpResponse, Error> {
+    let key = key.into_inner();
+    // Checked before the prefix, which `./private/...` would otherwise slip past
+    validate_key(&key).map_err(|_| ErrorNotFound("Media not found."))?;
+    let private = key.starts_with(PRIVATE_PREFIX);
+    if private {
+        let authorized = Query::<SignedQuery>::from_query(req.query_string())
+            .map(|signature| st.storage.verify(&key, &signature))
+            .unwrap_or(false);
+        if !authorized {
+            return Err(ErrorForbidden("Invalid or expired signature."));
+        }
+    }
+
+    let path = st
+        .storage
+        .local_path(&key)
+        .ok_or_else(|| ErrorNotFound("Media not found."))?;
+    // Images and audio are already compressed, and compressing would break byte ranges
+    let file = NamedFile::open(path)?.set_content_encoding(ContentEncoding::Identity);
+
+    let cache_control = if private {
+        PRIVATE_MEDIA
+    } else if is_fingerprinted(&key) {
+        IMMUTABLE
+    } else {
+        PUBLIC_MEDIA
+    };
+    respond(&req, file, cache_control)
+}
+
+/// Serves a file of the frontend bundle, leaving compression to the `Compress` middleware
+pub fn bundle_file(req: &HttpRequest, file: NamedFile) -> Result<HttpResponse, Error> {
+    let name = file
+        .path()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_owned();
+    let file = if req.headers().contains_key(RANGE) {
+        // A compressed body can't be split into the requested ranges
+        file.set_content_encoding(ContentEncoding::Identity)
+    } else {
+        file
+    };
+
+    respond(
+        req,
+        file,
+        if is_fingerprinted(&name) {
+            IMMUTABLE
+        } else {
+            REVALIDATE
+        },
+    )
+}
+
+fn respond(
+    req: &HttpRequest,
+    file: NamedFile,
+    cache_control: &'static str,
+) -> Result<HttpResponse, Error> {
+    let mut response = file.into_response(req)?;
+    response
+        .headers_mut()
+        .insert(CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    Ok(response)
+}
+
+/// Whether a file name carries a content hash, like `synchrotron-5f3c2a9e.wasm`
+fn is_fingerprinted(key: &str) -> bool {
+    key.rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .split(|c| c == '.' || c == '-' || c == '_')
+        .any(|part| {
+            part.len() >= 8
+                && part.chars().all(|c| c.is_ascii_hexdigit())
+                && !part.chars().all(|c| c.is_ascii_digit())
+        })
+}
@@ -0,0 +1,85 @@
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use actix_web::{error::BlockingError, web};
+use async_trait::async_trait;
+use hmac::Mac;
+
+use super::{hmac_sha256, resolve_within, validate_key, SignedQuery, Storage, StorageError};
+
+/// Stores objects on the gateway's disk, where they are served by the `media` handler
+pub struct LocalStorage {
+    root: PathBuf,
+    // Prefix of the URLs that objects are served from, empty for the gateway's own origin
+    public_url: String,
+    // Secret for signing URLs
+    secret: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf, public_url: String, secret: String) -> Self {
+        Self {
+            root,
+            public_url: public_url.trim_end_matches('/').to_owned(),
+            secret,
+        }
+    }
+
+    fn mac(&self, key: &str, expires: i64) -> hmac::Hmac<sha2::Sha256> {
+        hmac_sha256(
+            self.secret.as_bytes(),
+            format!("{}\n{}", key, expires).as_bytes(),
+        )
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, body: Vec<u8>, _content_type: &str) -> Result<(), StorageError> {
+        validate_key(key)?;
+        let path = self.root.join(key);
+        web::block(move || {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, body)
+        })
+        .await
+        .map_err(|err| match err {
+            BlockingError::Error(err) => StorageError::Io(err),
+            BlockingError::Canceled => StorageError::Io(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "upload canceled",
+            )),
+        })
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/media/{}", self.public_url, key)
+    }
+
+    fn signed_url(&self, key: &str, expires_in: Duration) -> String {
+        let expires = chrono::Utc::now().timestamp() + expires_in.as_secs() as i64;
+        format!(
+            "{}/media/{}?expires={}&signature={}",
+            self.public_url,
+            key,
+            expires,
+            hex::encode(self.mac(key, expires).finalize().into_bytes())
+        )
+    }
+
+    fn verify(&self, key: &str, signature: &SignedQuery) -> bool {
+        if signature.expires < chrono::Utc::now().timestamp() {
+            return false;
+        }
+        match hex::decode(&signature.signature) {
+            Ok(bytes) => self.mac(key, signature.expires).verify(&bytes).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        validate_key(key).ok()?;
+        resolve_within(&self.root, key).ok()
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    env, error, fmt, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac, NewMac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::config::ConfigError;
+
+pub mod handler;
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Key prefix for the media of private documents, which is never served without a signature
+pub const PRIVATE_PREFIX: &str = schema::PRIVATE_MEDIA_PREFIX;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Http(reqwest::Error),
+    InvalidKey(String),
+    // The backend answered the request with an error status
+    Rejected(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Io(ref err) => write!(f, "IO error: {}", err),
+            Self::Http(ref err) => write!(f, "HTTP error: {}", err),
+            Self::InvalidKey(ref key) => write!(f, "Invalid object key: {}", key),
+            Self::Rejected(ref msg) => write!(f, "Storage backend rejected request: {}", msg),
+        }
+    }
+}
+
+impl error::Error for StorageError {
+    fn cause(&self) -> Option<&(dyn error::Error)> {
+        match *self {
+            Self::Io(ref err) => Some(err),
+            Self::Http(ref err) => Some(err),
+            Self::InvalidKey(_) => None,
+            Self::Rejected(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> StorageError {
+        StorageError::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for StorageError {
+    fn from(err: reqwest::Error) -> StorageError {
+        StorageError::Http(err)
+    }
+}
+
+/// Backend holding page images and track audio
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Uploads an object, replacing any existing object with the same key
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<(), StorageError>;
+
+    /// Permanent URL of an object
+    fn url(&self, key: &str) -> String;
+
+    /// URL of an object that stops working after `expires_in`
+    fn signed_url(&self, key: &str, expires_in: Duration) -> String;
+
+    /// Checks a signature issued by `signed_url`, for backends whose objects are served by the
+    /// gateway itself
+    fn verify(&self, _key: &str, _signature: &SignedQuery) -> bool {
+        false
+    }
+
+    /// Location of an object on disk, for backends whose objects are served by the gateway itself
+    fn local_path(&self, _key: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Query string of a URL issued by `LocalStorage::signed_url`
+#[derive(Deserialize, Debug)]
+pub struct SignedQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Builds the backend selected by the `STORAGE_BACKEND` environment variable. Local storage
+/// defaults to the static root that the gateway serves.
+pub fn from_env(static_root: &Path) -> Result<Arc<dyn Storage>, ConfigError> {
+    let backend = env::var("STORAGE_BACKEND").unwrap_or_default();
+    let storage: Arc<dyn Storage> = match backend.as_str() {
+        "s3" => Arc::new(S3Storage::new(
+            required("S3_ENDPOINT")?,
+            required("S3_BUCKET")?,
+            env::var("S3_REGION").unwrap_or("us-east-1".to_owned()),
+            required("S3_ACCESS_KEY")?,
+            required("S3_SECRET_KEY")?,
+        )),
+        _ => Arc::new(LocalStorage::new(
+            env::var("STORAGE_ROOT")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| static_root.to_owned()),
+            env::var("STORAGE_PUBLIC_URL").unwrap_or_default(),
+            required("STORAGE_SECRET")?,
+        )),
+    };
+
+    Ok(storage)
+}
+
+fn required(name: &str) -> Result<String, ConfigError> {
+    env::var(name).map_err(|_| ConfigError::Missing(name.to_owned()))
+}
+
+/// Rejects keys that could escape the storage root, or name a path other than their own through
+/// `.` segments, such as `./private/...` slipping past a prefix check
+pub fn validate_key(key: &str) -> Result<(), StorageError> {
+    if key.is_empty()
+        || key.starts_with('/')
+        || key.contains('\\')
+        || key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(StorageError::InvalidKey(key.to_owned()));
+    }
+    Ok(())
+}
+
+/// Resolves a relative path inside `root`, refusing anything that escapes it through `..`,
+/// absolute paths or symbolic links
+pub fn resolve_within(root: &Path, relative: &str) -> io::Result<PathBuf> {
+    let root = root.canonicalize()?;
+    let path = root.join(relative).canonicalize()?;
+    if path.starts_with(&root) {
+        Ok(path)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Path is outside of the static root",
+        ))
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac
+}
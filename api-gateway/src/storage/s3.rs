@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::Mac;
+use sha2::{Digest, Sha256};
+
+use super::{hmac_sha256, validate_key, Storage, StorageError};
+
+/// Longest expiry that S3 accepts for a presigned URL
+const MAX_EXPIRY: u64 = 7 * 24 * 60 * 60;
+
+/// Stores objects in an S3-compatible bucket, addressed path-style so MinIO works out of the box.
+/// Public media is expected to be readable through the bucket policy.
+pub struct S3Storage {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    client: reqwest::Client,
+}
+
+impl S3Storage {
+    pub fn new(
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    ) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_owned(),
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn path(&self, key: &str) -> String {
+        format!("/{}/{}", self.bucket, uri_encode(key, false))
+    }
+
+    /// Presigns a request with AWS Signature Version 4, passing the signature in the query string
+    fn presign(&self, method: &str, key: &str, expires_in: Duration) -> String {
+        let now = chrono::Utc::now();
+        let date = now.format("%Y%m%d").to_string();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let host = self
+            .endpoint
+            .splitn(2, "://")
+            .last()
+            .unwrap_or_default()
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        let path = self.path(key);
+
+        // Parameters must be sorted by name
+        let query = format!(
+            "X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Credential={}&X-Amz-Date={}&X-Amz-Expires={}&X-Amz-SignedHeaders=host",
+            uri_encode(&format!("{}/{}", self.access_key, scope), true),
+            timestamp,
+            expires_in.as_secs().max(1).min(MAX_EXPIRY)
+        );
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            method, path, query, host
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                format!("AWS4{}", self.secret_key).as_bytes(),
+                date.as_bytes(),
+            )
+            .finalize()
+            .into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()).finalize().into_bytes(),
+        );
+        let signature = hex::encode(
+            hmac_sha256(&signing_key, string_to_sign.as_bytes())
+                .finalize()
+                .into_bytes(),
+        );
+
+        format!(
+            "{}{}?{}&X-Amz-Signature={}",
+            self.endpoint, path, query, signature
+        )
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<(), StorageError> {
+        validate_key(key)?;
+        let response = self
+            .client
+            .put(self.presign("PUT", key, Duration::from_secs(60)).as_str())
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(StorageError::Rejected(format!(
+                "{}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            )))
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}{}", self.endpoint, self.path(key))
+    }
+
+    fn signed_url(&self, key: &str, expires_in: Duration) -> String {
+        self.presign("GET", key, expires_in)
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, as SigV4 expects
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_owned(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
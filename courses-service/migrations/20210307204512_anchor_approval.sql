@@ -0,0 +1,2 @@
+ALTER TABLE anchors ADD COLUMN original_owner INT;
+ALTER TABLE anchors ADD COLUMN approved_by INT;
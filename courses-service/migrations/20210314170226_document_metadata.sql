@@ -0,0 +1,9 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE documents ADD COLUMN language VARCHAR (64) NOT NULL DEFAULT '';
+ALTER TABLE documents ADD COLUMN level VARCHAR (64) NOT NULL DEFAULT '';
+ALTER TABLE documents ADD COLUMN description TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX documents_title_trgm_idx ON documents USING GIN (title gin_trgm_ops);
+CREATE INDEX documents_search_idx ON documents
+  USING GIN (to_tsvector('simple', title || ' ' || description));
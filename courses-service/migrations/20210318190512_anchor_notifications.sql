@@ -0,0 +1,28 @@
+-- Publishes every change to an anchor on the anchor_changes channel, along with the
+-- document it belongs to, so that watchers of the document can be updated live
+CREATE FUNCTION notify_anchor_change() RETURNS trigger AS $$
+DECLARE
+  anchor anchors;
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    anchor := OLD;
+  ELSE
+    anchor := NEW;
+  END IF;
+
+  PERFORM pg_notify(
+    'anchor_changes',
+    json_build_object(
+      'operation', TG_OP,
+      'document_id', (SELECT document FROM pages WHERE id = anchor.document_page),
+      'anchor', row_to_json(anchor)
+    )::text
+  );
+
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER anchor_changes
+AFTER INSERT OR UPDATE OR DELETE ON anchors
+FOR EACH ROW EXECUTE PROCEDURE notify_anchor_change();
@@ -0,0 +1,14 @@
+-- Audit trail of the changes made to approved anchors, with the state of the anchor
+-- before and after each change so that it can be restored
+CREATE TABLE anchor_events (
+  id SERIAL PRIMARY KEY,
+  -- Not a foreign key since the events outlive deleted anchors
+  anchor_id INT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('create', 'move', 'promote', 'delete', 'restore')),
+  actor INT NOT NULL,
+  before JSONB,
+  after JSONB,
+  created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX anchor_events_anchor_id ON anchor_events (anchor_id);
@@ -0,0 +1,11 @@
+-- Where each user left off listening to each document
+CREATE TABLE progress (
+  owning_user INT NOT NULL,
+  document INT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+  track INT NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+  track_time REAL NOT NULL,
+  document_page INT NOT NULL REFERENCES pages(id) ON DELETE CASCADE,
+  completion REAL NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (owning_user, document)
+);
@@ -0,0 +1,16 @@
+-- Private notes that users pin to a position on a page and/or a point in a track
+CREATE TABLE user_notes (
+  id SERIAL PRIMARY KEY,
+  owning_user INT NOT NULL,
+  document INT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+  content TEXT NOT NULL,
+  document_page INT REFERENCES pages(id) ON DELETE CASCADE,
+  position_top REAL,
+  position_left REAL,
+  track INT REFERENCES tracks(id) ON DELETE CASCADE,
+  track_time REAL,
+  created_at TIMESTAMPTZ NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX user_notes_owning_user_document ON user_notes (owning_user, document);
@@ -0,0 +1,2 @@
+-- Media for private documents is only served through signed, expiring URLs
+ALTER TABLE documents ADD COLUMN private BOOLEAN NOT NULL DEFAULT FALSE;
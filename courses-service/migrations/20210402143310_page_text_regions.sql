@@ -0,0 +1,13 @@
+-- Text recognised on, or transcribed from, a page, with bounding boxes in percent of the page
+CREATE TABLE page_text_regions (
+  id SERIAL PRIMARY KEY,
+  document_page INT NOT NULL REFERENCES pages(id) ON DELETE CASCADE,
+  ordinal INT NOT NULL,
+  content TEXT NOT NULL,
+  position_top REAL NOT NULL,
+  position_left REAL NOT NULL,
+  width REAL NOT NULL,
+  height REAL NOT NULL
+);
+
+CREATE INDEX page_text_regions_document_page ON page_text_regions (document_page, ordinal);
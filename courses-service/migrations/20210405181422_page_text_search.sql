@@ -0,0 +1,3 @@
+CREATE INDEX page_text_regions_content_trgm_idx ON page_text_regions USING GIN (content gin_trgm_ops);
+CREATE INDEX page_text_regions_search_idx ON page_text_regions
+  USING GIN (to_tsvector('simple', content));
@@ -0,0 +1,8 @@
+-- Notes outlive the page or track they point at, and stay attached to their document
+ALTER TABLE user_notes
+  DROP CONSTRAINT user_notes_document_page_fkey,
+  ADD CONSTRAINT user_notes_document_page_fkey
+    FOREIGN KEY (document_page) REFERENCES pages(id) ON DELETE SET NULL,
+  DROP CONSTRAINT user_notes_track_fkey,
+  ADD CONSTRAINT user_notes_track_fkey
+    FOREIGN KEY (track) REFERENCES tracks(id) ON DELETE SET NULL;
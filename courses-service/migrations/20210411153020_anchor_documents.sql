@@ -0,0 +1,41 @@
+-- Anchors keep the document of their page, so that the change notification of an anchor
+-- removed along with its page or document can still name the document
+ALTER TABLE anchors ADD COLUMN document INT REFERENCES documents(id) ON DELETE CASCADE;
+
+UPDATE anchors SET document = pages.document FROM pages WHERE pages.id = anchors.document_page;
+
+ALTER TABLE anchors ALTER COLUMN document SET NOT NULL;
+
+CREATE FUNCTION set_anchor_document() RETURNS trigger AS $$
+BEGIN
+  NEW.document := (SELECT document FROM pages WHERE id = NEW.document_page);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER anchor_document
+BEFORE INSERT OR UPDATE OF document_page ON anchors
+FOR EACH ROW EXECUTE PROCEDURE set_anchor_document();
+
+CREATE OR REPLACE FUNCTION notify_anchor_change() RETURNS trigger AS $$
+DECLARE
+  anchor anchors;
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    anchor := OLD;
+  ELSE
+    anchor := NEW;
+  END IF;
+
+  PERFORM pg_notify(
+    'anchor_changes',
+    json_build_object(
+      'operation', TG_OP,
+      'document_id', anchor.document,
+      'anchor', row_to_json(anchor)
+    )::text
+  );
+
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,25 @@
+-- Leaves the title out of anchor change notifications. Postgres rejects payloads of 8000 bytes
+-- or more, and the title has no length limit, so a long one would fail the change itself. The
+-- listener loads the current row of a changed anchor instead.
+CREATE OR REPLACE FUNCTION notify_anchor_change() RETURNS trigger AS $$
+DECLARE
+  anchor anchors;
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    anchor := OLD;
+  ELSE
+    anchor := NEW;
+  END IF;
+
+  PERFORM pg_notify(
+    'anchor_changes',
+    json_build_object(
+      'operation', TG_OP,
+      'document_id', anchor.document,
+      'anchor', to_jsonb(anchor) - 'title'
+    )::text
+  );
+
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,217 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use sqlx::postgres::PgPoolOptions;
+use structopt::StructOpt;
+
+mod alignment;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "synchrotron-align")]
+/// Suggests anchors for a page by aligning its text with a track's audio. The suggestions are
+/// saved as user anchors of the system user, for moderators to review and promote.
+struct Opt {
+    /// ID of the page to suggest anchors for
+    #[structopt(long = "page")]
+    page: i32,
+    /// ID of the track that reads the page
+    #[structopt(long = "track")]
+    track: i32,
+    /// Plain text of the page, with lines in the order they're read
+    #[structopt(long = "transcript", parse(from_os_str))]
+    transcript: PathBuf,
+    /// ID of the user that owns the suggested anchors
+    #[structopt(long = "system-user")]
+    system_user: i32,
+    /// Directory served by the gateway, which the track's audio path is relative to
+    #[structopt(long = "static-root", parse(from_os_str), default_value = "./static")]
+    static_root: PathBuf,
+    /// Shortest pause between sentences, in seconds
+    #[structopt(long = "min-silence", default_value = "0.3")]
+    min_silence: f32,
+    /// Remove earlier suggestions of the system user for the page and track
+    #[structopt(long = "replace")]
+    replace: bool,
+    /// Print the suggestions without writing anything to the database
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+/// Decodes a WAV or MP3 file into mono samples, along with its sample rate
+fn decode_audio(path: &Path) -> Result<(Vec<f32>, u32), Box<dyn std::error::Error>> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "wav" => {
+            let mut reader = hound::WavReader::open(path)?;
+            let spec = reader.spec();
+            let samples = match spec.sample_format {
+                hound::SampleFormat::Float => {
+                    reader.samples::<f32>().collect::<Result<Vec<f32>, _>>()?
+                }
+                hound::SampleFormat::Int => {
+                    if spec.bits_per_sample == 0 || spec.bits_per_sample > 32 {
+                        return Err(format!(
+                            "{} has {} bits per sample, expected 1 to 32",
+                            path.display(),
+                            spec.bits_per_sample
+                        )
+                        .into());
+                    }
+                    let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                    reader
+                        .samples::<i32>()
+                        .map(|s| s.map(|s| s as f32 / scale))
+                        .collect::<Result<Vec<f32>, _>>()?
+                }
+            };
+            Ok((to_mono(&samples, spec.channels as usize), spec.sample_rate))
+        }
+        "mp3" => {
+            let mut decoder = minimp3::Decoder::new(fs::File::open(path)?);
+            let (mut samples, mut sample_rate) = (vec![], 0);
+            loop {
+                match decoder.next_frame() {
+                    Ok(frame) => {
+                        sample_rate = frame.sample_rate as u32;
+                        let frame_samples = frame
+                            .data
+                            .iter()
+                            .map(|s| *s as f32 / std::i16::MAX as f32)
+                            .collect::<Vec<f32>>();
+                        samples.extend(to_mono(&frame_samples, frame.channels));
+                    }
+                    Err(minimp3::Error::Eof) => break,
+                    Err(err) => return Err(format!("{:?}", err).into()),
+                }
+            }
+            Ok((samples, sample_rate))
+        }
+        _ => Err(format!(
+            "Unsupported audio format for {}, convert it to WAV or MP3 first",
+            path.display()
+        )
+        .into()),
+    }
+}
+
+fn to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    samples
+        .chunks(channels.max(1))
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+fn print_proposals(proposals: &[alignment::Proposal]) {
+    println!("Suggested anchors:");
+    for p in proposals.iter() {
+        println!(
+            "  {:>8.2}s  top {:>5.1}  left {:>5.1}  \"{}\"",
+            p.track_time, p.position_top, p.position_left, p.title
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let opt = Opt::from_args();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&env::var("DATABASE_URL")?)
+        .await?;
+
+    let track = sqlx::query!(
+        "SELECT tracks.audio_path FROM tracks
+        JOIN pages ON pages.document = tracks.document
+        WHERE tracks.id = $1 AND pages.id = $2;",
+        opt.track,
+        opt.page
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| {
+        format!(
+            "Track {} and page {} don't belong to the same document",
+            opt.track, opt.page
+        )
+    })?;
+
+    let text = fs::read_to_string(&opt.transcript)?;
+    let (samples, sample_rate) = decode_audio(&opt.static_root.join(&track.audio_path))?;
+
+    let text_segments = alignment::segment_text(&text);
+    let speech_segments = alignment::detect_speech(&samples, sample_rate, opt.min_silence);
+    log::info!(
+        "Found {} sentences and {} stretches of speech",
+        text_segments.len(),
+        speech_segments.len()
+    );
+
+    let proposals = alignment::align(&text_segments, &speech_segments);
+    print_proposals(&proposals);
+
+    if opt.dry_run {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+
+    if opt.replace {
+        sqlx::query!(
+            "DELETE FROM user_anchors
+            WHERE document_page = $1 AND track = $2 AND owning_user = $3;",
+            opt.page,
+            opt.track,
+            opt.system_user
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    for p in proposals.iter() {
+        sqlx::query!(
+            "INSERT INTO user_anchors (
+                title,
+                track_time,
+                position_top,
+                position_left,
+                document_page,
+                track,
+                owning_user,
+                created_at,
+                updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);",
+            p.title,
+            p.track_time,
+            p.position_top,
+            p.position_left,
+            opt.page,
+            opt.track,
+            opt.system_user,
+            chrono::Utc::now(),
+            chrono::Utc::now()
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    log::info!(
+        "Saved {} suggested anchors for page {} as user {}",
+        proposals.len(),
+        opt.page,
+        opt.system_user
+    );
+
+    Ok(())
+}
@@ -0,0 +1,375 @@
+use std::cmp::Ordering;
+
+/// Length of the frames that audio energy is measured over, in seconds
+const FRAME_SECONDS: f32 = 0.02;
+
+/// Shortest stretch of sound that counts as speech rather than a click or breath, in seconds
+const MIN_SPEECH_SECONDS: f32 = 0.1;
+
+/// Frame energy below which audio is silent however quiet the rest of the recording is, in dB
+/// relative to full scale
+const SILENCE_DB: f32 = -60.0;
+
+/// Margin kept between proposed anchors and the edges of the page, in percent
+const PAGE_MARGIN: f32 = 5.0;
+
+/// Span of the page text that is read as one unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSegment {
+    pub text: String,
+    // Number of characters in the text before the segment
+    pub offset: usize,
+    // Line and column that the segment starts at
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Stretch of audio between two pauses, in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeechSegment {
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Suggested anchor for the start of a text segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub title: String,
+    pub track_time: f32,
+    pub position_top: f32,
+    pub position_left: f32,
+}
+
+/// Splits page text into sentences, keeping track of where each one starts
+pub fn segment_text(text: &str) -> Vec<TextSegment> {
+    let mut segments = vec![];
+    let mut current = String::new();
+    let (mut line, mut column) = (0, 0);
+    let mut start = None;
+    let mut previous = None;
+
+    for (offset, c) in text.chars().enumerate() {
+        if start.is_none() && !c.is_whitespace() {
+            start = Some((offset, line, column));
+        }
+        if start.is_some() {
+            current.push(if c.is_whitespace() { ' ' } else { c });
+        }
+
+        let ends_sentence = matches!(c, '.' | '!' | '?' | '…' | '。' | '！' | '？');
+        // A blank line ends a heading or any other text without closing punctuation
+        let ends_paragraph = c == '\n' && previous == Some('\n');
+        if ends_sentence || ends_paragraph {
+            if let Some((offset, line, column)) = start.take() {
+                segments.push(TextSegment {
+                    text: current.split_whitespace().collect::<Vec<_>>().join(" "),
+                    offset,
+                    line,
+                    column,
+                });
+            }
+            current.clear();
+        }
+
+        previous = Some(c);
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+    }
+
+    if let Some((offset, line, column)) = start {
+        let text = current.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            segments.push(TextSegment {
+                text,
+                offset,
+                line,
+                column,
+            });
+        }
+    }
+
+    segments
+}
+
+/// Finds the stretches of speech in mono audio by comparing the energy of short frames against
+/// the recording's own noise floor, splitting wherever the audio stays quiet for `min_silence`
+pub fn detect_speech(samples: &[f32], sample_rate: u32, min_silence: f32) -> Vec<SpeechSegment> {
+    let frame_len = ((sample_rate as f32 * FRAME_SECONDS) as usize).max(1);
+    let energies = samples
+        .chunks(frame_len)
+        .map(|frame| {
+            // Decoders can let through NaN or infinite samples, which are counted as silence
+            let power = frame
+                .iter()
+                .filter(|s| s.is_finite())
+                .map(|s| s * s)
+                .sum::<f32>()
+                / frame.len() as f32;
+            10.0 * (power + 1e-10).log10()
+        })
+        .collect::<Vec<f32>>();
+    if energies.is_empty() {
+        return vec![];
+    }
+
+    let mut sorted = energies.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let noise_floor = sorted[sorted.len() / 10];
+    let speech_level = sorted[sorted.len() * 9 / 10];
+    // A recording that is silent throughout has no speech level to speak of
+    let threshold = (noise_floor + (speech_level - noise_floor) * 0.25).max(SILENCE_DB);
+
+    let min_silence_frames = (min_silence / FRAME_SECONDS).ceil() as usize;
+    let mut segments = vec![];
+    let mut start = None;
+    let mut silent_frames = 0;
+
+    for (i, energy) in energies.iter().enumerate() {
+        if *energy >= threshold {
+            if start.is_none() {
+                start = Some(i);
+            }
+            silent_frames = 0;
+        } else if let Some(s) = start {
+            silent_frames += 1;
+            if silent_frames >= min_silence_frames {
+                segments.push((s, i + 1 - silent_frames));
+                start = None;
+                silent_frames = 0;
+            }
+        }
+    }
+    if let Some(s) = start {
+        segments.push((s, energies.len() - silent_frames));
+    }
+
+    segments
+        .into_iter()
+        .map(|(start, end)| SpeechSegment {
+            start: start as f32 * FRAME_SECONDS,
+            end: end as f32 * FRAME_SECONDS,
+        })
+        .filter(|s| s.end - s.start >= MIN_SPEECH_SECONDS)
+        .collect()
+}
+
+/// Matches each text segment to the pause it most likely follows. Reading time is assumed to be
+/// proportional to the number of characters read, measured over the detected speech only, and
+/// every estimate is snapped to the start of a speech segment without going back in time.
+pub fn align(text: &[TextSegment], speech: &[SpeechSegment]) -> Vec<Proposal> {
+    if text.is_empty() || speech.is_empty() {
+        return vec![];
+    }
+
+    let total_chars = text
+        .last()
+        .map(|s| s.offset + s.text.chars().count())
+        .unwrap_or(0)
+        .max(1) as f32;
+    let total_speech = speech.iter().map(|s| s.end - s.start).sum::<f32>();
+    let lines = text.iter().map(|s| s.line).max().unwrap_or(0) + 1;
+    let columns = text
+        .iter()
+        .map(|s| s.column + s.text.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut proposals = vec![];
+    let mut next_speech = 0;
+    for segment in text {
+        if next_speech >= speech.len() {
+            break;
+        }
+
+        let expected =
+            speech_time_to_track_time(speech, segment.offset as f32 / total_chars * total_speech);
+        let (index, _) = speech[next_speech..]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (next_speech + i, (s.start - expected).abs()))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .unwrap();
+        next_speech = index + 1;
+
+        proposals.push(Proposal {
+            title: segment.text.chars().take(40).collect(),
+            track_time: speech[index].start,
+            position_top: PAGE_MARGIN
+                + (100.0 - 2.0 * PAGE_MARGIN) * segment.line as f32 / lines as f32,
+            position_left: PAGE_MARGIN
+                + (100.0 - 2.0 * PAGE_MARGIN) * segment.column as f32 / columns as f32,
+        });
+    }
+
+    proposals
+}
+
+/// Converts a time counted over speech only into a time in the track
+fn speech_time_to_track_time(speech: &[SpeechSegment], mut speech_time: f32) -> f32 {
+    for segment in speech {
+        let length = segment.end - segment.start;
+        if speech_time <= length {
+            return segment.start + speech_time;
+        }
+        speech_time -= length;
+    }
+    speech.last().map(|s| s.end).unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 1000;
+
+    /// Audio alternating between a steady signal and silence, given as seconds of each
+    fn audio(parts: &[(f32, f32)]) -> Vec<f32> {
+        parts
+            .iter()
+            .flat_map(|&(level, seconds)| {
+                std::iter::repeat(level).take((seconds * SAMPLE_RATE as f32) as usize)
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn segments_sentences_and_paragraphs() {
+        let segments = segment_text("Title\n\nHello   there. Bye!");
+
+        assert_eq!(
+            segments,
+            vec![
+                TextSegment {
+                    text: "Title".to_owned(),
+                    offset: 0,
+                    line: 0,
+                    column: 0,
+                },
+                TextSegment {
+                    text: "Hello there.".to_owned(),
+                    offset: 7,
+                    line: 2,
+                    column: 0,
+                },
+                TextSegment {
+                    text: "Bye!".to_owned(),
+                    offset: 22,
+                    line: 2,
+                    column: 15,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_speech_in_empty_audio() {
+        assert_eq!(detect_speech(&[], SAMPLE_RATE, 0.3), vec![]);
+    }
+
+    #[test]
+    fn no_speech_in_silent_audio() {
+        assert_eq!(
+            detect_speech(&audio(&[(0.0, 2.0)]), SAMPLE_RATE, 0.3),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn splits_speech_at_pauses() {
+        let samples = audio(&[(0.5, 0.5), (0.0, 0.5), (0.5, 0.5)]);
+
+        let speech = detect_speech(&samples, SAMPLE_RATE, 0.3);
+
+        assert_eq!(speech.len(), 2);
+        assert_close(speech[0].start, 0.0);
+        assert_close(speech[0].end, 0.5);
+        assert_close(speech[1].start, 1.0);
+        assert_close(speech[1].end, 1.5);
+    }
+
+    #[test]
+    fn keeps_speech_through_short_pauses() {
+        let samples = audio(&[(0.5, 0.5), (0.0, 0.1), (0.5, 0.5)]);
+
+        let speech = detect_speech(&samples, SAMPLE_RATE, 0.3);
+
+        assert_eq!(speech.len(), 1);
+        assert_close(speech[0].start, 0.0);
+        assert_close(speech[0].end, 1.1);
+    }
+
+    #[test]
+    fn counts_non_finite_samples_as_silence() {
+        let samples = audio(&[(0.5, 0.5), (f32::NAN, 0.5), (0.5, 0.5)]);
+
+        let speech = detect_speech(&samples, SAMPLE_RATE, 0.3);
+
+        assert_eq!(speech.len(), 2);
+        assert_close(speech[1].start, 1.0);
+    }
+
+    #[test]
+    fn nothing_to_align_without_text_or_speech() {
+        let speech = [SpeechSegment {
+            start: 0.0,
+            end: 1.0,
+        }];
+
+        assert_eq!(align(&[], &speech), vec![]);
+        assert_eq!(align(&segment_text("Hello."), &[]), vec![]);
+    }
+
+    #[test]
+    fn aligns_text_proportionally_to_speech() {
+        let text = segment_text("Aaaa. Bbbb.");
+        let speech = [
+            SpeechSegment {
+                start: 0.0,
+                end: 1.0,
+            },
+            SpeechSegment {
+                start: 2.0,
+                end: 3.0,
+            },
+        ];
+
+        let proposals = align(&text, &speech);
+
+        assert_eq!(proposals.len(), 2);
+        assert_eq!(proposals[0].title, "Aaaa.");
+        assert_close(proposals[0].track_time, 0.0);
+        assert_close(proposals[0].position_top, PAGE_MARGIN);
+        assert_close(proposals[0].position_left, PAGE_MARGIN);
+        assert_eq!(proposals[1].title, "Bbbb.");
+        assert_close(proposals[1].track_time, 2.0);
+        assert_close(
+            proposals[1].position_left,
+            PAGE_MARGIN + (100.0 - 2.0 * PAGE_MARGIN) * 6.0 / 11.0,
+        );
+    }
+
+    #[test]
+    fn speech_time_past_the_end_is_the_end_of_the_track() {
+        let speech = [SpeechSegment {
+            start: 1.0,
+            end: 2.0,
+        }];
+
+        assert_close(speech_time_to_track_time(&speech, 0.5), 1.5);
+        assert_close(speech_time_to_track_time(&speech, 5.0), 2.0);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use sqlx::postgres::{PgListener, PgPool};
+use tokio::{
+    stream::Stream,
+    sync::{broadcast, mpsc, oneshot},
+    time::delay_for,
+};
+use tonic::Status;
+
+use schema::courses::{Anchor, AnchorChange, AnchorChangeKind};
+
+/// Postgres channel that the anchor trigger notifies
+pub const CHANNEL: &str = "anchor_changes";
+
+/// Changes kept for watchers that haven't caught up yet. A bulk import with `replace` sends a
+/// deletion and an insertion per anchor, so this is sized well beyond one document's anchors;
+/// watchers that still fall behind are ended with `data_loss` and have to reload.
+pub const CAPACITY: usize = 4096;
+
+/// Wait before reconnecting after the connection is first lost, doubled after every failure
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between attempts to reconnect
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Row of the anchors table as serialized to JSON by Postgres
+#[derive(Debug, Deserialize)]
+pub struct AnchorRow {
+    pub id: i32,
+    // Left out of change notifications
+    pub title: Option<String>,
+    pub track_time: f32,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub document_page: i32,
+    pub track: i32,
+    pub original_owner: Option<i32>,
+    pub approved_by: Option<i32>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<AnchorRow> for Anchor {
+    fn from(row: AnchorRow) -> Self {
+        Self {
+            id: row.id,
+            title: row.title.unwrap_or("".to_owned()),
+            track_time: row.track_time,
+            position_top: row.position_top,
+            position_left: row.position_left,
+            page_id: row.document_page,
+            track_id: row.track,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Notification {
+    operation: String,
+    document_id: i32,
+    anchor: AnchorRow,
+}
+
+/// Change to an anchor of a document, as relayed to watchers
+#[derive(Debug, Clone)]
+pub struct DocumentAnchorChange {
+    pub document_id: i32,
+    pub change: AnchorChange,
+}
+
+/// Stream of the changes sent to one watcher. The task feeding it learns that the watcher has
+/// left when the stream is dropped, rather than only on its next send.
+pub struct WatchStream {
+    changes: mpsc::Receiver<Result<AnchorChange, Status>>,
+    // Closes the receiver held by the task once the stream is dropped
+    _watching: oneshot::Sender<()>,
+}
+
+impl WatchStream {
+    /// Stream of the changes sent on `changes`, along with the receiver that resolves once the
+    /// stream is dropped
+    pub fn new(
+        changes: mpsc::Receiver<Result<AnchorChange, Status>>,
+    ) -> (Self, oneshot::Receiver<()>) {
+        let (watching, left) = oneshot::channel();
+        (
+            Self {
+                changes,
+                _watching: watching,
+            },
+            left,
+        )
+    }
+}
+
+impl Stream for WatchStream {
+    type Item = Result<AnchorChange, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.changes).poll_next(cx)
+    }
+}
+
+/// Change described by a notification, which leaves out the anchor's title. The current row of
+/// an inserted or updated anchor is loaded to fill it in, and `None` is returned if the anchor
+/// has been deleted since, as its own notification follows.
+async fn load_change(pool: &PgPool, payload: &str) -> Result<Option<DocumentAnchorChange>, String> {
+    let notification =
+        serde_json::from_str::<Notification>(payload).map_err(|err| err.to_string())?;
+
+    let (kind, anchor) = if notification.operation == "DELETE" {
+        (AnchorChangeKind::Deleted, notification.anchor)
+    } else {
+        let row = sqlx::query!(
+            r#"SELECT to_jsonb(anchors)::text AS "row!" FROM anchors WHERE id=$1;"#,
+            notification.anchor.id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+        match row {
+            Some(row) => (
+                AnchorChangeKind::Upserted,
+                serde_json::from_str::<AnchorRow>(&row.row).map_err(|err| err.to_string())?,
+            ),
+            None => return Ok(None),
+        }
+    };
+
+    Ok(Some(DocumentAnchorChange {
+        document_id: notification.document_id,
+        change: AnchorChange {
+            kind: kind as i32,
+            anchor: Some(anchor.into()),
+        },
+    }))
+}
+
+/// Relays the notifications received by the listener to every open watch stream
+pub async fn relay(
+    mut listener: PgListener,
+    pool: PgPool,
+    sender: broadcast::Sender<DocumentAnchorChange>,
+) {
+    let mut reconnect_delay = RECONNECT_DELAY;
+    loop {
+        match listener.recv().await {
+            Ok(notification) => {
+                reconnect_delay = RECONNECT_DELAY;
+                match load_change(&pool, notification.payload()).await {
+                    Ok(Some(change)) => {
+                        // Sending only fails when nobody is watching
+                        let _ = sender.send(change);
+                    }
+                    Ok(None) => {}
+                    Err(err) => log::error!("Failed to relay anchor notification: {}", err),
+                }
+            }
+            // The listener reconnects on the next call to recv, which is held back so that an
+            // unreachable database isn't retried in a busy loop
+            Err(err) => {
+                log::error!(
+                    "Lost connection while listening for anchor changes, retrying in {:?}: {}",
+                    reconnect_delay,
+                    err
+                );
+                delay_for(reconnect_delay).await;
+                reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        }
+    }
+}
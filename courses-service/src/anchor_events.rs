@@ -0,0 +1,186 @@
+use serde::Deserialize;
+use sqlx::{postgres::Postgres, Transaction};
+
+use schema::courses::AnchorEventKind;
+
+use crate::{anchor_changes::AnchorRow, errors::CoursesServiceError};
+
+/// Value of the `table` field marking snapshots of user anchors, which approved anchor snapshots
+/// do not have
+const USER_ANCHORS_TABLE: &str = "user_anchors";
+
+/// Row of the user_anchors table as serialized to JSON by Postgres
+#[derive(Debug, Deserialize)]
+pub struct UserAnchorRow {
+    pub id: i32,
+    pub title: Option<String>,
+    pub track_time: f32,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub document_page: i32,
+    pub track: i32,
+    pub owning_user: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// State stored in an event: an approved anchor, or the user anchor that a promotion replaced
+#[derive(Debug)]
+pub enum Snapshot {
+    Anchor(AnchorRow),
+    UserAnchor(UserAnchorRow),
+}
+
+impl Snapshot {
+    /// The approved anchor of the snapshot, `None` for a user anchor
+    pub fn into_anchor(self) -> Option<AnchorRow> {
+        match self {
+            Self::Anchor(row) => Some(row),
+            Self::UserAnchor(_) => None,
+        }
+    }
+}
+
+/// Name the kind is stored under in the anchor_events table
+fn kind_name(kind: AnchorEventKind) -> &'static str {
+    match kind {
+        AnchorEventKind::Create => "create",
+        AnchorEventKind::Move => "move",
+        AnchorEventKind::Promote => "promote",
+        AnchorEventKind::Delete => "delete",
+        AnchorEventKind::Restore => "restore",
+    }
+}
+
+pub fn kind_from_name(name: &str) -> AnchorEventKind {
+    match name {
+        "create" => AnchorEventKind::Create,
+        "move" => AnchorEventKind::Move,
+        "promote" => AnchorEventKind::Promote,
+        "delete" => AnchorEventKind::Delete,
+        // The table does not allow any other kind
+        _ => AnchorEventKind::Restore,
+    }
+}
+
+/// Current state of an anchor as it is stored in its events, `None` if it does not exist
+pub async fn snapshot(
+    tx: &mut Transaction<'_, Postgres>,
+    anchor_id: i32,
+) -> Result<Option<String>, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"SELECT to_jsonb(anchors)::text AS "snapshot!" FROM anchors WHERE id=$1;"#,
+        anchor_id
+    )
+    .fetch_optional(tx)
+    .await?
+    .map(|a| a.snapshot))
+}
+
+/// Current state of a user anchor, marked so that restoring a promotion recreates it as a user
+/// anchor. `None` if it does not exist.
+pub async fn user_anchor_snapshot(
+    tx: &mut Transaction<'_, Postgres>,
+    user_anchor_id: i32,
+) -> Result<Option<String>, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"SELECT (jsonb_build_object('table', $2::text) || to_jsonb(user_anchors))::text
+            AS "snapshot!"
+        FROM user_anchors WHERE id=$1;"#,
+        user_anchor_id,
+        USER_ANCHORS_TABLE
+    )
+    .fetch_optional(tx)
+    .await?
+    .map(|a| a.snapshot))
+}
+
+/// Whether the page and track that a snapshot is placed on still exist, which restoring it needs
+pub async fn pins_exist(
+    tx: &mut Transaction<'_, Postgres>,
+    page_id: i32,
+    track_id: i32,
+) -> Result<bool, sqlx::Error> {
+    Ok(sqlx::query!(
+        r#"SELECT
+            EXISTS (SELECT 1 FROM pages WHERE id=$1)
+            AND EXISTS (SELECT 1 FROM tracks WHERE id=$2) AS "exist!";"#,
+        page_id,
+        track_id
+    )
+    .fetch_one(tx)
+    .await?
+    .exist)
+}
+
+/// Records a change made to an anchor by `actor`. `before` is the snapshot taken before the
+/// change; the state after it is read from the anchors table.
+pub async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    anchor_id: i32,
+    kind: AnchorEventKind,
+    actor: i32,
+    before: Option<String>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO anchor_events (
+            anchor_id,
+            kind,
+            actor,
+            before,
+            after,
+            created_at
+        ) VALUES (
+            $1,
+            $2,
+            $3,
+            $4::text::jsonb,
+            (SELECT to_jsonb(anchors) FROM anchors WHERE id=$1),
+            $5
+        );",
+        anchor_id,
+        kind_name(kind),
+        actor,
+        before,
+        chrono::Utc::now()
+    )
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the deletion of every anchor of a document by `actor`, before they are deleted
+pub async fn record_document_deletion(
+    tx: &mut Transaction<'_, Postgres>,
+    document_id: i32,
+    actor: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO anchor_events (anchor_id, kind, actor, before, created_at)
+        SELECT id, $1, $2, to_jsonb(anchors), $3 FROM anchors WHERE document_page IN
+            (SELECT id FROM pages WHERE document=$4);",
+        kind_name(AnchorEventKind::Delete),
+        actor,
+        chrono::Utc::now(),
+        document_id
+    )
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+pub fn parse_snapshot(snapshot: Option<String>) -> Result<Option<Snapshot>, CoursesServiceError> {
+    snapshot
+        .map(|s| {
+            let value = serde_json::from_str::<serde_json::Value>(&s)?;
+            let table = value.get("table").and_then(|table| table.as_str());
+            Ok(if table == Some(USER_ANCHORS_TABLE) {
+                Snapshot::UserAnchor(serde_json::from_value(value)?)
+            } else {
+                Snapshot::Anchor(serde_json::from_value(value)?)
+            })
+        })
+        .transpose()
+}
@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+
+/// Length given to the final cue of a track, which has no following anchor to end it
+const FINAL_CUE_SECONDS: f32 = 5.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Approved anchor as written to and read from export files
+pub struct ExportedAnchor {
+    pub title: String,
+    pub track_time: f32,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub page_id: i32,
+    pub track_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Anchor read from a format that doesn't record its page or position
+pub struct TimedAnchor {
+    pub title: String,
+    pub track_time: f32,
+    pub track_id: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonExport {
+    document_id: i32,
+    anchors: Vec<ExportedAnchor>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Payload of a WebVTT cue, locating the anchor on its page
+struct CuePayload {
+    title: String,
+    page_id: i32,
+    position_top: f32,
+    position_left: f32,
+}
+
+pub fn to_json(document_id: i32, anchors: Vec<ExportedAnchor>) -> String {
+    serde_json::to_string_pretty(&JsonExport {
+        document_id,
+        anchors,
+    })
+    .unwrap_or_default()
+}
+
+pub fn from_json(content: &str) -> Result<Vec<ExportedAnchor>, String> {
+    serde_json::from_str::<JsonExport>(content)
+        .map(|export| export.anchors)
+        .map_err(|err| format!("Invalid JSON export: {}", err))
+}
+
+/// Writes the anchors of a single track as WebVTT metadata cues, sorted by track time
+pub fn to_webvtt(anchors: &[&ExportedAnchor]) -> String {
+    let mut content = "WEBVTT\n".to_owned();
+    for (i, a) in anchors.iter().enumerate() {
+        let end = anchors
+            .get(i + 1)
+            .map(|next| next.track_time)
+            .unwrap_or(a.track_time + FINAL_CUE_SECONDS)
+            .max(a.track_time + 0.001);
+        let payload = CuePayload {
+            title: a.title.clone(),
+            page_id: a.page_id,
+            position_top: a.position_top,
+            position_left: a.position_left,
+        };
+        // Cue text must not contain `-->`, which can only appear inside the JSON's strings
+        let payload = serde_json::to_string(&payload)
+            .unwrap_or_default()
+            .replace('>', "\\u003e");
+        content.push_str(&format!(
+            "\n{}\n{} --> {}\n{}\n",
+            i + 1,
+            vtt_timestamp(a.track_time),
+            vtt_timestamp(end),
+            payload
+        ));
+    }
+    content
+}
+
+pub fn from_webvtt(content: &str, track_id: i32) -> Result<Vec<ExportedAnchor>, String> {
+    let mut lines = content.lines().map(|l| l.trim());
+    if !lines
+        .next()
+        .map(|header| header.starts_with("WEBVTT"))
+        .unwrap_or(false)
+    {
+        return Err("WebVTT files must begin with a WEBVTT header".to_owned());
+    }
+
+    let mut anchors = vec![];
+    let mut start = None;
+    for line in lines {
+        // The line after a timing line is the cue's payload, whatever it contains
+        if let Some(track_time) = start.take() {
+            let payload: CuePayload = serde_json::from_str(line)
+                .map_err(|err| format!("Invalid cue payload at {}: {}", track_time, err))?;
+            anchors.push(ExportedAnchor {
+                title: payload.title,
+                track_time,
+                position_top: payload.position_top,
+                position_left: payload.position_left,
+                page_id: payload.page_id,
+                track_id,
+            });
+        } else if let Some((timing, _)) = split_once(line, "-->") {
+            start = Some(parse_timestamp(timing.trim())?);
+        }
+    }
+    Ok(anchors)
+}
+
+/// Writes the anchors of a single track as LRC timestamped lines, sorted by track time
+pub fn to_lrc(track_title: &str, anchors: &[&ExportedAnchor]) -> String {
+    let mut content = format!("[ti:{}]\n", single_line(track_title));
+    for a in anchors {
+        content.push_str(&format!(
+            "[{}]{}\n",
+            lrc_timestamp(a.track_time),
+            single_line(&a.title)
+        ));
+    }
+    content
+}
+
+/// Text that fits on one LRC line, since everything after a line break would be read back as a
+/// line of its own
+fn single_line(text: &str) -> String {
+    text.replace(|c: char| c == '\r' || c == '\n', " ")
+}
+
+/// Reads the timestamped lines of an LRC file, which give when each anchor is read but not
+/// where it is placed. Metadata tags such as `[ti:...]` are skipped.
+pub fn from_lrc(content: &str, track_id: i32) -> Result<Vec<TimedAnchor>, String> {
+    let mut anchors = vec![];
+    for line in content.lines() {
+        let mut rest = line.trim();
+        let mut times = vec![];
+        // A line can carry several timestamps when its text is repeated
+        while let Some(tag) = rest.strip_prefix('[') {
+            let (inner, after) = match split_once(tag, "]") {
+                Some(parts) => parts,
+                None => break,
+            };
+            if !inner.starts_with(|c: char| c.is_ascii_digit()) {
+                break;
+            }
+            times.push(parse_timestamp(inner)?);
+            rest = after;
+        }
+        for track_time in times {
+            anchors.push(TimedAnchor {
+                title: rest.trim().to_owned(),
+                track_time,
+                track_id,
+            });
+        }
+    }
+    Ok(anchors)
+}
+
+fn vtt_timestamp(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+fn lrc_timestamp(seconds: f32) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    format!(
+        "{:02}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+fn parse_timestamp(timestamp: &str) -> Result<f32, String> {
+    timestamp
+        .split(':')
+        .try_fold(0.0, |acc, part| {
+            part.parse::<f32>().map(|value| acc * 60.0 + value)
+        })
+        .map_err(|_| format!("Invalid timestamp \"{}\"", timestamp))
+}
+
+fn split_once<'a>(s: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    s.find(separator)
+        .map(|i| (&s[..i], &s[i + separator.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor(title: &str, track_time: f32, page_id: i32) -> ExportedAnchor {
+        ExportedAnchor {
+            title: title.to_owned(),
+            track_time,
+            position_top: 12.5,
+            position_left: 40.25,
+            page_id,
+            track_id: 3,
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let anchors = vec![anchor("One", 1.5, 7), anchor("Two", 62.25, 8)];
+
+        let content = to_json(4, anchors.clone());
+
+        assert_eq!(from_json(&content), Ok(anchors));
+    }
+
+    #[test]
+    fn webvtt_round_trip() {
+        let anchors = vec![
+            anchor("One", 1.5, 7),
+            anchor("Two", 62.25, 8),
+            anchor("Three", 3600.0, 8),
+        ];
+
+        let content = to_webvtt(&anchors.iter().collect::<Vec<_>>());
+
+        assert_eq!(from_webvtt(&content, 3), Ok(anchors));
+    }
+
+    #[test]
+    fn webvtt_round_trip_of_titles_with_cue_timings() {
+        let anchors = vec![anchor("A --> B", 1.5, 7), anchor("-->", 10.0, 7)];
+
+        let content = to_webvtt(&anchors.iter().collect::<Vec<_>>());
+
+        assert_eq!(content.matches("-->").count(), 2);
+        assert_eq!(from_webvtt(&content, 3), Ok(anchors));
+    }
+
+    #[test]
+    fn webvtt_cues_end_at_the_next_anchor() {
+        let anchors = vec![anchor("One", 1.5, 7), anchor("Two", 10.0, 7)];
+
+        let content = to_webvtt(&anchors.iter().collect::<Vec<_>>());
+
+        assert!(content.contains("00:00:01.500 --> 00:00:10.000"));
+        assert!(content.contains("00:00:10.000 --> 00:00:15.000"));
+    }
+
+    #[test]
+    fn webvtt_requires_its_header() {
+        assert!(from_webvtt("1\n00:00:01.000 --> 00:00:02.000\n{}\n", 3).is_err());
+    }
+
+    #[test]
+    fn lrc_round_trip() {
+        let anchors = vec![anchor("One", 1.5, 7), anchor("Two", 3723.25, 8)];
+
+        let content = to_lrc("Lesson 1", &anchors.iter().collect::<Vec<_>>());
+
+        assert_eq!(
+            from_lrc(&content, 3),
+            Ok(vec![
+                TimedAnchor {
+                    title: "One".to_owned(),
+                    track_time: 1.5,
+                    track_id: 3,
+                },
+                TimedAnchor {
+                    title: "Two".to_owned(),
+                    track_time: 3723.25,
+                    track_id: 3,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn lrc_titles_are_kept_to_one_line() {
+        let anchors = vec![anchor("First\r\nSecond", 1.5, 7)];
+
+        let content = to_lrc("Lesson\n1", &anchors.iter().collect::<Vec<_>>());
+
+        assert_eq!(content, "[ti:Lesson 1]\n[00:01.50]First  Second\n");
+        assert_eq!(from_lrc(&content, 3).unwrap()[0].title, "First  Second");
+    }
+
+    #[test]
+    fn lrc_lines_with_several_timestamps() {
+        let anchors = from_lrc("[ar:Someone]\n[00:01.00][00:05.50]Refrain\n", 3).unwrap();
+
+        assert_eq!(
+            anchors
+                .iter()
+                .map(|a| (a.title.as_str(), a.track_time))
+                .collect::<Vec<_>>(),
+            vec![("Refrain", 1.0), ("Refrain", 5.5)]
+        );
+    }
+
+    #[test]
+    fn timestamps_at_hour_boundaries() {
+        assert_eq!(vtt_timestamp(3599.999), "00:59:59.999");
+        assert_eq!(vtt_timestamp(3600.0), "01:00:00.000");
+        assert_eq!(vtt_timestamp(-1.0), "00:00:00.000");
+        // LRC has no hours field, so minutes keep counting past the hour
+        assert_eq!(lrc_timestamp(3600.0), "60:00.00");
+        assert_eq!(lrc_timestamp(3723.25), "62:03.25");
+        assert_eq!(parse_timestamp("01:00:00.000"), Ok(3600.0));
+        assert_eq!(parse_timestamp("62:03.25"), Ok(3723.25));
+    }
+}
@@ -0,0 +1,93 @@
+use jsonwebtoken::{decode, decode_header, errors::ErrorKind, Validation};
+use schema::{jwt_keys::JwtKeySet, AUTH_ERROR_METADATA_KEY};
+use serde::Deserialize;
+use tonic::{metadata::MetadataValue, Request, Status};
+
+// Set by the interceptor once the forwarded token has been verified
+const USER_ID_METADATA_KEY: &str = "x-verified-user-id";
+const USER_ROLE_METADATA_KEY: &str = "x-verified-user-role";
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    user_id: i32,
+    role: i32,
+}
+
+/// User whose access token was verified by the interceptor
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveUser {
+    pub id: i32,
+    pub role: i32,
+}
+
+/// Verifies access tokens issued by the users service
+pub struct JwtVerifier {
+    keys: JwtKeySet,
+}
+
+impl JwtVerifier {
+    /// Loads the keys from the same `JWT_KEYS_FILE` or `JWT_SECRET` as the users service
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            keys: JwtKeySet::from_env(false)?,
+        })
+    }
+
+    fn verify(&self, token: &str) -> Result<Claims, Status> {
+        let result = decode_header(token).and_then(|header| {
+            let key = header
+                .kid
+                .and_then(|kid| self.keys.keys.get(&kid))
+                .ok_or_else(|| jsonwebtoken::errors::Error::from(ErrorKind::InvalidToken))?;
+            decode::<Claims>(token, &key.decoding, &Validation::new(key.algorithm))
+        });
+
+        result.map(|data| data.claims).map_err(|err| {
+            let (code, message) = match err.kind() {
+                ErrorKind::ExpiredSignature => ("TOKEN_EXPIRED", "Access token has expired"),
+                _ => ("INVALID_TOKEN", "Invalid access token"),
+            };
+            let mut status = Status::unauthenticated(message);
+            status
+                .metadata_mut()
+                .insert(AUTH_ERROR_METADATA_KEY, MetadataValue::from_static(code));
+            status
+        })
+    }
+}
+
+/// Verifies the access token forwarded in `authorization` metadata and records its user for
+/// `active_user`. Requests without a token continue anonymously.
+pub fn interceptor(
+    verifier: JwtVerifier,
+) -> impl Fn(Request<()>) -> Result<Request<()>, Status> + Send + Sync + 'static {
+    move |mut request: Request<()>| {
+        let metadata = request.metadata_mut();
+        // Callers must not be able to claim an identity themselves
+        metadata.remove(USER_ID_METADATA_KEY);
+        metadata.remove(USER_ROLE_METADATA_KEY);
+
+        let token = metadata
+            .get("authorization")
+            .and_then(|token| token.to_str().ok())
+            .map(str::to_owned);
+        if let Some(token) = token {
+            let claims = verifier.verify(&token)?;
+            metadata.insert(USER_ID_METADATA_KEY, MetadataValue::from(claims.user_id));
+            metadata.insert(USER_ROLE_METADATA_KEY, MetadataValue::from(claims.role));
+        }
+
+        Ok(request)
+    }
+}
+
+/// User verified by the interceptor, if the request carried an access token
+pub fn active_user<T>(request: &Request<T>) -> Option<ActiveUser> {
+    let read =
+        |key: &str| -> Option<i32> { request.metadata().get(key)?.to_str().ok()?.parse().ok() };
+
+    Some(ActiveUser {
+        id: read(USER_ID_METADATA_KEY)?,
+        role: read(USER_ROLE_METADATA_KEY)?,
+    })
+}
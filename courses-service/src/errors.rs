@@ -3,12 +3,14 @@ use std::{error, fmt};
 #[derive(Debug)]
 pub enum CoursesServiceError {
     Database(sqlx::Error),
+    InvalidSnapshot(String),
 }
 
 impl fmt::Display for CoursesServiceError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Self::Database(ref err) => write!(f, "Database error: {}", err),
+            Self::InvalidSnapshot(ref err) => write!(f, "Invalid anchor snapshot: {}", err),
         }
     }
 }
@@ -17,6 +19,7 @@ impl error::Error for CoursesServiceError {
     fn cause(&self) -> Option<&(dyn error::Error)> {
         match *self {
             Self::Database(ref err) => Some(err),
+            Self::InvalidSnapshot(_) => None,
         }
     }
 }
@@ -27,8 +30,25 @@ impl From<sqlx::Error> for CoursesServiceError {
     }
 }
 
+impl From<serde_json::Error> for CoursesServiceError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::InvalidSnapshot(err.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for CoursesServiceError {
+    fn from(err: chrono::ParseError) -> Self {
+        Self::InvalidSnapshot(err.to_string())
+    }
+}
+
 impl From<CoursesServiceError> for tonic::Status {
     fn from(err: CoursesServiceError) -> tonic::Status {
-        tonic::Status::unknown(err.to_string())
+        match err {
+            CoursesServiceError::Database(sqlx::Error::RowNotFound) => {
+                tonic::Status::not_found(err.to_string())
+            }
+            err => tonic::Status::unknown(err.to_string()),
+        }
     }
 }
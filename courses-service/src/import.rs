@@ -0,0 +1,365 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use sqlx::postgres::PgPoolOptions;
+use structopt::StructOpt;
+
+mod pages;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "ogg", "oga", "m4a", "wav", "flac"];
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "synchrotron-import")]
+/// Imports a course document along with its page images and audio tracks
+struct Opt {
+    /// Title of the new document
+    #[structopt(short = "t", long = "title", required_unless = "manifest")]
+    title: Option<String>,
+    /// Language that the document teaches
+    #[structopt(long = "language", default_value = "")]
+    language: String,
+    /// Level of the document, such as A1 or beginner
+    #[structopt(long = "level", default_value = "")]
+    level: String,
+    /// Short description of the document
+    #[structopt(long = "description", default_value = "")]
+    description: String,
+    /// Directory of page images, numbered in file name order, with numbers compared by value
+    #[structopt(long = "pages", parse(from_os_str), required_unless = "manifest")]
+    pages: Option<PathBuf>,
+    /// Directory of audio tracks, numbered in file name order, with numbers compared by value
+    #[structopt(long = "tracks", parse(from_os_str), required_unless = "manifest")]
+    tracks: Option<PathBuf>,
+    /// JSON manifest listing the title, pages and tracks of the document
+    #[structopt(
+        short = "m",
+        long = "manifest",
+        parse(from_os_str),
+        conflicts_with_all = &["title", "language", "level", "description", "pages", "tracks"]
+    )]
+    manifest: Option<PathBuf>,
+    /// Directory served by the gateway, stored paths are made relative to it
+    #[structopt(long = "static-root", parse(from_os_str), default_value = "./static")]
+    static_root: PathBuf,
+    /// Print the import plan without writing anything to the database
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+/// Manifest describing a document, with file paths relative to the manifest
+struct Manifest {
+    title: String,
+    #[serde(default)]
+    language: String,
+    #[serde(default)]
+    level: String,
+    #[serde(default)]
+    description: String,
+    pages: Vec<PathBuf>,
+    tracks: Vec<ManifestTrack>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestTrack {
+    title: Option<String>,
+    path: PathBuf,
+}
+
+#[derive(Debug)]
+struct PlannedPage {
+    page_number: i32,
+    image_path: String,
+    aspect_ratio: f32,
+    height: f32,
+}
+
+#[derive(Debug)]
+struct PlannedTrack {
+    track_number: i32,
+    title: String,
+    audio_path: String,
+}
+
+#[derive(Debug)]
+struct Plan {
+    title: String,
+    language: String,
+    level: String,
+    description: String,
+    pages: Vec<PlannedPage>,
+    tracks: Vec<PlannedTrack>,
+}
+
+fn list_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut files = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| extensions.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .collect::<Vec<PathBuf>>();
+    files.sort_by_key(|path| (natural_key(path), path.clone()));
+    Ok(files)
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum KeyPart {
+    // Digits without their leading zeros, compared by length first so that 10 follows 9
+    Number(usize, String),
+    Text(String),
+}
+
+/// Orders file stems by the value of the numbers in them, so that `page-10` follows `page-9`
+fn natural_key(path: &Path) -> Vec<KeyPart> {
+    let stem = file_title(path).to_lowercase();
+    let mut parts = vec![];
+    let mut chars = stem.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let is_digit = c.is_ascii_digit();
+        let mut part = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_digit() != is_digit {
+                break;
+            }
+            part.push(c);
+            chars.next();
+        }
+        parts.push(if is_digit {
+            let digits = part.trim_start_matches('0').to_owned();
+            KeyPart::Number(digits.len(), digits)
+        } else {
+            KeyPart::Text(part)
+        });
+    }
+    parts
+}
+
+fn stored_path(static_root: &Path, file: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let relative = fs::canonicalize(file)?
+        .strip_prefix(fs::canonicalize(static_root)?)
+        .map_err(|_| format!("{} is not inside the static root", file.display()))?
+        .to_owned();
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn file_title(file: &Path) -> String {
+    file.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn plan(opt: &Opt) -> Result<Plan, Box<dyn std::error::Error>> {
+    let (title, language, level, description, page_files, track_files) =
+        if let Some(manifest_path) = &opt.manifest {
+            let manifest: Manifest = serde_json::from_str(&fs::read_to_string(manifest_path)?)?;
+            let base = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+            (
+                manifest.title,
+                manifest.language,
+                manifest.level,
+                manifest.description,
+                manifest.pages.iter().map(|p| base.join(p)).collect(),
+                manifest
+                    .tracks
+                    .into_iter()
+                    .map(|t| {
+                        let path = base.join(&t.path);
+                        (t.title.unwrap_or_else(|| file_title(&path)), path)
+                    })
+                    .collect(),
+            )
+        } else {
+            (
+                opt.title.clone().unwrap_or_default(),
+                opt.language.clone(),
+                opt.level.clone(),
+                opt.description.clone(),
+                list_files(opt.pages.as_ref().unwrap(), IMAGE_EXTENSIONS)?,
+                list_files(opt.tracks.as_ref().unwrap(), AUDIO_EXTENSIONS)?
+                    .into_iter()
+                    .map(|path| (file_title(&path), path))
+                    .collect::<Vec<(String, PathBuf)>>(),
+            )
+        };
+
+    if page_files.is_empty() {
+        return Err("No page images found".into());
+    }
+
+    let mut aspect_ratios = vec![];
+    for file in page_files.iter() {
+        let size = imagesize::size(file)
+            .map_err(|err| format!("Unable to read {}: {:?}", file.display(), err))?;
+        // Its aspect ratio would be infinite or NaN, and so would the heights of later pages
+        if size.width == 0 || size.height == 0 {
+            return Err(format!(
+                "{} is {}x{}, but page images must have a width and height",
+                file.display(),
+                size.width,
+                size.height
+            )
+            .into());
+        }
+        aspect_ratios.push(pages::aspect_ratio(size.width as i32, size.height as i32));
+    }
+    let heights = pages::heights(0.0, &aspect_ratios);
+
+    let mut planned_pages = vec![];
+    for (i, file) in page_files.iter().enumerate() {
+        planned_pages.push(PlannedPage {
+            page_number: i as i32 + 1,
+            image_path: stored_path(&opt.static_root, file)?,
+            aspect_ratio: aspect_ratios[i],
+            height: heights[i],
+        });
+    }
+
+    let mut planned_tracks = vec![];
+    for (i, (title, file)) in track_files.into_iter().enumerate() {
+        planned_tracks.push(PlannedTrack {
+            track_number: i as i32 + 1,
+            title,
+            audio_path: stored_path(&opt.static_root, &file)?,
+        });
+    }
+
+    // Imported documents are public, and private media is never served to them without a signature
+    if planned_pages
+        .iter()
+        .map(|p| &p.image_path)
+        .chain(planned_tracks.iter().map(|t| &t.audio_path))
+        .any(|path| !pages::media_key_allowed(false, path))
+    {
+        return Err(format!(
+            "Media of imported documents must not be stored under {}",
+            schema::PRIVATE_MEDIA_PREFIX
+        )
+        .into());
+    }
+
+    Ok(Plan {
+        title,
+        language,
+        level,
+        description,
+        pages: planned_pages,
+        tracks: planned_tracks,
+    })
+}
+
+fn print_plan(plan: &Plan) {
+    println!("Document: {}", plan.title);
+    println!("Language: {}  Level: {}", plan.language, plan.level);
+    if !plan.description.is_empty() {
+        println!("Description: {}", plan.description);
+    }
+    println!("Pages:");
+    for p in plan.pages.iter() {
+        println!(
+            "  {:>4}  {}  aspect ratio {:.4}  height {:.4}",
+            p.page_number, p.image_path, p.aspect_ratio, p.height
+        );
+    }
+    println!("Tracks:");
+    for t in plan.tracks.iter() {
+        println!("  {:>4}  {}  \"{}\"", t.track_number, t.audio_path, t.title);
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let opt = Opt::from_args();
+
+    let plan = plan(&opt)?;
+    print_plan(&plan);
+
+    if opt.dry_run {
+        return Ok(());
+    }
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&env::var("DATABASE_URL")?)
+        .await?;
+
+    let mut tx = pool.begin().await?;
+
+    let document = sqlx::query!(
+        "INSERT INTO documents (
+            title,
+            language,
+            level,
+            description,
+            created_at,
+            updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id;",
+        plan.title,
+        plan.language,
+        plan.level,
+        plan.description,
+        chrono::Utc::now(),
+        chrono::Utc::now()
+    )
+    .fetch_one(&mut tx)
+    .await?;
+
+    for p in plan.pages.iter() {
+        sqlx::query!(
+            "INSERT INTO pages (
+                page_number,
+                image_path,
+                aspect_ratio,
+                height,
+                document
+            ) VALUES ($1, $2, $3, $4, $5);",
+            p.page_number,
+            p.image_path,
+            p.aspect_ratio,
+            p.height,
+            document.id
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    for t in plan.tracks.iter() {
+        sqlx::query!(
+            "INSERT INTO tracks (
+                track_number,
+                title,
+                audio_path,
+                document
+            ) VALUES ($1, $2, $3, $4);",
+            t.track_number,
+            t.title,
+            t.audio_path,
+            document.id
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    log::info!(
+        "Imported document {} with {} pages and {} tracks",
+        document.id,
+        plan.pages.len(),
+        plan.tracks.len()
+    );
+
+    Ok(())
+}
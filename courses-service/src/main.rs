@@ -1,71 +1,206 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::env;
 
-use sqlx::postgres::{PgPoolOptions, Postgres};
+use sqlx::{
+    postgres::{PgListener, PgPoolOptions, Postgres},
+    Acquire,
+};
+use tokio::sync::{broadcast, mpsc};
 use tonic::{transport::Server, Request, Response, Status};
 
 use schema::{
     courses::{
         courses_server::{Courses, CoursesServer},
-        Anchor, Bookmark, CreateAnchorRequest, CreateAnchorResponse, CreateBookmarkRequest,
-        CreateBookmarkResponse, CreateUserAnchorRequest, CreateUserAnchorResponse,
-        DeleteAnchorRequest, DeleteAnchorResponse, DeleteBookmarkRequest, DeleteBookmarkResponse,
-        DeleteUserAnchorRequest, DeleteUserAnchorResponse, Document, GetAnchorsByIDsRequest,
-        GetAnchorsByIDsResponse, GetAnchorsByPageIDsRequest, GetAnchorsByPageIDsResponse,
-        GetBookmarksByIDsRequest, GetBookmarksByIDsResponse, GetDocumentBookmarksRequest,
-        GetDocumentBookmarksResponse, GetDocumentPagesRequest, GetDocumentPagesResponse,
-        GetDocumentTracksRequest, GetDocumentTracksResponse, GetDocumentsByIDsRequest,
+        AddPagesRequest, AddPagesResponse, AddTracksRequest, AddTracksResponse, Anchor,
+        AnchorEvent, AnchorEventKind, AnchorFile, AnchorFileFormat, Bookmark, CreateAnchorRequest,
+        CreateAnchorResponse, CreateBookmarkRequest, CreateBookmarkResponse, CreateDocumentRequest,
+        CreateDocumentResponse, CreateUserAnchorRequest, CreateUserAnchorResponse,
+        CreateUserNoteRequest, CreateUserNoteResponse, DeleteAnchorRequest, DeleteAnchorResponse,
+        DeleteBookmarkRequest, DeleteBookmarkResponse, DeleteDocumentRequest,
+        DeleteDocumentResponse, DeleteUserAnchorRequest, DeleteUserAnchorResponse,
+        DeleteUserNoteRequest, DeleteUserNoteResponse, Document, ExportDocumentAnchorsRequest,
+        ExportDocumentAnchorsResponse, GetAnchorHistoryRequest, GetAnchorHistoryResponse,
+        GetAnchorsByIDsRequest, GetAnchorsByIDsResponse, GetAnchorsByPageIDsRequest,
+        GetAnchorsByPageIDsResponse, GetBookmarksByIDsRequest, GetBookmarksByIDsResponse,
+        GetDocumentBookmarksRequest, GetDocumentBookmarksResponse, GetDocumentPagesRequest,
+        GetDocumentPagesResponse, GetDocumentTracksRequest, GetDocumentTracksResponse,
+        GetDocumentUserNotesRequest, GetDocumentUserNotesResponse, GetDocumentsByIDsRequest,
         GetDocumentsByIDsResponse, GetDocumentsRequest, GetDocumentsResponse, GetPagesByIDsRequest,
-        GetPagesByIDsResponse, GetTracksByIDsRequest, GetTracksByIDsResponse,
-        GetUserAnchorsByIDsRequest, GetUserAnchorsByIDsResponse, GetUserAnchorsByPageIDsRequest,
-        GetUserAnchorsByPageIDsResponse, Page, PageAnchors, PageUserAnchors, Track,
-        UpdateTrackTitleRequest, UpdateTrackTitleResponse, UserAnchor,
+        GetPagesByIDsResponse, GetProgressRequest, GetProgressResponse,
+        GetTextRegionsByPageIDsRequest, GetTextRegionsByPageIDsResponse, GetTracksByIDsRequest,
+        GetTracksByIDsResponse, GetUserAnchorsByIDsRequest, GetUserAnchorsByIDsResponse,
+        GetUserAnchorsByPageIDsRequest, GetUserAnchorsByPageIDsResponse,
+        ImportDocumentAnchorsRequest, ImportDocumentAnchorsResponse, Page, PageAnchors,
+        PageTextRegions, PageUserAnchors, Progress, PromoteUserAnchorRequest,
+        PromoteUserAnchorResponse, ReorderPagesRequest, ReorderPagesResponse, RestoreAnchorRequest,
+        RestoreAnchorResponse, SaveProgressRequest, SaveProgressResponse,
+        SearchDocumentTextRequest, SearchDocumentTextResponse, SearchDocumentsRequest,
+        SearchDocumentsResponse, TextMatch, TextRegion, Track, UpdateAnchorRequest,
+        UpdateAnchorResponse, UpdateTrackTitleRequest, UpdateTrackTitleResponse,
+        UpdateUserAnchorRequest, UpdateUserAnchorResponse, UpdateUserNoteRequest,
+        UpdateUserNoteResponse, UserAnchor, UserNote, WatchDocumentAnchorsRequest,
     },
     shared::UserRole,
 };
 
+mod anchor_changes;
+mod anchor_events;
+mod anchor_export;
+mod auth;
 mod errors;
+mod pages;
 
+use anchor_changes::{DocumentAnchorChange, WatchStream};
+use anchor_events::Snapshot;
+use anchor_export::ExportedAnchor;
 use errors::CoursesServiceError;
 
 #[derive(Debug)]
 pub struct CoursesService<T>
 where
-    for<'a> &'a T: sqlx::Executor<'a, Database = Postgres>,
+    for<'a> &'a T: sqlx::Executor<'a, Database = Postgres> + sqlx::Acquire<'a, Database = Postgres>,
 {
     executor: T,
+    anchor_changes: broadcast::Sender<DocumentAnchorChange>,
 }
 
 impl<T> CoursesService<T>
 where
-    for<'a> &'a T: sqlx::Executor<'a, Database = Postgres>,
+    for<'a> &'a T: sqlx::Executor<'a, Database = Postgres> + sqlx::Acquire<'a, Database = Postgres>,
 {
-    pub fn new(executor: T) -> Self {
-        Self { executor }
+    pub fn new(executor: T, anchor_changes: broadcast::Sender<DocumentAnchorChange>) -> Self {
+        Self {
+            executor,
+            anchor_changes,
+        }
+    }
+
+    /// Checks that the page and track a note is pinned to belong to its document
+    async fn check_note_pins(
+        &self,
+        document_id: i32,
+        page_id: i32,
+        track_id: i32,
+    ) -> Result<(), tonic::Status> {
+        let pins = (sqlx::query!(
+            r#"SELECT
+                ($1 = 0 OR EXISTS(SELECT 1 FROM pages WHERE id=$1 AND document=$3)) AS "page!",
+                ($2 = 0 OR EXISTS(SELECT 1 FROM tracks WHERE id=$2 AND document=$3)) AS "track!";"#,
+            page_id,
+            track_id,
+            document_id
+        )
+        .fetch_one(&self.executor)
+        .await)
+            .map_err(CoursesServiceError::from)?;
+
+        if pins.page && pins.track {
+            Ok(())
+        } else {
+            Err(tonic::Status::invalid_argument(format!(
+                "Notes can only be pinned to pages and tracks of document {}.",
+                document_id
+            )))
+        }
     }
 }
 
 #[tonic::async_trait]
 impl<T: Send + Sync + 'static> Courses for CoursesService<T>
 where
-    for<'a> &'a T: sqlx::Executor<'a, Database = Postgres>,
+    for<'a> &'a T: sqlx::Executor<'a, Database = Postgres> + sqlx::Acquire<'a, Database = Postgres>,
 {
+    type WatchDocumentAnchorsStream = WatchStream;
+
     async fn get_documents(
         &self,
         request: Request<GetDocumentsRequest>,
     ) -> Result<Response<GetDocumentsResponse>, Status> {
         let req = request.into_inner();
         let documents = sqlx::query!(
-            "SELECT * FROM documents LIMIT $1 OFFSET $2;",
+            "SELECT * FROM documents ORDER BY id LIMIT $1 OFFSET $2;",
             req.limit as i64,
             req.offset as i64
         )
         .fetch_all(&self.executor)
         .await
         .map_err(CoursesServiceError::from)?;
+        let total = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM documents;"#)
+            .fetch_one(&self.executor)
+            .await
+            .map_err(CoursesServiceError::from)?
+            .count;
 
         Ok(Response::new(GetDocumentsResponse {
-            total: documents.len() as i32,
+            total: total as i32,
+            documents: documents
+                .into_iter()
+                .map(|d| Document {
+                    id: d.id,
+                    title: d.title,
+                    created_at: d.created_at.to_rfc3339(),
+                    updated_at: d.updated_at.to_rfc3339(),
+                    language: d.language,
+                    level: d.level,
+                    description: d.description,
+                    private: d.private,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn search_documents(
+        &self,
+        request: Request<SearchDocumentsRequest>,
+    ) -> Result<Response<SearchDocumentsResponse>, Status> {
+        let req = request.into_inner();
+        let query = req.query.trim();
+        let documents = sqlx::query!(
+            "SELECT * FROM documents
+            WHERE ($1 = ''
+                OR title % $1
+                OR title ILIKE '%' || $1 || '%'
+                OR to_tsvector('simple', title || ' ' || description)
+                    @@ plainto_tsquery('simple', $1))
+            AND ($2 = '' OR language = $2)
+            AND ($3 = '' OR level = $3)
+            ORDER BY
+                CASE WHEN $4 = 1 THEN updated_at END DESC,
+                CASE WHEN $4 = 2 THEN title END,
+                similarity(title, $1) DESC,
+                id
+            LIMIT $5 OFFSET $6;",
+            query,
+            req.language,
+            req.level,
+            req.sort,
+            req.limit as i64,
+            req.offset as i64
+        )
+        .fetch_all(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?;
+        let total = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM documents
+            WHERE ($1 = ''
+                OR title % $1
+                OR title ILIKE '%' || $1 || '%'
+                OR to_tsvector('simple', title || ' ' || description)
+                    @@ plainto_tsquery('simple', $1))
+            AND ($2 = '' OR language = $2)
+            AND ($3 = '' OR level = $3);"#,
+            query,
+            req.language,
+            req.level
+        )
+        .fetch_one(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?
+        .count;
+
+        Ok(Response::new(SearchDocumentsResponse {
+            total: total as i32,
             documents: documents
                 .into_iter()
                 .map(|d| Document {
@@ -73,6 +208,10 @@ where
                     title: d.title,
                     created_at: d.created_at.to_rfc3339(),
                     updated_at: d.updated_at.to_rfc3339(),
+                    language: d.language,
+                    level: d.level,
+                    description: d.description,
+                    private: d.private,
                 })
                 .collect(),
         }))
@@ -99,18 +238,115 @@ where
                     title: d.title,
                     created_at: d.created_at.to_rfc3339(),
                     updated_at: d.updated_at.to_rfc3339(),
+                    language: d.language,
+                    level: d.level,
+                    description: d.description,
+                    private: d.private,
                 })
                 .collect(),
         }))
     }
 
+    async fn create_document(
+        &self,
+        request: tonic::Request<CreateDocumentRequest>,
+    ) -> Result<tonic::Response<CreateDocumentResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Administrator as i32 {
+                let d = (sqlx::query!(
+                    "INSERT INTO documents (
+                        title,
+                        language,
+                        level,
+                        description,
+                        private,
+                        created_at,
+                        updated_at
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *;",
+                    req.title,
+                    req.language,
+                    req.level,
+                    req.description,
+                    req.private,
+                    chrono::Utc::now(),
+                    chrono::Utc::now()
+                )
+                .fetch_one(&self.executor)
+                .await)
+                    .map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(CreateDocumentResponse {
+                    document: Some(Document {
+                        id: d.id,
+                        title: d.title,
+                        created_at: d.created_at.to_rfc3339(),
+                        updated_at: d.updated_at.to_rfc3339(),
+                        language: d.language,
+                        level: d.level,
+                        description: d.description,
+                        private: d.private,
+                    }),
+                }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only administrators may create documents.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to create a document.",
+            ))
+        }
+    }
+
+    async fn delete_document(
+        &self,
+        request: tonic::Request<DeleteDocumentRequest>,
+    ) -> Result<tonic::Response<DeleteDocumentResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Administrator as i32 {
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                // The anchors are removed by the cascade, so their deletion is recorded first
+                anchor_events::record_document_deletion(&mut tx, req.document_id, user.id)
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+                (sqlx::query!("DELETE FROM documents WHERE id=$1;", req.document_id)
+                    .execute(&mut tx)
+                    .await)
+                    .map_err(CoursesServiceError::from)?;
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(DeleteDocumentResponse { success: true }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only administrators may delete documents.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to delete a document.",
+            ))
+        }
+    }
+
     async fn get_document_pages(
         &self,
         request: tonic::Request<GetDocumentPagesRequest>,
     ) -> Result<tonic::Response<GetDocumentPagesResponse>, tonic::Status> {
         let req = request.into_inner();
         let pages = sqlx::query!(
-            "SELECT * FROM pages WHERE document=$1 LIMIT $2 OFFSET $3;",
+            "SELECT * FROM pages WHERE document=$1 ORDER BY page_number, id LIMIT $2 OFFSET $3;",
             req.document_id,
             req.limit as i64,
             req.offset as i64
@@ -118,9 +354,17 @@ where
         .fetch_all(&self.executor)
         .await
         .map_err(CoursesServiceError::from)?;
+        let total = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM pages WHERE document=$1;"#,
+            req.document_id
+        )
+        .fetch_one(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?
+        .count;
 
         Ok(Response::new(GetDocumentPagesResponse {
-            total: pages.len() as i32,
+            total: total as i32,
             pages: pages
                 .into_iter()
                 .map(|p| Page {
@@ -163,13 +407,195 @@ where
         }))
     }
 
+    async fn add_pages(
+        &self,
+        request: tonic::Request<AddPagesRequest>,
+    ) -> Result<tonic::Response<AddPagesResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Administrator as i32 {
+                if req.pages.iter().any(|p| p.width <= 0 || p.height <= 0) {
+                    return Err(tonic::Status::invalid_argument(
+                        "Page dimensions must be positive.",
+                    ));
+                }
+
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                let document = (sqlx::query!(
+                    "SELECT private FROM documents WHERE id=$1;",
+                    req.document_id
+                )
+                .fetch_one(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?;
+                if req
+                    .pages
+                    .iter()
+                    .any(|p| !pages::media_key_allowed(document.private, &p.image_path))
+                {
+                    return Err(media_key_error(document.private));
+                }
+
+                let last_page = (sqlx::query!(
+                    "SELECT page_number, aspect_ratio, height FROM pages
+                    WHERE document=$1 ORDER BY page_number DESC LIMIT 1;",
+                    req.document_id
+                )
+                .fetch_optional(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?;
+
+                let (first_page_number, start) = last_page
+                    .map(|p| (p.page_number + 1, p.height + p.aspect_ratio))
+                    .unwrap_or((1, 0.0));
+                let aspect_ratios = req
+                    .pages
+                    .iter()
+                    .map(|p| pages::aspect_ratio(p.width, p.height))
+                    .collect::<Vec<f32>>();
+                let heights = pages::heights(start, &aspect_ratios);
+
+                let mut added = vec![];
+                for (i, p) in req.pages.into_iter().enumerate() {
+                    let page = (sqlx::query!(
+                        "INSERT INTO pages (
+                            page_number,
+                            image_path,
+                            aspect_ratio,
+                            height,
+                            document
+                        ) VALUES ($1, $2, $3, $4, $5) RETURNING *;",
+                        first_page_number + i as i32,
+                        p.image_path,
+                        aspect_ratios[i],
+                        heights[i],
+                        req.document_id
+                    )
+                    .fetch_one(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?;
+
+                    added.push(Page {
+                        id: page.id,
+                        page_number: page.page_number,
+                        image_path: page.image_path,
+                        aspect_ratio: page.aspect_ratio,
+                        height: page.height,
+                        document_id: page.document,
+                    });
+                }
+
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(AddPagesResponse { pages: added }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only administrators may add pages.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to add pages.",
+            ))
+        }
+    }
+
+    async fn reorder_pages(
+        &self,
+        request: tonic::Request<ReorderPagesRequest>,
+    ) -> Result<tonic::Response<ReorderPagesResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Administrator as i32 {
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                let existing = (sqlx::query!(
+                    "SELECT id, aspect_ratio FROM pages WHERE document=$1;",
+                    req.document_id
+                )
+                .fetch_all(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?
+                    .into_iter()
+                    .map(|p| (p.id, p.aspect_ratio))
+                    .collect::<HashMap<i32, f32>>();
+
+                let mut requested = req.page_ids.clone();
+                requested.sort_unstable();
+                requested.dedup();
+                if requested.len() != req.page_ids.len()
+                    || requested.len() != existing.len()
+                    || requested.iter().any(|id| !existing.contains_key(id))
+                {
+                    return Err(tonic::Status::invalid_argument(
+                        "Page IDs must list every page of the document exactly once.",
+                    ));
+                }
+
+                let aspect_ratios = req
+                    .page_ids
+                    .iter()
+                    .map(|id| existing[id])
+                    .collect::<Vec<f32>>();
+                let heights = pages::heights(0.0, &aspect_ratios);
+
+                let mut reordered = vec![];
+                for (i, id) in req.page_ids.iter().enumerate() {
+                    let page = (sqlx::query!(
+                        "UPDATE pages SET page_number=$1, height=$2 WHERE id=$3 RETURNING *;",
+                        i as i32 + 1,
+                        heights[i],
+                        id
+                    )
+                    .fetch_one(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?;
+
+                    reordered.push(Page {
+                        id: page.id,
+                        page_number: page.page_number,
+                        image_path: page.image_path,
+                        aspect_ratio: page.aspect_ratio,
+                        height: page.height,
+                        document_id: page.document,
+                    });
+                }
+
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(ReorderPagesResponse { pages: reordered }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only administrators may reorder pages.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to reorder pages.",
+            ))
+        }
+    }
+
     async fn get_document_tracks(
         &self,
         request: tonic::Request<GetDocumentTracksRequest>,
     ) -> Result<tonic::Response<GetDocumentTracksResponse>, tonic::Status> {
         let req = request.into_inner();
         let tracks = sqlx::query!(
-            "SELECT * FROM tracks WHERE document=$1 LIMIT $2 OFFSET $3;",
+            "SELECT * FROM tracks WHERE document=$1 ORDER BY track_number, id LIMIT $2 OFFSET $3;",
             req.document_id,
             req.limit as i64,
             req.offset as i64
@@ -177,9 +603,17 @@ where
         .fetch_all(&self.executor)
         .await
         .map_err(CoursesServiceError::from)?;
+        let total = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM tracks WHERE document=$1;"#,
+            req.document_id
+        )
+        .fetch_one(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?
+        .count;
 
         Ok(Response::new(GetDocumentTracksResponse {
-            total: tracks.len() as i32,
+            total: total as i32,
             tracks: tracks
                 .into_iter()
                 .map(|t| Track {
@@ -220,13 +654,96 @@ where
         }))
     }
 
+    async fn add_tracks(
+        &self,
+        request: tonic::Request<AddTracksRequest>,
+    ) -> Result<tonic::Response<AddTracksResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Administrator as i32 {
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                let document = (sqlx::query!(
+                    "SELECT private FROM documents WHERE id=$1;",
+                    req.document_id
+                )
+                .fetch_one(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?;
+                if req
+                    .tracks
+                    .iter()
+                    .any(|t| !pages::media_key_allowed(document.private, &t.audio_path))
+                {
+                    return Err(media_key_error(document.private));
+                }
+
+                let last_track_number = (sqlx::query!(
+                    "SELECT MAX(track_number) AS track_number FROM tracks WHERE document=$1;",
+                    req.document_id
+                )
+                .fetch_one(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?
+                    .track_number
+                    .unwrap_or(0);
+
+                let mut added = vec![];
+                for (i, t) in req.tracks.into_iter().enumerate() {
+                    let track = (sqlx::query!(
+                        "INSERT INTO tracks (
+                            track_number,
+                            title,
+                            audio_path,
+                            document
+                        ) VALUES ($1, $2, $3, $4) RETURNING *;",
+                        last_track_number + i as i32 + 1,
+                        t.title,
+                        t.audio_path,
+                        req.document_id
+                    )
+                    .fetch_one(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?;
+
+                    added.push(Track {
+                        id: track.id,
+                        track_number: track.track_number,
+                        title: track.title,
+                        audio_path: track.audio_path,
+                        document_id: track.document,
+                    });
+                }
+
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(AddTracksResponse { tracks: added }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only administrators may add tracks.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to add tracks.",
+            ))
+        }
+    }
+
     async fn update_track_title(
         &self,
         request: tonic::Request<UpdateTrackTitleRequest>,
     ) -> Result<tonic::Response<UpdateTrackTitleResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
 
-        if let Some(user) = req.active_user {
+        if let Some(user) = active_user {
             if user.role == (UserRole::Moderator as i32)
                 || user.role == (UserRole::Administrator as i32)
             {
@@ -266,7 +783,7 @@ where
     ) -> Result<tonic::Response<GetDocumentBookmarksResponse>, tonic::Status> {
         let req = request.into_inner();
         let bookmarks = sqlx::query!(
-            "SELECT * FROM bookmarks WHERE document=$1 LIMIT $2 OFFSET $3;",
+            "SELECT * FROM bookmarks WHERE document=$1 ORDER BY id LIMIT $2 OFFSET $3;",
             req.document_id,
             req.limit as i64,
             req.offset as i64
@@ -274,9 +791,17 @@ where
         .fetch_all(&self.executor)
         .await
         .map_err(CoursesServiceError::from)?;
+        let total = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM bookmarks WHERE document=$1;"#,
+            req.document_id
+        )
+        .fetch_one(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?
+        .count;
 
         Ok(Response::new(GetDocumentBookmarksResponse {
-            total: bookmarks.len() as i32,
+            total: total as i32,
             bookmarks: bookmarks
                 .into_iter()
                 .map(|b| Bookmark {
@@ -319,8 +844,9 @@ where
         &self,
         request: tonic::Request<CreateBookmarkRequest>,
     ) -> Result<tonic::Response<CreateBookmarkResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
-        if let Some(user) = req.active_user {
+        if let Some(user) = active_user {
             if user.role == (UserRole::Moderator as i32)
                 || user.role == (UserRole::Administrator as i32)
             {
@@ -362,8 +888,9 @@ where
         &self,
         request: tonic::Request<DeleteBookmarkRequest>,
     ) -> Result<tonic::Response<DeleteBookmarkResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
-        if let Some(user) = req.active_user {
+        if let Some(user) = active_user {
             if user.role == (UserRole::Moderator as i32)
                 || user.role == (UserRole::Administrator as i32)
             {
@@ -521,14 +1048,18 @@ where
         &self,
         request: tonic::Request<CreateAnchorRequest>,
     ) -> Result<tonic::Response<CreateAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
 
-        let user_role = req
-            .active_user
-            .map(|u| u.role)
-            .unwrap_or(UserRole::Standard as i32);
+        if let Some(user) = active_user.filter(|u| {
+            u.role == UserRole::Moderator as i32 || u.role == UserRole::Administrator as i32
+        }) {
+            let mut tx = self
+                .executor
+                .begin()
+                .await
+                .map_err(CoursesServiceError::from)?;
 
-        if user_role == UserRole::Moderator as i32 || user_role == UserRole::Administrator as i32 {
             let a = (sqlx::query!(
                 "INSERT INTO anchors (
                     title,
@@ -549,10 +1080,15 @@ where
                 chrono::Utc::now(),
                 chrono::Utc::now()
             )
-            .fetch_one(&self.executor)
+            .fetch_one(&mut tx)
             .await)
                 .map_err(CoursesServiceError::from)?;
 
+            anchor_events::record(&mut tx, a.id, AnchorEventKind::Create, user.id, None)
+                .await
+                .map_err(CoursesServiceError::from)?;
+            tx.commit().await.map_err(CoursesServiceError::from)?;
+
             Ok(Response::new(CreateAnchorResponse {
                 anchor: Some(Anchor {
                     id: a.id,
@@ -577,9 +1113,10 @@ where
         &self,
         request: tonic::Request<CreateUserAnchorRequest>,
     ) -> Result<tonic::Response<CreateUserAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
 
-        if let Some(user) = req.active_user {
+        if let Some(user) = active_user {
             let a = (sqlx::query!(
                 "INSERT INTO user_anchors (
                     title,
@@ -627,38 +1164,82 @@ where
         }
     }
 
-    async fn delete_anchor(
+    async fn update_anchor(
         &self,
-        request: tonic::Request<DeleteAnchorRequest>,
-    ) -> Result<tonic::Response<DeleteAnchorResponse>, tonic::Status> {
+        request: tonic::Request<UpdateAnchorRequest>,
+    ) -> Result<tonic::Response<UpdateAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
 
-        let user_role = req
-            .active_user
-            .map(|u| u.role)
-            .unwrap_or(UserRole::Standard as i32);
+        if let Some(user) = active_user.filter(|u| {
+            u.role == UserRole::Moderator as i32 || u.role == UserRole::Administrator as i32
+        }) {
+            let mut tx = self
+                .executor
+                .begin()
+                .await
+                .map_err(CoursesServiceError::from)?;
 
-        if user_role == UserRole::Moderator as i32 || user_role == UserRole::Administrator as i32 {
-            (sqlx::query!("DELETE FROM anchors WHERE id=$1;", req.id)
-                .execute(&self.executor)
-                .await)
+            let before = anchor_events::snapshot(&mut tx, req.id)
+                .await
                 .map_err(CoursesServiceError::from)?;
 
-            Ok(Response::new(DeleteAnchorResponse { success: true }))
+            let a = (sqlx::query!(
+                "UPDATE anchors SET
+                    title=$1,
+                    track_time=$2,
+                    position_top=$3,
+                    position_left=$4,
+                    document_page=$5,
+                    track=$6,
+                    updated_at=$7
+                WHERE id=$8 RETURNING *;",
+                req.title,
+                req.track_time,
+                req.position_top,
+                req.position_left,
+                req.page_id,
+                req.track_id,
+                chrono::Utc::now(),
+                req.id
+            )
+            .fetch_one(&mut tx)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            anchor_events::record(&mut tx, a.id, AnchorEventKind::Move, user.id, before)
+                .await
+                .map_err(CoursesServiceError::from)?;
+            tx.commit().await.map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(UpdateAnchorResponse {
+                anchor: Some(Anchor {
+                    id: a.id,
+                    title: a.title.unwrap_or("".to_owned()),
+                    track_time: a.track_time,
+                    position_top: a.position_top,
+                    position_left: a.position_left,
+                    page_id: a.document_page,
+                    track_id: a.track,
+                    created_at: a.created_at.to_rfc3339(),
+                    updated_at: a.updated_at.to_rfc3339(),
+                }),
+            }))
         } else {
             Err(tonic::Status::permission_denied(
-                "Only moderators may delete anchors.",
+                "Only moderators may update anchors.",
             ))
         }
     }
 
-    async fn delete_user_anchor(
+    async fn update_user_anchor(
         &self,
-        request: tonic::Request<DeleteUserAnchorRequest>,
-    ) -> Result<tonic::Response<DeleteUserAnchorResponse>, tonic::Status> {
+        request: tonic::Request<UpdateUserAnchorRequest>,
+    ) -> Result<tonic::Response<UpdateUserAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
         let req = request.into_inner();
 
-        if let Some(user) = req.active_user {
+        if let Some(user) = active_user {
             let result =
                 (sqlx::query!("SELECT owning_user FROM user_anchors WHERE id=$1;", req.id)
                     .fetch_one(&self.executor)
@@ -669,40 +1250,1249 @@ where
                 || user.role == UserRole::Moderator as i32
                 || user.role == UserRole::Administrator as i32
             {
-                (sqlx::query!("DELETE FROM user_anchors WHERE id=$1;", req.id)
-                    .execute(&self.executor)
-                    .await)
+                let a = (sqlx::query!(
+                    "UPDATE user_anchors SET
+                        title=$1,
+                        track_time=$2,
+                        position_top=$3,
+                        position_left=$4,
+                        document_page=$5,
+                        track=$6,
+                        updated_at=$7
+                    WHERE id=$8 RETURNING *;",
+                    req.title,
+                    req.track_time,
+                    req.position_top,
+                    req.position_left,
+                    req.page_id,
+                    req.track_id,
+                    chrono::Utc::now(),
+                    req.id
+                )
+                .fetch_one(&self.executor)
+                .await)
                     .map_err(CoursesServiceError::from)?;
 
-                Ok(Response::new(DeleteUserAnchorResponse { success: true }))
+                Ok(Response::new(UpdateUserAnchorResponse {
+                    user_anchor: Some(UserAnchor {
+                        id: a.id,
+                        title: a.title.unwrap_or("".to_owned()),
+                        track_time: a.track_time,
+                        position_top: a.position_top,
+                        position_left: a.position_left,
+                        page_id: a.document_page,
+                        track_id: a.track,
+                        created_at: a.created_at.to_rfc3339(),
+                        updated_at: a.updated_at.to_rfc3339(),
+                        owner: a.owning_user,
+                    }),
+                }))
             } else {
                 Err(tonic::Status::permission_denied(
-                    "You may not delete other users' anchors.",
+                    "You may not update other users' anchors.",
                 ))
             }
         } else {
             Err(tonic::Status::permission_denied(
-                "You must be logged in to delete a user anchor.",
+                "You must be logged in to update a user anchor.",
             ))
         }
     }
-}
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    dotenv::dotenv().ok();
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
-    let addr = "[::0]:50051".parse()?;
+    async fn promote_user_anchor(
+        &self,
+        request: tonic::Request<PromoteUserAnchorRequest>,
+    ) -> Result<tonic::Response<PromoteUserAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
 
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&env::var("DATABASE_URL")?)
-        .await?;
+        if let Some(user) = active_user {
+            if user.role == UserRole::Moderator as i32
+                || user.role == UserRole::Administrator as i32
+            {
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                // Kept so that restoring the promotion gives the learner their anchor back
+                let before = anchor_events::user_anchor_snapshot(&mut tx, req.id)
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+                let ua =
+                    (sqlx::query!("DELETE FROM user_anchors WHERE id=$1 RETURNING *;", req.id)
+                        .fetch_one(&mut tx)
+                        .await)
+                        .map_err(CoursesServiceError::from)?;
+
+                let a = (sqlx::query!(
+                    "INSERT INTO anchors (
+                        title,
+                        track_time,
+                        position_top,
+                        position_left,
+                        document_page,
+                        track,
+                        original_owner,
+                        approved_by,
+                        created_at,
+                        updated_at
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *;",
+                    ua.title,
+                    ua.track_time,
+                    ua.position_top,
+                    ua.position_left,
+                    ua.document_page,
+                    ua.track,
+                    ua.owning_user,
+                    user.id,
+                    ua.created_at,
+                    chrono::Utc::now()
+                )
+                .fetch_one(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?;
+
+                anchor_events::record(&mut tx, a.id, AnchorEventKind::Promote, user.id, before)
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(PromoteUserAnchorResponse {
+                    anchor: Some(Anchor {
+                        id: a.id,
+                        title: a.title.unwrap_or("".to_owned()),
+                        track_time: a.track_time,
+                        position_top: a.position_top,
+                        position_left: a.position_left,
+                        page_id: a.document_page,
+                        track_id: a.track,
+                        created_at: a.created_at.to_rfc3339(),
+                        updated_at: a.updated_at.to_rfc3339(),
+                    }),
+                }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only moderators may promote user anchors.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to promote a user anchor.",
+            ))
+        }
+    }
+
+    async fn export_document_anchors(
+        &self,
+        request: tonic::Request<ExportDocumentAnchorsRequest>,
+    ) -> Result<tonic::Response<ExportDocumentAnchorsResponse>, tonic::Status> {
+        let req = request.into_inner();
+        let format = AnchorFileFormat::from_i32(req.format)
+            .ok_or_else(|| tonic::Status::invalid_argument("Unknown anchor file format."))?;
+
+        let anchors = sqlx::query!(
+            "SELECT
+                anchors.title,
+                anchors.track_time,
+                anchors.position_top,
+                anchors.position_left,
+                anchors.document_page,
+                anchors.track
+            FROM anchors INNER JOIN pages ON pages.id = anchors.document_page
+            WHERE pages.document=$1
+            ORDER BY pages.page_number, anchors.track_time;",
+            req.document_id
+        )
+        .fetch_all(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?
+        .into_iter()
+        .map(|a| ExportedAnchor {
+            title: a.title.unwrap_or("".to_owned()),
+            track_time: a.track_time,
+            position_top: a.position_top,
+            position_left: a.position_left,
+            page_id: a.document_page,
+            track_id: a.track,
+        })
+        .collect::<Vec<ExportedAnchor>>();
+
+        let files = if format == AnchorFileFormat::Json {
+            vec![AnchorFile {
+                file_name: format!("document-{}-anchors.json", req.document_id),
+                content: anchor_export::to_json(req.document_id, anchors),
+                track_id: 0,
+            }]
+        } else {
+            let tracks = sqlx::query!(
+                "SELECT id, track_number, title FROM tracks WHERE document=$1 ORDER BY track_number;",
+                req.document_id
+            )
+            .fetch_all(&self.executor)
+            .await
+            .map_err(CoursesServiceError::from)?;
+
+            tracks
+                .into_iter()
+                .map(|t| {
+                    let mut track_anchors = anchors
+                        .iter()
+                        .filter(|a| a.track_id == t.id)
+                        .collect::<Vec<&ExportedAnchor>>();
+                    track_anchors.sort_by(|a, b| {
+                        a.track_time
+                            .partial_cmp(&b.track_time)
+                            .unwrap_or(Ordering::Equal)
+                    });
+
+                    if format == AnchorFileFormat::Webvtt {
+                        AnchorFile {
+                            file_name: format!("track-{}.vtt", t.track_number),
+                            content: anchor_export::to_webvtt(&track_anchors),
+                            track_id: t.id,
+                        }
+                    } else {
+                        AnchorFile {
+                            file_name: format!("track-{}.lrc", t.track_number),
+                            content: anchor_export::to_lrc(&t.title, &track_anchors),
+                            track_id: t.id,
+                        }
+                    }
+                })
+                .collect()
+        };
+
+        Ok(Response::new(ExportDocumentAnchorsResponse { files }))
+    }
+
+    async fn import_document_anchors(
+        &self,
+        request: tonic::Request<ImportDocumentAnchorsRequest>,
+    ) -> Result<tonic::Response<ImportDocumentAnchorsResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Moderator as i32
+                || user.role == UserRole::Administrator as i32
+            {
+                let format = AnchorFileFormat::from_i32(req.format).ok_or_else(|| {
+                    tonic::Status::invalid_argument("Unknown anchor file format.")
+                })?;
+
+                let mut anchors = vec![];
+                let mut unplaced = vec![];
+                for file in req.files.iter() {
+                    match format {
+                        AnchorFileFormat::Json => anchors.extend(
+                            anchor_export::from_json(&file.content)
+                                .map_err(tonic::Status::invalid_argument)?,
+                        ),
+                        AnchorFileFormat::Webvtt => anchors.extend(
+                            anchor_export::from_webvtt(&file.content, file.track_id)
+                                .map_err(tonic::Status::invalid_argument)?,
+                        ),
+                        AnchorFileFormat::Lrc => unplaced.extend(
+                            anchor_export::from_lrc(&file.content, file.track_id)
+                                .map_err(tonic::Status::invalid_argument)?,
+                        ),
+                    }
+                }
+
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                // LRC lines only have a time, so they're placed like the existing anchor of the
+                // track at that time, or else at the top of the page of the anchor before them,
+                // or of the first page when the track has none
+                if !unplaced.is_empty() {
+                    let existing = (sqlx::query!(
+                        "SELECT track, track_time, position_top, position_left, document_page
+                        FROM anchors WHERE track IN (SELECT id FROM tracks WHERE document=$1)
+                        ORDER BY track_time;",
+                        req.document_id
+                    )
+                    .fetch_all(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?;
+                    let first_page = (sqlx::query!(
+                        "SELECT id FROM pages WHERE document=$1 ORDER BY page_number LIMIT 1;",
+                        req.document_id
+                    )
+                    .fetch_optional(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?
+                        .map(|p| p.id)
+                        .ok_or_else(|| {
+                            tonic::Status::invalid_argument("The document has no pages.")
+                        })?;
+
+                    for a in unplaced {
+                        let on_track = || existing.iter().filter(|e| e.track == a.track_id);
+                        // LRC times are rounded to hundredths of a second
+                        let same = on_track().find(|e| (e.track_time - a.track_time).abs() < 0.01);
+                        let before = on_track().filter(|e| e.track_time <= a.track_time).last();
+                        let (position_top, position_left, page_id) = match (same, before) {
+                            (Some(e), _) => (e.position_top, e.position_left, e.document_page),
+                            (None, Some(e)) => (0.0, 0.0, e.document_page),
+                            (None, None) => (0.0, 0.0, first_page),
+                        };
+                        anchors.push(ExportedAnchor {
+                            title: a.title,
+                            track_time: a.track_time,
+                            position_top,
+                            position_left,
+                            page_id,
+                            track_id: a.track_id,
+                        });
+                    }
+                }
+
+                let page_ids =
+                    (sqlx::query!("SELECT id FROM pages WHERE document=$1;", req.document_id)
+                        .fetch_all(&mut tx)
+                        .await)
+                        .map_err(CoursesServiceError::from)?
+                        .into_iter()
+                        .map(|p| p.id)
+                        .collect::<HashSet<i32>>();
+                let track_ids =
+                    (sqlx::query!("SELECT id FROM tracks WHERE document=$1;", req.document_id)
+                        .fetch_all(&mut tx)
+                        .await)
+                        .map_err(CoursesServiceError::from)?
+                        .into_iter()
+                        .map(|t| t.id)
+                        .collect::<HashSet<i32>>();
+
+                if let Some(a) = anchors
+                    .iter()
+                    .find(|a| !page_ids.contains(&a.page_id) || !track_ids.contains(&a.track_id))
+                {
+                    return Err(tonic::Status::invalid_argument(format!(
+                        "Anchor at {} on page {} does not belong to document {}.",
+                        a.track_time, a.page_id, req.document_id
+                    )));
+                }
+
+                if req.replace {
+                    anchor_events::record_document_deletion(&mut tx, req.document_id, user.id)
+                        .await
+                        .map_err(CoursesServiceError::from)?;
+                    (sqlx::query!(
+                        "DELETE FROM anchors WHERE document_page IN
+                            (SELECT id FROM pages WHERE document=$1);",
+                        req.document_id
+                    )
+                    .execute(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?;
+                }
+
+                for a in anchors.iter() {
+                    let inserted = (sqlx::query!(
+                        "INSERT INTO anchors (
+                            title,
+                            track_time,
+                            position_top,
+                            position_left,
+                            document_page,
+                            track,
+                            approved_by,
+                            created_at,
+                            updated_at
+                        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id;",
+                        a.title,
+                        a.track_time,
+                        a.position_top,
+                        a.position_left,
+                        a.page_id,
+                        a.track_id,
+                        user.id,
+                        chrono::Utc::now(),
+                        chrono::Utc::now()
+                    )
+                    .fetch_one(&mut tx)
+                    .await)
+                        .map_err(CoursesServiceError::from)?;
+
+                    anchor_events::record(
+                        &mut tx,
+                        inserted.id,
+                        AnchorEventKind::Create,
+                        user.id,
+                        None,
+                    )
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+                }
+
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(ImportDocumentAnchorsResponse {
+                    imported: anchors.len() as i32,
+                    page_ids: page_ids.into_iter().collect(),
+                }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only moderators may import anchors.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to import anchors.",
+            ))
+        }
+    }
+
+    async fn delete_anchor(
+        &self,
+        request: tonic::Request<DeleteAnchorRequest>,
+    ) -> Result<tonic::Response<DeleteAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user.filter(|u| {
+            u.role == UserRole::Moderator as i32 || u.role == UserRole::Administrator as i32
+        }) {
+            let mut tx = self
+                .executor
+                .begin()
+                .await
+                .map_err(CoursesServiceError::from)?;
+
+            let before = anchor_events::snapshot(&mut tx, req.id)
+                .await
+                .map_err(CoursesServiceError::from)?;
+
+            (sqlx::query!("DELETE FROM anchors WHERE id=$1;", req.id)
+                .execute(&mut tx)
+                .await)
+                .map_err(CoursesServiceError::from)?;
+
+            // Nothing to record when the anchor did not exist
+            if before.is_some() {
+                anchor_events::record(&mut tx, req.id, AnchorEventKind::Delete, user.id, before)
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+            }
+            tx.commit().await.map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(DeleteAnchorResponse { success: true }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "Only moderators may delete anchors.",
+            ))
+        }
+    }
+
+    async fn delete_user_anchor(
+        &self,
+        request: tonic::Request<DeleteUserAnchorRequest>,
+    ) -> Result<tonic::Response<DeleteUserAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            let result =
+                (sqlx::query!("SELECT owning_user FROM user_anchors WHERE id=$1;", req.id)
+                    .fetch_one(&self.executor)
+                    .await)
+                    .map_err(CoursesServiceError::from)?;
+
+            if result.owning_user == user.id
+                || user.role == UserRole::Moderator as i32
+                || user.role == UserRole::Administrator as i32
+            {
+                (sqlx::query!("DELETE FROM user_anchors WHERE id=$1;", req.id)
+                    .execute(&self.executor)
+                    .await)
+                    .map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(DeleteUserAnchorResponse { success: true }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "You may not delete other users' anchors.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to delete a user anchor.",
+            ))
+        }
+    }
+
+    async fn watch_document_anchors(
+        &self,
+        request: Request<WatchDocumentAnchorsRequest>,
+    ) -> Result<Response<Self::WatchDocumentAnchorsStream>, Status> {
+        let document_id = request.into_inner().document_id;
+        let mut changes = self.anchor_changes.subscribe();
+        let (mut tx, rx) = mpsc::channel(16);
+        let (stream, mut left) = WatchStream::new(rx);
+
+        tokio::spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    received = changes.recv() => received,
+                    // The watcher has disconnected, however quiet the document is
+                    _ = &mut left => break,
+                };
+                let result = match received {
+                    Ok(change) if change.document_id == document_id => {
+                        tx.send(Ok(change.change)).await
+                    }
+                    Ok(_) => continue,
+                    // The watcher has to reload the anchors if it fell behind
+                    Err(broadcast::RecvError::Lagged(_)) => {
+                        let _ = tx
+                            .send(Err(Status::data_loss("Anchor changes were missed")))
+                            .await;
+                        break;
+                    }
+                    Err(broadcast::RecvError::Closed) => break,
+                };
+
+                // The watcher disconnected while the change was being sent
+                if result.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(stream))
+    }
+
+    async fn get_anchor_history(
+        &self,
+        request: tonic::Request<GetAnchorHistoryRequest>,
+    ) -> Result<tonic::Response<GetAnchorHistoryResponse>, tonic::Status> {
+        let req = request.into_inner();
+
+        let events = (sqlx::query!(
+            "SELECT
+                id,
+                anchor_id,
+                kind,
+                actor,
+                before::text AS before,
+                after::text AS after,
+                created_at
+            FROM anchor_events WHERE anchor_id=$1 ORDER BY id;",
+            req.anchor_id
+        )
+        .fetch_all(&self.executor)
+        .await)
+            .map_err(CoursesServiceError::from)?;
+
+        Ok(Response::new(GetAnchorHistoryResponse {
+            events: events
+                .into_iter()
+                .map(|e| {
+                    Ok(AnchorEvent {
+                        id: e.id,
+                        anchor_id: e.anchor_id,
+                        kind: anchor_events::kind_from_name(&e.kind) as i32,
+                        actor: e.actor,
+                        // The user anchor replaced by a promotion was not an approved anchor
+                        before: anchor_events::parse_snapshot(e.before)?
+                            .and_then(Snapshot::into_anchor)
+                            .map(Anchor::from),
+                        after: anchor_events::parse_snapshot(e.after)?
+                            .and_then(Snapshot::into_anchor)
+                            .map(Anchor::from),
+                        created_at: e.created_at.to_rfc3339(),
+                    })
+                })
+                .collect::<Result<Vec<AnchorEvent>, CoursesServiceError>>()?,
+        }))
+    }
+
+    async fn restore_anchor(
+        &self,
+        request: tonic::Request<RestoreAnchorRequest>,
+    ) -> Result<tonic::Response<RestoreAnchorResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if user.role == UserRole::Moderator as i32
+                || user.role == UserRole::Administrator as i32
+            {
+                let mut tx = self
+                    .executor
+                    .begin()
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                let event = (sqlx::query!(
+                    "SELECT anchor_id, before::text AS before FROM anchor_events WHERE id=$1;",
+                    req.event_id
+                )
+                .fetch_optional(&mut tx)
+                .await)
+                    .map_err(CoursesServiceError::from)?
+                    .ok_or_else(|| tonic::Status::not_found("Anchor event not found."))?;
+
+                let current = anchor_events::snapshot(&mut tx, event.anchor_id)
+                    .await
+                    .map_err(CoursesServiceError::from)?;
+
+                let replaced = anchor_events::parse_snapshot(current.clone())?
+                    .and_then(Snapshot::into_anchor)
+                    .map(Anchor::from);
+                let mut user_anchor = None;
+                let before = anchor_events::parse_snapshot(event.before)?;
+                let pins = match before {
+                    Some(Snapshot::Anchor(ref a)) => Some((a.document_page, a.track)),
+                    Some(Snapshot::UserAnchor(ref ua)) => Some((ua.document_page, ua.track)),
+                    None => None,
+                };
+                if let Some((page_id, track_id)) = pins {
+                    if !anchor_events::pins_exist(&mut tx, page_id, track_id)
+                        .await
+                        .map_err(CoursesServiceError::from)?
+                    {
+                        return Err(tonic::Status::failed_precondition(
+                            "The page or track of the anchor has been deleted since.",
+                        ));
+                    }
+                }
+                let anchor = match before {
+                    Some(Snapshot::Anchor(a)) => {
+                        let created_at = chrono::DateTime::parse_from_rfc3339(&a.created_at)
+                            .map_err(CoursesServiceError::from)?
+                            .with_timezone(&chrono::Utc);
+
+                        let a = (sqlx::query!(
+                            "INSERT INTO anchors (
+                                id,
+                                title,
+                                track_time,
+                                position_top,
+                                position_left,
+                                document_page,
+                                track,
+                                original_owner,
+                                approved_by,
+                                created_at,
+                                updated_at
+                            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                            ON CONFLICT (id) DO UPDATE SET
+                                title=EXCLUDED.title,
+                                track_time=EXCLUDED.track_time,
+                                position_top=EXCLUDED.position_top,
+                                position_left=EXCLUDED.position_left,
+                                document_page=EXCLUDED.document_page,
+                                track=EXCLUDED.track,
+                                original_owner=EXCLUDED.original_owner,
+                                approved_by=EXCLUDED.approved_by,
+                                updated_at=EXCLUDED.updated_at
+                            RETURNING *;",
+                            a.id,
+                            a.title,
+                            a.track_time,
+                            a.position_top,
+                            a.position_left,
+                            a.document_page,
+                            a.track,
+                            a.original_owner,
+                            a.approved_by,
+                            created_at,
+                            chrono::Utc::now()
+                        )
+                        .fetch_one(&mut tx)
+                        .await)
+                            .map_err(CoursesServiceError::from)?;
+
+                        Some(Anchor {
+                            id: a.id,
+                            title: a.title.unwrap_or("".to_owned()),
+                            track_time: a.track_time,
+                            position_top: a.position_top,
+                            position_left: a.position_left,
+                            page_id: a.document_page,
+                            track_id: a.track,
+                            created_at: a.created_at.to_rfc3339(),
+                            updated_at: a.updated_at.to_rfc3339(),
+                        })
+                    }
+                    // The anchor was promoted from a user anchor, which is given back to its
+                    // owner in place of the anchor
+                    Some(Snapshot::UserAnchor(ua)) => {
+                        let created_at = chrono::DateTime::parse_from_rfc3339(&ua.created_at)
+                            .map_err(CoursesServiceError::from)?
+                            .with_timezone(&chrono::Utc);
+
+                        (sqlx::query!("DELETE FROM anchors WHERE id=$1;", event.anchor_id)
+                            .execute(&mut tx)
+                            .await)
+                            .map_err(CoursesServiceError::from)?;
+                        user_anchor = (sqlx::query!(
+                            "INSERT INTO user_anchors (
+                                id,
+                                title,
+                                track_time,
+                                position_top,
+                                position_left,
+                                document_page,
+                                track,
+                                owning_user,
+                                created_at,
+                                updated_at
+                            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                            ON CONFLICT (id) DO NOTHING RETURNING *;",
+                            ua.id,
+                            ua.title,
+                            ua.track_time,
+                            ua.position_top,
+                            ua.position_left,
+                            ua.document_page,
+                            ua.track,
+                            ua.owning_user,
+                            created_at,
+                            chrono::Utc::now()
+                        )
+                        .fetch_optional(&mut tx)
+                        .await)
+                            .map_err(CoursesServiceError::from)?
+                            .map(|a| UserAnchor {
+                                id: a.id,
+                                title: a.title.unwrap_or("".to_owned()),
+                                track_time: a.track_time,
+                                position_top: a.position_top,
+                                position_left: a.position_left,
+                                page_id: a.document_page,
+                                track_id: a.track,
+                                created_at: a.created_at.to_rfc3339(),
+                                updated_at: a.updated_at.to_rfc3339(),
+                                owner: a.owning_user,
+                            });
+
+                        None
+                    }
+                    // The anchor did not exist before the event, so restoring removes it
+                    None => {
+                        (sqlx::query!("DELETE FROM anchors WHERE id=$1;", event.anchor_id)
+                            .execute(&mut tx)
+                            .await)
+                            .map_err(CoursesServiceError::from)?;
+
+                        None
+                    }
+                };
+
+                anchor_events::record(
+                    &mut tx,
+                    event.anchor_id,
+                    AnchorEventKind::Restore,
+                    user.id,
+                    current,
+                )
+                .await
+                .map_err(CoursesServiceError::from)?;
+                tx.commit().await.map_err(CoursesServiceError::from)?;
+
+                Ok(Response::new(RestoreAnchorResponse {
+                    anchor,
+                    replaced,
+                    user_anchor,
+                }))
+            } else {
+                Err(tonic::Status::permission_denied(
+                    "Only moderators may restore anchors.",
+                ))
+            }
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to restore an anchor.",
+            ))
+        }
+    }
+
+    async fn save_progress(
+        &self,
+        request: tonic::Request<SaveProgressRequest>,
+    ) -> Result<tonic::Response<SaveProgressResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            let belongs = (sqlx::query!(
+                r#"SELECT
+                    EXISTS(SELECT 1 FROM tracks WHERE id=$1 AND document=$3) AS "track!",
+                    EXISTS(SELECT 1 FROM pages WHERE id=$2 AND document=$3) AS "page!";"#,
+                req.track_id,
+                req.page_id,
+                req.document_id
+            )
+            .fetch_one(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            if !belongs.track || !belongs.page {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "Track {} and page {} must belong to document {}.",
+                    req.track_id, req.page_id, req.document_id
+                )));
+            }
+
+            let p = (sqlx::query!(
+                "INSERT INTO progress (
+                    owning_user,
+                    document,
+                    track,
+                    track_time,
+                    document_page,
+                    completion,
+                    updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (owning_user, document) DO UPDATE SET
+                    track=EXCLUDED.track,
+                    track_time=EXCLUDED.track_time,
+                    document_page=EXCLUDED.document_page,
+                    completion=EXCLUDED.completion,
+                    updated_at=EXCLUDED.updated_at
+                RETURNING *;",
+                user.id,
+                req.document_id,
+                req.track_id,
+                req.track_time.max(0.0),
+                req.page_id,
+                req.completion.max(0.0).min(100.0),
+                chrono::Utc::now()
+            )
+            .fetch_one(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(SaveProgressResponse {
+                progress: Some(Progress {
+                    document_id: p.document,
+                    track_id: p.track,
+                    track_time: p.track_time,
+                    page_id: p.document_page,
+                    completion: p.completion,
+                    updated_at: p.updated_at.to_rfc3339(),
+                }),
+            }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to save your progress.",
+            ))
+        }
+    }
+
+    async fn get_progress(
+        &self,
+        request: tonic::Request<GetProgressRequest>,
+    ) -> Result<tonic::Response<GetProgressResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            let p = (sqlx::query!(
+                "SELECT * FROM progress WHERE owning_user=$1 AND document=$2;",
+                user.id,
+                req.document_id
+            )
+            .fetch_optional(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(GetProgressResponse {
+                progress: p.map(|p| Progress {
+                    document_id: p.document,
+                    track_id: p.track,
+                    track_time: p.track_time,
+                    page_id: p.document_page,
+                    completion: p.completion,
+                    updated_at: p.updated_at.to_rfc3339(),
+                }),
+            }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to view your progress.",
+            ))
+        }
+    }
+
+    async fn get_document_user_notes(
+        &self,
+        request: tonic::Request<GetDocumentUserNotesRequest>,
+    ) -> Result<tonic::Response<GetDocumentUserNotesResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            let notes = (sqlx::query!(
+                "SELECT * FROM user_notes WHERE owning_user=$1 AND document=$2 ORDER BY created_at;",
+                user.id,
+                req.document_id
+            )
+            .fetch_all(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(GetDocumentUserNotesResponse {
+                user_notes: notes
+                    .into_iter()
+                    .map(|n| UserNote {
+                        id: n.id,
+                        document_id: n.document,
+                        content: n.content,
+                        page_id: n.document_page.unwrap_or(0),
+                        position_top: n.position_top.unwrap_or(0.0),
+                        position_left: n.position_left.unwrap_or(0.0),
+                        track_id: n.track.unwrap_or(0),
+                        track_time: n.track_time.unwrap_or(0.0),
+                        created_at: n.created_at.to_rfc3339(),
+                        updated_at: n.updated_at.to_rfc3339(),
+                    })
+                    .collect(),
+            }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to view your notes.",
+            ))
+        }
+    }
+
+    async fn create_user_note(
+        &self,
+        request: tonic::Request<CreateUserNoteRequest>,
+    ) -> Result<tonic::Response<CreateUserNoteResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if req.content.trim().is_empty() {
+                return Err(tonic::Status::invalid_argument("Notes cannot be empty."));
+            }
+            self.check_note_pins(req.document_id, req.page_id, req.track_id)
+                .await?;
+
+            let page_id = Some(req.page_id).filter(|&id| id != 0);
+            let track_id = Some(req.track_id).filter(|&id| id != 0);
+
+            let n = (sqlx::query!(
+                "INSERT INTO user_notes (
+                    owning_user,
+                    document,
+                    content,
+                    document_page,
+                    position_top,
+                    position_left,
+                    track,
+                    track_time,
+                    created_at,
+                    updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *;",
+                user.id,
+                req.document_id,
+                req.content,
+                page_id,
+                page_id.map(|_| req.position_top),
+                page_id.map(|_| req.position_left),
+                track_id,
+                track_id.map(|_| req.track_time),
+                chrono::Utc::now(),
+                chrono::Utc::now()
+            )
+            .fetch_one(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(CreateUserNoteResponse {
+                user_note: Some(UserNote {
+                    id: n.id,
+                    document_id: n.document,
+                    content: n.content,
+                    page_id: n.document_page.unwrap_or(0),
+                    position_top: n.position_top.unwrap_or(0.0),
+                    position_left: n.position_left.unwrap_or(0.0),
+                    track_id: n.track.unwrap_or(0),
+                    track_time: n.track_time.unwrap_or(0.0),
+                    created_at: n.created_at.to_rfc3339(),
+                    updated_at: n.updated_at.to_rfc3339(),
+                }),
+            }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to create a note.",
+            ))
+        }
+    }
+
+    async fn update_user_note(
+        &self,
+        request: tonic::Request<UpdateUserNoteRequest>,
+    ) -> Result<tonic::Response<UpdateUserNoteResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            if req.content.trim().is_empty() {
+                return Err(tonic::Status::invalid_argument("Notes cannot be empty."));
+            }
+
+            let document_id = (sqlx::query!(
+                "SELECT document FROM user_notes WHERE id=$1 AND owning_user=$2;",
+                req.id,
+                user.id
+            )
+            .fetch_optional(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?
+                .ok_or_else(|| tonic::Status::not_found("Note not found."))?
+                .document;
+            self.check_note_pins(document_id, req.page_id, req.track_id)
+                .await?;
+
+            let page_id = Some(req.page_id).filter(|&id| id != 0);
+            let track_id = Some(req.track_id).filter(|&id| id != 0);
+
+            let n = (sqlx::query!(
+                "UPDATE user_notes SET
+                    content=$1,
+                    document_page=$2,
+                    position_top=$3,
+                    position_left=$4,
+                    track=$5,
+                    track_time=$6,
+                    updated_at=$7
+                WHERE id=$8 AND owning_user=$9 RETURNING *;",
+                req.content,
+                page_id,
+                page_id.map(|_| req.position_top),
+                page_id.map(|_| req.position_left),
+                track_id,
+                track_id.map(|_| req.track_time),
+                chrono::Utc::now(),
+                req.id,
+                user.id
+            )
+            .fetch_one(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(UpdateUserNoteResponse {
+                user_note: Some(UserNote {
+                    id: n.id,
+                    document_id: n.document,
+                    content: n.content,
+                    page_id: n.document_page.unwrap_or(0),
+                    position_top: n.position_top.unwrap_or(0.0),
+                    position_left: n.position_left.unwrap_or(0.0),
+                    track_id: n.track.unwrap_or(0),
+                    track_time: n.track_time.unwrap_or(0.0),
+                    created_at: n.created_at.to_rfc3339(),
+                    updated_at: n.updated_at.to_rfc3339(),
+                }),
+            }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to update a note.",
+            ))
+        }
+    }
+
+    async fn delete_user_note(
+        &self,
+        request: tonic::Request<DeleteUserNoteRequest>,
+    ) -> Result<tonic::Response<DeleteUserNoteResponse>, tonic::Status> {
+        let active_user = auth::active_user(&request);
+        let req = request.into_inner();
+
+        if let Some(user) = active_user {
+            let deleted = (sqlx::query!(
+                "DELETE FROM user_notes WHERE id=$1 AND owning_user=$2;",
+                req.id,
+                user.id
+            )
+            .execute(&self.executor)
+            .await)
+                .map_err(CoursesServiceError::from)?;
+
+            Ok(Response::new(DeleteUserNoteResponse {
+                success: deleted.rows_affected() > 0,
+            }))
+        } else {
+            Err(tonic::Status::permission_denied(
+                "You must be logged in to delete a note.",
+            ))
+        }
+    }
+
+    async fn get_text_regions_by_page_ids(
+        &self,
+        request: tonic::Request<GetTextRegionsByPageIDsRequest>,
+    ) -> Result<tonic::Response<GetTextRegionsByPageIDsResponse>, tonic::Status> {
+        let req = request.into_inner();
+        let regions = sqlx::query!(
+            "SELECT * FROM page_text_regions WHERE document_page IN (SELECT * FROM UNNEST($1::int[]))
+            ORDER BY ordinal;",
+            &req.ids
+        )
+        .fetch_all(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?;
+
+        Ok(Response::new(GetTextRegionsByPageIDsResponse {
+            text_regions: regions.into_iter().fold(HashMap::new(), |mut acc, cur| {
+                acc.entry(cur.document_page)
+                    .or_insert(PageTextRegions {
+                        text_regions: vec![],
+                    })
+                    .text_regions
+                    .push(TextRegion {
+                        id: cur.id,
+                        page_id: cur.document_page,
+                        content: cur.content,
+                        position_top: cur.position_top,
+                        position_left: cur.position_left,
+                        width: cur.width,
+                        height: cur.height,
+                    });
+                acc
+            }),
+        }))
+    }
+
+    async fn search_document_text(
+        &self,
+        request: Request<SearchDocumentTextRequest>,
+    ) -> Result<Response<SearchDocumentTextResponse>, Status> {
+        let req = request.into_inner();
+        let query = req.query.trim();
+        if query.is_empty() {
+            return Ok(Response::new(SearchDocumentTextResponse {
+                matches: vec![],
+            }));
+        }
+
+        let matches = sqlx::query!(
+            r#"SELECT
+                page_text_regions.id,
+                page_text_regions.content,
+                page_text_regions.position_top,
+                page_text_regions.position_left,
+                pages.id AS page_id,
+                pages.page_number,
+                nearest.id AS "anchor_id?",
+                nearest.track AS "track_id?",
+                nearest.track_time AS "track_time?"
+            FROM page_text_regions
+            JOIN pages ON pages.id = page_text_regions.document_page
+            LEFT JOIN LATERAL (
+                SELECT anchors.id, anchors.track, anchors.track_time FROM anchors
+                WHERE anchors.document_page = pages.id
+                ORDER BY
+                    (anchors.position_top - page_text_regions.position_top) ^ 2
+                    + (anchors.position_left - page_text_regions.position_left) ^ 2,
+                    anchors.id
+                LIMIT 1
+            ) nearest ON TRUE
+            WHERE pages.document = $1
+            AND (page_text_regions.content ILIKE $4 ESCAPE '\'
+                OR to_tsvector('simple', page_text_regions.content)
+                    @@ plainto_tsquery('simple', $2))
+            ORDER BY
+                page_text_regions.content ILIKE $4 ESCAPE '\' DESC,
+                pages.page_number,
+                page_text_regions.ordinal
+            LIMIT $3;"#,
+            req.document_id,
+            query,
+            (req.limit as i64).max(0).min(MAX_TEXT_MATCHES),
+            contains_pattern(query)
+        )
+        .fetch_all(&self.executor)
+        .await
+        .map_err(CoursesServiceError::from)?;
+
+        Ok(Response::new(SearchDocumentTextResponse {
+            matches: matches
+                .into_iter()
+                .map(|m| TextMatch {
+                    page_id: m.page_id,
+                    page_number: m.page_number,
+                    text_region_id: m.id,
+                    content: m.content,
+                    position_top: m.position_top,
+                    position_left: m.position_left,
+                    anchor_id: m.anchor_id.unwrap_or(0),
+                    track_id: m.track_id.unwrap_or(0),
+                    track_time: m.track_time.unwrap_or(0.0),
+                })
+                .collect(),
+        }))
+    }
+}
+
+/// Most matches that a search of a document's text returns, since each one also looks up the
+/// nearest anchor
+const MAX_TEXT_MATCHES: i64 = 100;
+
+/// `ILIKE` pattern matching text that contains the query, with the query's own wildcards and
+/// backslashes taken literally
+fn contains_pattern(query: &str) -> String {
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Error for media whose key doesn't match the privacy of its document, which would leave private
+/// media served without a signature or public media unreachable
+fn media_key_error(private: bool) -> tonic::Status {
+    tonic::Status::invalid_argument(if private {
+        format!(
+            "Media of a private document must be stored under {}.",
+            schema::PRIVATE_MEDIA_PREFIX
+        )
+    } else {
+        format!(
+            "Media of a public document must not be stored under {}.",
+            schema::PRIVATE_MEDIA_PREFIX
+        )
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let addr = "[::0]:50051".parse()?;
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&env::var("DATABASE_URL")?)
+        .await?;
+
+    let mut listener = PgListener::connect(&env::var("DATABASE_URL")?).await?;
+    listener.listen(anchor_changes::CHANNEL).await?;
+    let (changes, _) = broadcast::channel(anchor_changes::CAPACITY);
+    tokio::spawn(anchor_changes::relay(
+        listener,
+        pool.clone(),
+        changes.clone(),
+    ));
 
-    let service = CoursesService::new(pool);
+    let service = CoursesService::new(pool, changes);
+    let verifier = auth::JwtVerifier::from_env()?;
 
     Server::builder()
-        .add_service(CoursesServer::new(service))
+        .add_service(CoursesServer::with_interceptor(
+            service,
+            auth::interceptor(verifier),
+        ))
         .serve(addr)
         .await?;
 
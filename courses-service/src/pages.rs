@@ -0,0 +1,23 @@
+/// Aspect ratio of a page image, expressed as its height over its width
+pub fn aspect_ratio(width: i32, height: i32) -> f32 {
+    height as f32 / width as f32
+}
+
+/// Vertical offset of each page in units of page width, given the offset of the first page.
+/// The frontend compares these against the scroll position to determine the active page.
+pub fn heights(start: f32, aspect_ratios: &[f32]) -> Vec<f32> {
+    aspect_ratios
+        .iter()
+        .scan(start, |offset, aspect_ratio| {
+            let height = *offset;
+            *offset += aspect_ratio;
+            Some(height)
+        })
+        .collect()
+}
+
+/// Whether a media key is stored where its document's privacy requires, under the private prefix
+/// exactly when the document is private
+pub fn media_key_allowed(private: bool, key: &str) -> bool {
+    key.starts_with(schema::PRIVATE_MEDIA_PREFIX) == private
+}
@@ -0,0 +1,94 @@
+use std::{env, fs, path::PathBuf};
+
+use sqlx::postgres::PgPoolOptions;
+use structopt::StructOpt;
+
+mod text_layer;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "synchrotron-text")]
+/// Imports the text layer of a page from hOCR, ALTO or a plain-text transcript, replacing any
+/// text regions the page already has
+struct Opt {
+    /// ID of the page that the text belongs to
+    #[structopt(long = "page")]
+    page: i32,
+    /// hOCR or ALTO file of the page, or a plain-text transcript with one line per line of text
+    #[structopt(long = "file", parse(from_os_str))]
+    file: PathBuf,
+    /// Print the text regions without writing anything to the database
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+fn print_regions(regions: &[text_layer::Region]) {
+    println!("Text regions:");
+    for r in regions.iter() {
+        println!(
+            "  top {:>5.1}  left {:>5.1}  {:>5.1} x {:>5.1}  \"{}\"",
+            r.position_top, r.position_left, r.width, r.height, r.content
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let opt = Opt::from_args();
+
+    let regions = text_layer::parse(&fs::read_to_string(&opt.file)?)?;
+    print_regions(&regions);
+
+    if opt.dry_run {
+        return Ok(());
+    }
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&env::var("DATABASE_URL")?)
+        .await?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("SELECT id FROM pages WHERE id = $1;", opt.page)
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or_else(|| format!("Page {} doesn't exist", opt.page))?;
+
+    sqlx::query!(
+        "DELETE FROM page_text_regions WHERE document_page = $1;",
+        opt.page
+    )
+    .execute(&mut tx)
+    .await?;
+
+    for (ordinal, r) in regions.iter().enumerate() {
+        sqlx::query!(
+            "INSERT INTO page_text_regions (
+                document_page,
+                ordinal,
+                content,
+                position_top,
+                position_left,
+                width,
+                height
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7);",
+            opt.page,
+            ordinal as i32,
+            r.content,
+            r.position_top,
+            r.position_left,
+            r.width,
+            r.height
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    log::info!("Saved {} text regions for page {}", regions.len(), opt.page);
+
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use roxmltree::{Document, Node};
+
+/// Line of text on a page, with its bounding box in percent of the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub content: String,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Reads the text layer of a page from hOCR, ALTO or a plain-text transcript. XML input is told
+/// apart by its root element; anything else is treated as a transcript.
+pub fn parse(input: &str) -> Result<Vec<Region>, String> {
+    if !input.trim_start().starts_with('<') {
+        return Ok(parse_transcript(input));
+    }
+
+    let document = Document::parse(input).map_err(|err| err.to_string())?;
+    match document.root_element().tag_name().name() {
+        "html" => parse_hocr(&document),
+        "alto" => parse_alto(&document),
+        other => Err(format!(
+            "Unrecognised root element <{}>, expected hOCR or ALTO",
+            other
+        )),
+    }
+}
+
+/// Stacks the non-empty lines of a transcript evenly down the full width of the page. The boxes
+/// are only an approximation, but keep the text selectable and in reading order.
+fn parse_transcript(input: &str) -> Vec<Region> {
+    let lines = input
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>();
+    let height = 100.0 / lines.len().max(1) as f32;
+
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, content)| Region {
+            content,
+            position_top: i as f32 * height,
+            position_left: 0.0,
+            width: 100.0,
+            height,
+        })
+        .collect()
+}
+
+/// Reads `ocr_line`-like elements, measured against the `bbox` of the `ocr_page`
+fn parse_hocr(document: &Document) -> Result<Vec<Region>, String> {
+    let mut regions = vec![];
+
+    let pages = document
+        .descendants()
+        .filter(|n| has_class(n, "ocr_page"))
+        .collect::<Vec<Node>>();
+    check_single_page("hOCR", pages.len())?;
+    for page in pages {
+        let (x0, y0, x1, y1) =
+            hocr_bbox(&page).ok_or_else(|| "hOCR page is missing its bbox".to_owned())?;
+        let (page_width, page_height) = (x1 - x0, y1 - y0);
+
+        for line in page.descendants().filter(|n| {
+            has_class(n, "ocr_line")
+                || has_class(n, "ocr_header")
+                || has_class(n, "ocr_caption")
+                || has_class(n, "ocr_textfloat")
+        }) {
+            let content = text_content(&line);
+            if content.is_empty() {
+                continue;
+            }
+            if let Some((lx0, ly0, lx1, ly1)) = hocr_bbox(&line) {
+                regions.push(Region {
+                    content,
+                    position_top: 100.0 * (ly0 - y0) / page_height,
+                    position_left: 100.0 * (lx0 - x0) / page_width,
+                    width: 100.0 * (lx1 - lx0) / page_width,
+                    height: 100.0 * (ly1 - ly0) / page_height,
+                });
+            }
+        }
+    }
+
+    Ok(regions)
+}
+
+/// Reads `TextLine` elements, measured against the size of the `Page`. Units cancel out, so both
+/// pixel and mm10 measurements work.
+fn parse_alto(document: &Document) -> Result<Vec<Region>, String> {
+    let mut regions = vec![];
+
+    let pages = document
+        .descendants()
+        .filter(|n| n.has_tag_name("Page"))
+        .collect::<Vec<Node>>();
+    check_single_page("ALTO", pages.len())?;
+    for page in pages {
+        let print_space = page.descendants().find(|n| n.has_tag_name("PrintSpace"));
+        let (page_width, page_height) =
+            match (alto_number(&page, "WIDTH"), alto_number(&page, "HEIGHT")) {
+                (Some(width), Some(height)) => (width, height),
+                _ => print_space
+                    .and_then(|space| {
+                        Some((
+                            alto_number(&space, "WIDTH")?,
+                            alto_number(&space, "HEIGHT")?,
+                        ))
+                    })
+                    .ok_or_else(|| "ALTO page is missing its WIDTH and HEIGHT".to_owned())?,
+            };
+        if page_width <= 0.0 || page_height <= 0.0 {
+            return Err("ALTO page has an empty WIDTH or HEIGHT".to_owned());
+        }
+
+        for line in page.descendants().filter(|n| n.has_tag_name("TextLine")) {
+            let content = line
+                .children()
+                .filter(|n| n.has_tag_name("String"))
+                .filter_map(|n| n.attribute("CONTENT"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if content.is_empty() {
+                continue;
+            }
+            if let (Some(left), Some(top), Some(width), Some(height)) = (
+                alto_number(&line, "HPOS"),
+                alto_number(&line, "VPOS"),
+                alto_number(&line, "WIDTH"),
+                alto_number(&line, "HEIGHT"),
+            ) {
+                regions.push(Region {
+                    content,
+                    position_top: 100.0 * top / page_height,
+                    position_left: 100.0 * left / page_width,
+                    width: 100.0 * width / page_width,
+                    height: 100.0 * height / page_height,
+                });
+            }
+        }
+    }
+
+    Ok(regions)
+}
+
+/// The text of one page is imported at a time, and the coordinates of several pages would be
+/// laid over each other. A file without a page is rejected too, rather than clearing the text
+/// the page already has.
+fn check_single_page(format: &str, pages: usize) -> Result<(), String> {
+    match pages {
+        1 => Ok(()),
+        0 => Err(format!("{} file has no page", format)),
+        _ => Err(format!(
+            "{} file has {} pages, but only the text of a single page can be imported",
+            format, pages
+        )),
+    }
+}
+
+fn has_class(node: &Node, class: &str) -> bool {
+    node.attribute("class")
+        .map(|classes| classes.split_whitespace().any(|c| c == class))
+        .unwrap_or(false)
+}
+
+/// Reads the `bbox x0 y0 x1 y1` property out of an hOCR `title` attribute
+fn hocr_bbox(node: &Node) -> Option<(f32, f32, f32, f32)> {
+    let title = node.attribute("title")?;
+    let bbox = title
+        .split(';')
+        .map(|property| property.trim())
+        .find(|property| property.starts_with("bbox "))?;
+    let numbers = bbox["bbox ".len()..]
+        .split_whitespace()
+        .map(|n| n.parse::<f32>().ok())
+        .collect::<Option<Vec<f32>>>()?;
+    match numbers.as_slice() {
+        [x0, y0, x1, y1] if x1 > x0 && y1 > y0 => Some((*x0, *y0, *x1, *y1)),
+        _ => None,
+    }
+}
+
+fn alto_number(node: &Node, attribute: &str) -> Option<f32> {
+    node.attribute(attribute)?
+        .parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+}
+
+fn text_content(node: &Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(content: &str, top: f32, left: f32, width: f32, height: f32) -> Region {
+        Region {
+            content: content.to_owned(),
+            position_top: top,
+            position_left: left,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn spreads_transcript_lines_down_the_page() {
+        let regions = parse("First  line\n\n   \nSecond line\n").unwrap();
+
+        assert_eq!(
+            regions,
+            vec![
+                region("First line", 0.0, 0.0, 100.0, 50.0),
+                region("Second line", 50.0, 0.0, 100.0, 50.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_hocr_bboxes_to_percent_of_the_page() {
+        let hocr = r#"<html><body>
+            <div class="ocr_page" title="image page.png; bbox 50 50 1050 2050">
+                <span class="ocr_line" title="bbox 150 250 650 350; baseline 0 -5">
+                    <span class="ocrx_word" title="bbox 150 250 400 350">Hello</span>
+                    <span class="ocrx_word" title="bbox 420 250 650 350">world</span>
+                </span>
+                <span class="ocr_line" title="bbox 150 400 650 500"> </span>
+                <span class="ocr_header" title="bbox 550 1050 1050 1250">Heading</span>
+            </div>
+        </body></html>"#;
+
+        assert_eq!(
+            parse(hocr).unwrap(),
+            vec![
+                region("Hello world", 10.0, 10.0, 50.0, 5.0),
+                region("Heading", 50.0, 50.0, 50.0, 10.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_hocr_pages_without_a_bbox() {
+        let hocr =
+            r#"<html><body><div class="ocr_page" title="image page.png"></div></body></html>"#;
+
+        assert!(parse(hocr).is_err());
+    }
+
+    #[test]
+    fn rejects_hocr_with_several_pages() {
+        let hocr = r#"<html><body>
+            <div class="ocr_page" title="bbox 0 0 1000 2000"></div>
+            <div class="ocr_page" title="bbox 0 0 1000 2000"></div>
+        </body></html>"#;
+
+        assert!(parse(hocr).is_err());
+    }
+
+    #[test]
+    fn rejects_hocr_without_a_page() {
+        assert!(parse("<html><body><p>Hello world</p></body></html>").is_err());
+    }
+
+    #[test]
+    fn converts_alto_positions_to_percent_of_the_page() {
+        let alto = r#"<alto><Layout><Page WIDTH="1000" HEIGHT="2000"><PrintSpace><TextBlock>
+            <TextLine HPOS="100" VPOS="200" WIDTH="500" HEIGHT="100">
+                <String CONTENT="Hello"/><SP/><String CONTENT="world"/>
+            </TextLine>
+            <TextLine HPOS="100" VPOS="400" WIDTH="500" HEIGHT="100"/>
+        </TextBlock></PrintSpace></Page></Layout></alto>"#;
+
+        assert_eq!(
+            parse(alto).unwrap(),
+            vec![region("Hello world", 10.0, 10.0, 50.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn measures_alto_against_the_print_space_without_a_page_size() {
+        let alto = r#"<alto><Layout><Page><PrintSpace WIDTH="500" HEIGHT="1000"><TextBlock>
+            <TextLine HPOS="50" VPOS="100" WIDTH="250" HEIGHT="50">
+                <String CONTENT="Hello"/>
+            </TextLine>
+        </TextBlock></PrintSpace></Page></Layout></alto>"#;
+
+        assert_eq!(
+            parse(alto).unwrap(),
+            vec![region("Hello", 10.0, 10.0, 50.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn rejects_alto_without_a_page() {
+        assert!(parse("<alto><Layout></Layout></alto>").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_xml() {
+        assert!(parse("<svg></svg>").is_err());
+    }
+}
@@ -5,7 +5,9 @@ use juniper::IntrospectionFormat;
 use gateway::graphql::schema::{create_schema, Context};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let ctx = Context::new(None, None, None, None, None, None, None, None);
+    let ctx = Context::new(
+        None, None, None, None, None, None, None, None, None, None, None, None, None,
+    );
 
     let (res, _errors) =
         juniper::introspect(&create_schema(), &ctx, IntrospectionFormat::default()).unwrap();
@@ -17,6 +17,10 @@ pub fn get_command(msg: &application::Msg, state: &Model) -> Command<Msg> {
             operations::all_documents(payload.clone(), state.authentication.token.clone()),
             |x| Msg::Application(application::Msg::AllDocumentsResponse(x)),
         ),
+        application::Msg::SearchDocumentsRequest(payload) => Command::perform(
+            operations::search_documents(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::SearchDocumentsResponse(x)),
+        ),
         application::Msg::DocumentRequest(payload) => Command::perform(
             operations::document(payload.clone(), state.authentication.token.clone()),
             |x| Msg::Application(application::Msg::DocumentResponse(x)),
@@ -39,6 +43,14 @@ pub fn get_command(msg: &application::Msg, state: &Model) -> Command<Msg> {
             })),
             Msg::Application,
         ),
+        application::Msg::UpdateAnchorRequest(payload) => Command::perform(
+            operations::update_anchor(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::UpdateAnchorResponse(x)),
+        ),
+        application::Msg::UpdateUserAnchorRequest(payload) => Command::perform(
+            operations::update_user_anchor(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::UpdateUserAnchorResponse(x)),
+        ),
         application::Msg::DeleteAnchorRequest(payload) => Command::perform(
             operations::delete_anchor(payload.clone(), state.authentication.token.clone()),
             |x| Msg::Application(application::Msg::DeleteAnchorResponse(x)),
@@ -47,6 +59,45 @@ pub fn get_command(msg: &application::Msg, state: &Model) -> Command<Msg> {
             operations::delete_user_anchor(payload.clone(), state.authentication.token.clone()),
             |x| Msg::Application(application::Msg::DeleteUserAnchorResponse(x)),
         ),
+        application::Msg::PromoteUserAnchorRequest(payload) => Command::perform(
+            operations::promote_user_anchor(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::PromoteUserAnchorResponse(x)),
+        ),
+        application::Msg::SaveProgressRequest(payload) => Command::perform(
+            operations::save_progress(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::SaveProgressResponse(x)),
+        ),
+        application::Msg::CreateUserNoteRequest(payload) => Command::perform(
+            operations::create_user_note(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::CreateUserNoteResponse(x)),
+        ),
+        application::Msg::DeleteUserNoteRequest(payload) => Command::perform(
+            operations::delete_user_note(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::DeleteUserNoteResponse(x)),
+        ),
+        application::Msg::SearchDocumentTextRequest(payload) => Command::perform(
+            operations::search_document_text(payload.clone(), state.authentication.token.clone()),
+            |x| Msg::Application(application::Msg::SearchDocumentTextResponse(x)),
+        ),
+        application::Msg::AnchorChangesMissed(document_id) => Command::batch(
+            state
+                .entities
+                .document_pages
+                .get(document_id)
+                .into_iter()
+                .flatten()
+                // Only the pages whose anchors have been loaded
+                .filter(|page_id| state.entities.page_anchors.contains_key(*page_id))
+                .map(|&page_id| {
+                    Command::perform(
+                        ready(application::Msg::PageRequest(PageRequestPayload {
+                            page_id,
+                        })),
+                        Msg::Application,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ),
         application::Msg::DocumentResponse(Ok(_)) => {
             if let Route::Course(_, Some(anchor_id)) = state.routing.route {
                 Command::perform(
@@ -62,6 +113,12 @@ pub fn get_command(msg: &application::Msg, state: &Model) -> Command<Msg> {
         }
         application::Msg::JumpToAnchorResponse(Ok(payload)) => {
             let anchor_id = payload.anchor.id;
+            let track_time = payload.anchor.track_time as f64;
+            let audio_url = state
+                .entities
+                .tracks_by_id
+                .get(&payload.anchor.track_id)
+                .map(|t| t.audio_url.clone());
             Command::perform(
                 operations::page(PageRequestPayload {
                     page_id: payload.anchor.page_id
@@ -76,6 +133,17 @@ pub fn get_command(msg: &application::Msg, state: &Model) -> Command<Msg> {
                             x.dyn_into::<web_sys::HtmlElement>().ok()
                         })
                         .and_then(|el| Some(el.scroll_into_view()));
+
+                    // Cue the anchor's track so that playing picks up from the linked spot
+                    if let Some(audio_url) = &audio_url {
+                        if let Some(el) = document
+                            .get_element_by_id("audio")
+                            .and_then(|x| x.dyn_into::<web_sys::HtmlAudioElement>().ok())
+                        {
+                            el.set_src(audio_url.as_str());
+                            el.set_current_time(track_time);
+                        }
+                    }
                     
                     Msg::Application(application::Msg::PageResponse(x))
                 }
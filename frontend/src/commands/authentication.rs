@@ -3,11 +3,47 @@ use iced::Command;
 
 use super::operations;
 use crate::{
-    messages::{authentication, routing, Msg},
-    state::Route,
+    messages::{
+        authentication::{self, LoginSuccessPayload, RefreshTokenRequestPayload},
+        routing, Msg,
+    },
+    state::{Model, Route},
 };
 
-pub fn get_command(msg: &authentication::Msg) -> Command<Msg> {
+/// Seconds before expiry at which the access token is refreshed
+const REFRESH_MARGIN_SECONDS: i32 = 60;
+
+/// Resolves once the given number of milliseconds has passed
+async fn delay(millis: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let _ = web_sys::window().map(|window| {
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis)
+        });
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+fn schedule_refresh(payload: &LoginSuccessPayload) -> Command<Msg> {
+    let refresh_token = payload.refresh_token.clone();
+    let seconds = (payload.expires_in - REFRESH_MARGIN_SECONDS).max(payload.expires_in / 2);
+    Command::perform(delay(seconds * 1000), move |_| {
+        Msg::Authentication(authentication::Msg::RefreshTokenRequest(
+            RefreshTokenRequestPayload { refresh_token },
+        ))
+    })
+}
+
+/// Drops the rejected session and sends the user back to the login screen
+pub fn session_expired() -> Command<Msg> {
+    Command::batch(vec![
+        Command::perform(ready(()), |_| {
+            Msg::Authentication(authentication::Msg::Logout)
+        }),
+        Command::perform(ready(Route::Login), |r| Msg::Routing(routing::Msg::Push(r))),
+    ])
+}
+
+pub fn get_command(msg: &authentication::Msg, state: &Model) -> Command<Msg> {
     match msg {
         authentication::Msg::LoginRequest(payload) => {
             Command::perform(operations::login(payload.clone(), None), |x| {
@@ -19,9 +55,28 @@ pub fn get_command(msg: &authentication::Msg) -> Command<Msg> {
                 Msg::Authentication(authentication::Msg::RegisterResponse(x))
             })
         }
-        authentication::Msg::LoginResponse(Ok(_)) => Command::perform(ready(Route::Courses), |r| {
-            Msg::Routing(routing::Msg::Push(r))
-        }),
+        authentication::Msg::RefreshTokenRequest(payload) => {
+            // Refreshes scheduled before a logout or a newer login are stale
+            if state.authentication.refresh_token.as_ref() != Some(&payload.refresh_token) {
+                return Command::none();
+            }
+            Command::perform(operations::refresh_token(payload.clone(), None), |x| {
+                Msg::Authentication(authentication::Msg::RefreshTokenResponse(x))
+            })
+        }
+        authentication::Msg::LogoutRequest(payload) => {
+            Command::perform(operations::logout(payload.clone(), None), |_| {
+                Msg::Authentication(authentication::Msg::Logout)
+            })
+        }
+        authentication::Msg::LoginResponse(Ok(payload)) => Command::batch(vec![
+            Command::perform(ready(Route::Courses), |r| {
+                Msg::Routing(routing::Msg::Push(r))
+            }),
+            schedule_refresh(payload),
+        ]),
+        authentication::Msg::RefreshTokenResponse(Ok(payload)) => schedule_refresh(payload),
+        authentication::Msg::RefreshTokenResponse(Err(_)) => session_expired(),
         authentication::Msg::RegisterResponse(Ok(_)) => {
             Command::perform(ready(Route::Login), |r| Msg::Routing(routing::Msg::Push(r)))
         }
@@ -2,17 +2,21 @@ use iced::Command;
 
 mod application;
 mod authentication;
-mod operations;
+pub mod operations;
 mod routing;
 mod ui;
 
 use crate::{messages::Msg, state::Model};
 
 pub fn get_command(message: &Msg, state: &Model) -> Command<Msg> {
+    if message.error().map_or(false, |e| e.is_unauthenticated()) {
+        return authentication::session_expired();
+    }
+
     match message {
         Msg::Application(x) => application::get_command(x, state),
-        Msg::Authentication(x) => authentication::get_command(x),
-        Msg::Routing(x) => routing::get_command(x),
+        Msg::Authentication(x) => authentication::get_command(x, state),
+        Msg::Routing(x) => routing::get_command(x, state),
         Msg::Ui(x) => ui::get_command(x, state),
         _ => Command::none(),
     }
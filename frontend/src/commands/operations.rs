@@ -1,21 +1,28 @@
+use std::convert::TryFrom;
 use std::i32;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use chrono::{DateTime, FixedOffset};
+use futures::{channel::mpsc, Stream};
 use graphql_client::{GraphQLQuery, QueryBody, Response as GraphQLResponse};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{Request, RequestInit, Response};
+use web_sys::{MessageEvent, Request, RequestInit, Response, WebSocket};
 
-use crate::{messages::{ErrorPayload, application::{AllDocumentsRequestPayload, AllDocumentsSuccessPayload, CreateAnchorRequestPayload, CreateAnchorSuccessPayload, CreateUserAnchorSuccessPayload, DeleteAnchorRequestPayload, DeleteAnchorSuccessPayload, DocumentRequestPayload, DocumentSuccessPayload, JumpToAnchorRequestPayload, JumpToAnchorSuccessPayload, PageRequestPayload, PageSuccessPayload}, authentication::{
-            LoginRequestPayload, LoginSuccessPayload, RegisterRequestPayload,
-            RegisterSuccessPayload,
+use crate::{messages::{ErrorPayload, application::{AllDocumentsRequestPayload, AnchorChangeKind, AnchorChangedPayload, AllDocumentsSuccessPayload, CreateAnchorRequestPayload, CreateAnchorSuccessPayload, CreateUserAnchorSuccessPayload, CreateUserNoteRequestPayload, CreateUserNoteSuccessPayload, DeleteAnchorRequestPayload, DeleteAnchorSuccessPayload, DeleteUserNoteRequestPayload, DeleteUserNoteSuccessPayload, DocumentRequestPayload, DocumentSuccessPayload, JumpToAnchorRequestPayload, JumpToAnchorSuccessPayload, PageRequestPayload, PageSuccessPayload, PromoteUserAnchorRequestPayload, SaveProgressRequestPayload, SaveProgressSuccessPayload, SearchDocumentTextRequestPayload, SearchDocumentTextSuccessPayload, SearchDocumentsRequestPayload, SearchDocumentsSuccessPayload, UpdateAnchorRequestPayload, UpdateAnchorSuccessPayload, UpdateUserAnchorSuccessPayload}, authentication::{
+            LoginRequestPayload, LoginSuccessPayload, LogoutRequestPayload, LogoutSuccessPayload,
+            RefreshTokenRequestPayload, RegisterRequestPayload, RegisterSuccessPayload,
         }}, state::entities::{
-        Anchor, Bookmark, Document as SchemaDocument, Page as SchemaPage, Track, User, UserAnchor,
-    }};
+        Anchor, Bookmark, Document as SchemaDocument, Page as SchemaPage, Progress, TextMatch,
+        TextRegion, Track, User, UserAnchor, UserNote,
+    }, state::ui::courses_screen::DocumentSort};
 
 const API_URL: &str = "https://synchrotron.nsenger.com/graphql";
+const SUBSCRIPTIONS_URL: &str = "wss://synchrotron.nsenger.com/subscriptions";
 
 type DateTimeFixedOffset = DateTime<FixedOffset>;
 
@@ -33,6 +40,20 @@ pub struct Register;
 )]
 pub struct Login;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct RefreshToken;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct Logout;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/gen/schema.json",
@@ -40,6 +61,13 @@ pub struct Login;
 )]
 pub struct AllDocuments;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct SearchDocuments;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/gen/schema.json",
@@ -68,6 +96,20 @@ pub struct CreateAnchor;
 )]
 pub struct DeleteAnchor;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct UpdateAnchor;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct UpdateUserAnchor;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/gen/schema.json",
@@ -89,25 +131,84 @@ pub struct DeleteUserAnchor;
 )]
 pub struct JumpToAnchor;
 
-async fn graphQLRequest<T, U, V, W>(
-    input: T,
-    build_query: fn(U) -> QueryBody<U>,
-    token: Option<String>,
-) -> Result<W, ErrorPayload>
-where
-    T: Into<U> + Clone,
-    U: Serialize,
-    V: Into<W> + DeserializeOwned,
-{
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct PromoteUserAnchor;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct PageAnchorsChanged;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct SaveProgress;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct CreateUserNote;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct DeleteUserNote;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gen/schema.json",
+    query_path = "src/commands/operations.graphql"
+)]
+pub struct SearchDocumentText;
+
+/// Error for a query whose entity has been deleted or never existed
+fn not_found(entity: &str) -> ErrorPayload {
+    ErrorPayload {
+        content: format!("{} doesn't exist", entity),
+        code: Some("NOT_FOUND".to_owned()),
+    }
+}
+
+/// Hash of a query that the gateway has persisted, sent in place of the query itself
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQuery {
+    version: i32,
+    sha256_hash: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQueryExtensions {
+    persisted_query: PersistedQuery,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQueryBody<'a, U> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<&'static str>,
+    operation_name: &'static str,
+    variables: &'a U,
+    extensions: PersistedQueryExtensions,
+}
+
+async fn fetch_json(body: String, token: Option<&str>) -> Result<JsValue, ErrorPayload> {
     let mut opts = RequestInit::new();
     opts.method("POST");
-    opts.body(
-        JsValue::from_serde(
-            Some(&serde_json::to_string(&build_query(input.into())).unwrap()).unwrap(),
-        )
-        .ok()
-        .as_ref(),
-    );
+    opts.body(JsValue::from_serde(&body).ok().as_ref());
     let request = Request::new_with_str(API_URL).unwrap();
     // Cannot set content-type header when using mode no-cors
     request
@@ -116,33 +217,201 @@ where
         .unwrap();
     request.headers().set("Accept", "application/json").unwrap();
     if let Some(t) = token {
-        request.headers().set("Authorization", t.as_str()).unwrap();
+        request.headers().set("Authorization", t).unwrap();
     }
     let window = web_sys::window().unwrap();
     let resp_value = JsFuture::from(window.fetch_with_request_and_init(&request, &opts))
         .await
         .map_err(|e| ErrorPayload {
             content: "Failed to generate JS future from request".to_owned(),
+            code: None,
         })?;
     let resp: Response = resp_value.dyn_into().unwrap();
-    let json = JsFuture::from(resp.json().unwrap())
+    JsFuture::from(resp.json().unwrap())
         .await
         .map_err(|e| ErrorPayload {
             content: "Failed to generate JS future parsing JSON".to_owned(),
-        })?;
-    let response: GraphQLResponse<V> = json.into_serde().map_err(|e| ErrorPayload {
+            code: None,
+        })
+}
+
+/// Sends the hash of the query first, and the query itself only when the gateway hasn't
+/// persisted it yet
+async fn graphQLRequest<T, U, V, W>(
+    input: T,
+    build_query: fn(U) -> QueryBody<U>,
+    token: Option<String>,
+) -> Result<W, ErrorPayload>
+where
+    T: Into<U> + Clone,
+    U: Serialize,
+    V: Into<W> + DeserializeOwned,
+{
+    let query = build_query(input.into());
+    let mut body = PersistedQueryBody {
+        query: None,
+        operation_name: query.operation_name,
+        variables: &query.variables,
+        extensions: PersistedQueryExtensions {
+            persisted_query: PersistedQuery {
+                version: 1,
+                sha256_hash: format!("{:x}", Sha256::digest(query.query.as_bytes())),
+            },
+        },
+    };
+
+    let mut response: GraphQLResponse<V> =
+        parse_response(fetch_json(serde_json::to_string(&body).unwrap(), token.as_deref()).await?)?;
+    if is_persisted_query_not_found(&response) {
+        body.query = Some(query.query);
+        response = parse_response(
+            fetch_json(serde_json::to_string(&body).unwrap(), token.as_deref()).await?,
+        )?;
+    }
+
+    response_result(response)
+}
+
+fn parse_response<V: DeserializeOwned>(json: JsValue) -> Result<GraphQLResponse<V>, ErrorPayload> {
+    json.into_serde().map_err(|e| ErrorPayload {
         content: "Failed to parse response".to_owned(),
-    })?;
+        code: None,
+    })
+}
 
+fn is_persisted_query_not_found<V>(response: &GraphQLResponse<V>) -> bool {
+    response
+        .errors
+        .iter()
+        .flatten()
+        .filter_map(|e| e.extensions.as_ref())
+        .any(|extensions| {
+            extensions.get("code").and_then(|code| code.as_str())
+                == Some("PERSISTED_QUERY_NOT_FOUND")
+        })
+}
+
+fn response_result<V, W>(response: GraphQLResponse<V>) -> Result<W, ErrorPayload>
+where
+    V: Into<W>,
+{
     if let Some(data) = response.data {
         Ok(data.into())
     } else {
+        let error = response.errors.and_then(|errors| errors.into_iter().next());
         Err(ErrorPayload {
-            content: "GraphQL error".to_owned(),
+            content: error
+                .as_ref()
+                .map(|e| e.message.clone())
+                .unwrap_or("GraphQL error".to_owned()),
+            code: error
+                .and_then(|e| e.extensions)
+                .and_then(|extensions| extensions.get("code").cloned())
+                .and_then(|code| code.as_str().map(str::to_owned)),
         })
     }
 }
 
+#[derive(Deserialize)]
+struct SubscriptionMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    payload: Option<serde_json::Value>,
+}
+
+/// Results of a graphql-ws subscription, which is stopped when this is dropped and ends when
+/// the server completes it
+pub struct GraphQLSubscription<W> {
+    socket: WebSocket,
+    results: mpsc::UnboundedReceiver<Result<W, ErrorPayload>>,
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl<W> Stream for GraphQLSubscription<W> {
+    type Item = Result<W, ErrorPayload>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.results).poll_next(cx)
+    }
+}
+
+impl<W> Drop for GraphQLSubscription<W> {
+    fn drop(&mut self) {
+        let _ = self.socket.close();
+    }
+}
+
+fn graphQLSubscription<U, V, W>(
+    variables: U,
+    build_query: fn(U) -> QueryBody<U>,
+) -> Result<GraphQLSubscription<W>, ErrorPayload>
+where
+    U: Serialize,
+    V: Into<W> + DeserializeOwned + 'static,
+    W: 'static,
+{
+    let socket =
+        WebSocket::new_with_str(SUBSCRIPTIONS_URL, "graphql-ws").map_err(|e| ErrorPayload {
+            content: "Failed to open WebSocket".to_owned(),
+            code: None,
+        })?;
+
+    let init = serde_json::json!({ "type": "connection_init", "payload": {} }).to_string();
+    let start = serde_json::json!({
+        "id": "1",
+        "type": "start",
+        "payload": build_query(variables),
+    })
+    .to_string();
+    let open_socket = socket.clone();
+    let on_open = Closure::wrap(Box::new(move || {
+        let _ = open_socket.send_with_str(init.as_str());
+        let _ = open_socket.send_with_str(start.as_str());
+    }) as Box<dyn FnMut()>);
+    socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+    let (sender, results) = mpsc::unbounded();
+    let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let message = match event
+            .data()
+            .as_string()
+            .and_then(|data| serde_json::from_str::<SubscriptionMessage>(data.as_str()).ok())
+        {
+            None => return,
+            Some(message) => message,
+        };
+
+        let result = match message.kind.as_str() {
+            "data" => message
+                .payload
+                .and_then(|payload| serde_json::from_value::<GraphQLResponse<V>>(payload).ok())
+                .map(response_result)
+                .unwrap_or(Err(ErrorPayload {
+                    content: "Failed to parse subscription data".to_owned(),
+                    code: None,
+                })),
+            "error" => Err(ErrorPayload {
+                content: "Subscription failed".to_owned(),
+                code: None,
+            }),
+            // The server has ended the subscription
+            "complete" => return sender.close_channel(),
+            // Acknowledgements and keep-alives
+            _ => return,
+        };
+        let _ = sender.unbounded_send(result);
+    }) as Box<dyn FnMut(MessageEvent)>);
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+    Ok(GraphQLSubscription {
+        socket,
+        results,
+        _on_open: on_open,
+        _on_message: on_message,
+    })
+}
+
 impl Into<i32> for login::UserRole {
     fn into(self) -> i32 {
         match self {
@@ -166,6 +435,8 @@ impl Into<LoginSuccessPayload> for login::ResponseData {
     fn into(self) -> LoginSuccessPayload {
         LoginSuccessPayload {
             token: self.login.token,
+            refresh_token: self.login.refresh_token,
+            expires_in: self.login.expires_in as i32,
             user: User {
                 id: self.login.user.id as i32,
                 username: self.login.user.username,
@@ -192,6 +463,81 @@ impl Into<i32> for register::UserRole {
     }
 }
 
+impl Into<i32> for refresh_token::UserRole {
+    fn into(self) -> i32 {
+        match self {
+            refresh_token::UserRole::MODERATOR => 1,
+            refresh_token::UserRole::ADMINISTRATOR => 2,
+            _ => 0,
+        }
+    }
+}
+
+impl Into<refresh_token::Variables> for RefreshTokenRequestPayload {
+    fn into(self) -> refresh_token::Variables {
+        refresh_token::Variables {
+            refresh_token: self.refresh_token,
+        }
+    }
+}
+
+impl Into<LoginSuccessPayload> for refresh_token::ResponseData {
+    fn into(self) -> LoginSuccessPayload {
+        LoginSuccessPayload {
+            token: self.refresh_token.token,
+            refresh_token: self.refresh_token.refresh_token,
+            expires_in: self.refresh_token.expires_in as i32,
+            user: User {
+                id: self.refresh_token.user.id as i32,
+                username: self.refresh_token.user.username,
+                role: self.refresh_token.user.role.into(),
+            },
+        }
+    }
+}
+
+pub async fn refresh_token(
+    input: RefreshTokenRequestPayload,
+    token: Option<String>,
+) -> Result<LoginSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        RefreshTokenRequestPayload,
+        refresh_token::Variables,
+        refresh_token::ResponseData,
+        LoginSuccessPayload,
+    >(input, RefreshToken::build_query, token)
+    .await
+}
+
+impl Into<logout::Variables> for LogoutRequestPayload {
+    fn into(self) -> logout::Variables {
+        logout::Variables {
+            refresh_token: self.refresh_token,
+        }
+    }
+}
+
+impl Into<LogoutSuccessPayload> for logout::ResponseData {
+    fn into(self) -> LogoutSuccessPayload {
+        LogoutSuccessPayload {
+            success: self.logout.success,
+        }
+    }
+}
+
+pub async fn logout(
+    input: LogoutRequestPayload,
+    token: Option<String>,
+) -> Result<LogoutSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        LogoutRequestPayload,
+        logout::Variables,
+        logout::ResponseData,
+        LogoutSuccessPayload,
+    >(input, Logout::build_query, token)
+    .await
+}
+
 impl Into<register::Variables> for RegisterRequestPayload {
     fn into(self) -> register::Variables {
         register::Variables {
@@ -246,6 +592,9 @@ impl Into<AllDocumentsSuccessPayload> for all_documents::ResponseData {
                     title: d.title,
                     created_at: d.created_at,
                     updated_at: d.updated_at,
+                    language: d.language,
+                    level: d.level,
+                    description: d.description,
                 })
                 .collect(),
         }
@@ -265,6 +614,66 @@ pub async fn all_documents(
     .await
 }
 
+impl Into<search_documents::DocumentSort> for DocumentSort {
+    fn into(self) -> search_documents::DocumentSort {
+        match self {
+            DocumentSort::Relevance => search_documents::DocumentSort::RELEVANCE,
+            DocumentSort::RecentlyUpdated => search_documents::DocumentSort::RECENTLY_UPDATED,
+            DocumentSort::Title => search_documents::DocumentSort::TITLE,
+        }
+    }
+}
+
+impl Into<search_documents::Variables> for SearchDocumentsRequestPayload {
+    fn into(self) -> search_documents::Variables {
+        search_documents::Variables {
+            query: self.query,
+            filters: Some(search_documents::DocumentFilters {
+                language: Some(self.language).filter(|l| !l.is_empty()),
+                level: Some(self.level).filter(|l| !l.is_empty()),
+                sort: Some(self.sort.into()),
+            }),
+            first: std::i32::MAX as i64,
+            after: None,
+        }
+    }
+}
+
+impl Into<SearchDocumentsSuccessPayload> for search_documents::ResponseData {
+    fn into(self) -> SearchDocumentsSuccessPayload {
+        SearchDocumentsSuccessPayload {
+            total: self.search_documents.total_count as i32,
+            documents: self
+                .search_documents
+                .edges
+                .into_iter()
+                .map(|e| SchemaDocument {
+                    id: e.node.id as i32,
+                    title: e.node.title,
+                    created_at: e.node.created_at,
+                    updated_at: e.node.updated_at,
+                    language: e.node.language,
+                    level: e.node.level,
+                    description: e.node.description,
+                })
+                .collect(),
+        }
+    }
+}
+
+pub async fn search_documents(
+    input: SearchDocumentsRequestPayload,
+    token: Option<String>,
+) -> Result<SearchDocumentsSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        SearchDocumentsRequestPayload,
+        search_documents::Variables,
+        search_documents::ResponseData,
+        SearchDocumentsSuccessPayload,
+    >(input, SearchDocuments::build_query, token)
+    .await
+}
+
 impl Into<document::Variables> for DocumentRequestPayload {
     fn into(self) -> document::Variables {
         document::Variables {
@@ -279,18 +688,23 @@ impl Into<document::Variables> for DocumentRequestPayload {
     }
 }
 
-impl Into<DocumentSuccessPayload> for document::ResponseData {
-    fn into(self) -> DocumentSuccessPayload {
-        let document_id = self.document_by_id.id as i32;
-        DocumentSuccessPayload {
+impl TryFrom<document::ResponseData> for DocumentSuccessPayload {
+    type Error = ErrorPayload;
+
+    fn try_from(data: document::ResponseData) -> Result<Self, Self::Error> {
+        let document = data.document_by_id.ok_or_else(|| not_found("Document"))?;
+        let document_id = document.id as i32;
+        Ok(DocumentSuccessPayload {
             document: SchemaDocument {
                 id: document_id,
-                title: self.document_by_id.title,
-                created_at: self.document_by_id.created_at,
-                updated_at: self.document_by_id.updated_at,
+                title: document.title,
+                created_at: document.created_at,
+                updated_at: document.updated_at,
+                language: document.language,
+                level: document.level,
+                description: document.description,
             },
-            bookmarks: self
-                .document_by_id
+            bookmarks: document
                 .bookmarks
                 .into_iter()
                 .map(|b| Bookmark {
@@ -300,32 +714,49 @@ impl Into<DocumentSuccessPayload> for document::ResponseData {
                     document_id,
                 })
                 .collect(),
-            pages: self
-                .document_by_id
+            pages: document
                 .pages
                 .into_iter()
                 .map(|p| SchemaPage {
                     id: p.id as i32,
                     page_number: p.page_number as i32,
-                    image_path: p.image_path,
+                    image_url: p.image_url,
                     aspect_ratio: p.aspect_ratio as f32,
                     height: p.height as f32,
                     document_id,
                 })
                 .collect(),
-            tracks: self
-                .document_by_id
+            tracks: document
                 .tracks
                 .into_iter()
                 .map(|t| Track {
                     id: t.id as i32,
                     track_number: t.track_number as i32,
                     title: t.title,
-                    audio_path: t.audio_path,
+                    audio_url: t.audio_url,
                     document_id,
                 })
                 .collect(),
-        }
+            progress: document.my_progress.map(|p| Progress {
+                document_id,
+                track_id: p.track.id as i32,
+                track_time: p.track_time as f32,
+                page_id: p.page.id as i32,
+                completion: p.completion as f32,
+            }),
+            user_notes: document
+                .my_notes
+                .into_iter()
+                .map(|n| UserNote {
+                    id: n.id as i32,
+                    content: n.content,
+                    page_id: n.page.map(|p| p.id as i32),
+                    track_id: n.track.map(|t| t.id as i32),
+                    track_time: n.track_time.unwrap_or(0.0) as f32,
+                    document_id,
+                })
+                .collect(),
+        })
     }
 }
 
@@ -337,9 +768,10 @@ pub async fn document(
         DocumentRequestPayload,
         document::Variables,
         document::ResponseData,
-        DocumentSuccessPayload,
+        document::ResponseData,
     >(input, Document::build_query, token)
     .await
+    .and_then(DocumentSuccessPayload::try_from)
 }
 
 impl Into<page::Variables> for PageRequestPayload {
@@ -350,20 +782,22 @@ impl Into<page::Variables> for PageRequestPayload {
     }
 }
 
-impl Into<PageSuccessPayload> for page::ResponseData {
-    fn into(self) -> PageSuccessPayload {
-        let page_id = self.page_by_id.id as i32;
-        PageSuccessPayload {
+impl TryFrom<page::ResponseData> for PageSuccessPayload {
+    type Error = ErrorPayload;
+
+    fn try_from(data: page::ResponseData) -> Result<Self, Self::Error> {
+        let page = data.page_by_id.ok_or_else(|| not_found("Page"))?;
+        let page_id = page.id as i32;
+        Ok(PageSuccessPayload {
             page: SchemaPage {
                 id: page_id,
-                page_number: self.page_by_id.page_number as i32,
-                image_path: self.page_by_id.image_path,
-                aspect_ratio: self.page_by_id.aspect_ratio as f32,
-                height: self.page_by_id.height as f32,
-                document_id: self.page_by_id.document.id as i32,
+                page_number: page.page_number as i32,
+                image_url: page.image_url,
+                aspect_ratio: page.aspect_ratio as f32,
+                height: page.height as f32,
+                document_id: page.document.id as i32,
             },
-            anchors: self
-                .page_by_id
+            anchors: page
                 .anchors
                 .into_iter()
                 .map(|a| Anchor {
@@ -378,8 +812,7 @@ impl Into<PageSuccessPayload> for page::ResponseData {
                     updated_at: a.updated_at,
                 })
                 .collect(),
-            user_anchors: self
-                .page_by_id
+            user_anchors: page
                 .user_anchors
                 .into_iter()
                 .map(|a| UserAnchor {
@@ -395,7 +828,20 @@ impl Into<PageSuccessPayload> for page::ResponseData {
                     owner: a.owner.id as i32,
                 })
                 .collect(),
-        }
+            text_regions: page
+                .text_regions
+                .into_iter()
+                .map(|r| TextRegion {
+                    id: r.id as i32,
+                    content: r.content,
+                    position_top: r.position_top as f32,
+                    position_left: r.position_left as f32,
+                    width: r.width as f32,
+                    height: r.height as f32,
+                    page_id,
+                })
+                .collect(),
+        })
     }
 }
 
@@ -403,12 +849,13 @@ pub async fn page(
     input: PageRequestPayload,
     token: Option<String>,
 ) -> Result<PageSuccessPayload, ErrorPayload> {
-    graphQLRequest::<PageRequestPayload, page::Variables, page::ResponseData, PageSuccessPayload>(
+    graphQLRequest::<PageRequestPayload, page::Variables, page::ResponseData, page::ResponseData>(
         input,
         Page::build_query,
         token,
     )
     .await
+    .and_then(PageSuccessPayload::try_from)
 }
 
 impl Into<create_anchor::Variables> for CreateAnchorRequestPayload {
@@ -500,6 +947,97 @@ pub async fn create_user_anchor(
     .await
 }
 
+impl Into<update_anchor::Variables> for UpdateAnchorRequestPayload {
+    fn into(self) -> update_anchor::Variables {
+        update_anchor::Variables {
+            anchor_id: self.anchor_id as i64,
+            page_id: self.page_id as i64,
+            position_left: self.position_left as f64,
+            position_top: self.position_top as f64,
+            title: self.title,
+            track_id: self.track_id as i64,
+            track_time: self.track_time as f64,
+        }
+    }
+}
+
+impl Into<update_user_anchor::Variables> for UpdateAnchorRequestPayload {
+    fn into(self) -> update_user_anchor::Variables {
+        update_user_anchor::Variables {
+            user_anchor_id: self.anchor_id as i64,
+            page_id: self.page_id as i64,
+            position_left: self.position_left as f64,
+            position_top: self.position_top as f64,
+            title: self.title,
+            track_id: self.track_id as i64,
+            track_time: self.track_time as f64,
+        }
+    }
+}
+
+impl Into<UpdateAnchorSuccessPayload> for update_anchor::ResponseData {
+    fn into(self) -> UpdateAnchorSuccessPayload {
+        UpdateAnchorSuccessPayload {
+            anchor: Anchor {
+                id: self.update_anchor.id as i32,
+                title: self.update_anchor.title,
+                track_time: self.update_anchor.track_time as f32,
+                position_top: self.update_anchor.position_top as f32,
+                position_left: self.update_anchor.position_left as f32,
+                page_id: self.update_anchor.page.id as i32,
+                track_id: self.update_anchor.track.id as i32,
+                created_at: self.update_anchor.created_at,
+                updated_at: self.update_anchor.updated_at,
+            },
+        }
+    }
+}
+
+impl Into<UpdateUserAnchorSuccessPayload> for update_user_anchor::ResponseData {
+    fn into(self) -> UpdateUserAnchorSuccessPayload {
+        UpdateUserAnchorSuccessPayload {
+            user_anchor: UserAnchor {
+                id: self.update_user_anchor.id as i32,
+                title: self.update_user_anchor.title,
+                track_time: self.update_user_anchor.track_time as f32,
+                position_top: self.update_user_anchor.position_top as f32,
+                position_left: self.update_user_anchor.position_left as f32,
+                page_id: self.update_user_anchor.page.id as i32,
+                track_id: self.update_user_anchor.track.id as i32,
+                created_at: self.update_user_anchor.created_at,
+                updated_at: self.update_user_anchor.updated_at,
+                owner: self.update_user_anchor.owner.id as i32,
+            },
+        }
+    }
+}
+
+pub async fn update_anchor(
+    input: UpdateAnchorRequestPayload,
+    token: Option<String>,
+) -> Result<UpdateAnchorSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        UpdateAnchorRequestPayload,
+        update_anchor::Variables,
+        update_anchor::ResponseData,
+        UpdateAnchorSuccessPayload,
+    >(input, UpdateAnchor::build_query, token)
+    .await
+}
+
+pub async fn update_user_anchor(
+    input: UpdateAnchorRequestPayload,
+    token: Option<String>,
+) -> Result<UpdateUserAnchorSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        UpdateAnchorRequestPayload,
+        update_user_anchor::Variables,
+        update_user_anchor::ResponseData,
+        UpdateUserAnchorSuccessPayload,
+    >(input, UpdateUserAnchor::build_query, token)
+    .await
+}
+
 impl Into<delete_anchor::Variables> for DeleteAnchorRequestPayload {
     fn into(self) -> delete_anchor::Variables {
         delete_anchor::Variables {
@@ -558,6 +1096,201 @@ pub async fn delete_user_anchor(
     .await
 }
 
+impl Into<promote_user_anchor::Variables> for PromoteUserAnchorRequestPayload {
+    fn into(self) -> promote_user_anchor::Variables {
+        promote_user_anchor::Variables {
+            user_anchor_id: self.user_anchor_id as i64,
+        }
+    }
+}
+
+impl Into<CreateAnchorSuccessPayload> for promote_user_anchor::ResponseData {
+    fn into(self) -> CreateAnchorSuccessPayload {
+        CreateAnchorSuccessPayload {
+            anchor: Anchor {
+                id: self.promote_user_anchor.id as i32,
+                title: self.promote_user_anchor.title,
+                track_time: self.promote_user_anchor.track_time as f32,
+                position_top: self.promote_user_anchor.position_top as f32,
+                position_left: self.promote_user_anchor.position_left as f32,
+                page_id: self.promote_user_anchor.page.id as i32,
+                track_id: self.promote_user_anchor.track.id as i32,
+                created_at: self.promote_user_anchor.created_at,
+                updated_at: self.promote_user_anchor.updated_at,
+            },
+        }
+    }
+}
+
+pub async fn promote_user_anchor(
+    input: PromoteUserAnchorRequestPayload,
+    token: Option<String>,
+) -> Result<CreateAnchorSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        PromoteUserAnchorRequestPayload,
+        promote_user_anchor::Variables,
+        promote_user_anchor::ResponseData,
+        CreateAnchorSuccessPayload,
+    >(input, PromoteUserAnchor::build_query, token)
+    .await
+}
+
+impl Into<save_progress::Variables> for SaveProgressRequestPayload {
+    fn into(self) -> save_progress::Variables {
+        save_progress::Variables {
+            document_id: self.document_id as i64,
+            track_id: self.track_id as i64,
+            track_time: self.track_time as f64,
+            page_id: self.page_id as i64,
+            completion: self.completion as f64,
+        }
+    }
+}
+
+impl Into<SaveProgressSuccessPayload> for save_progress::ResponseData {
+    fn into(self) -> SaveProgressSuccessPayload {
+        SaveProgressSuccessPayload {
+            progress: Progress {
+                document_id: self.save_progress.document.id as i32,
+                track_id: self.save_progress.track.id as i32,
+                track_time: self.save_progress.track_time as f32,
+                page_id: self.save_progress.page.id as i32,
+                completion: self.save_progress.completion as f32,
+            },
+        }
+    }
+}
+
+pub async fn save_progress(
+    input: SaveProgressRequestPayload,
+    token: Option<String>,
+) -> Result<SaveProgressSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        SaveProgressRequestPayload,
+        save_progress::Variables,
+        save_progress::ResponseData,
+        SaveProgressSuccessPayload,
+    >(input, SaveProgress::build_query, token)
+    .await
+}
+
+impl Into<create_user_note::Variables> for CreateUserNoteRequestPayload {
+    fn into(self) -> create_user_note::Variables {
+        create_user_note::Variables {
+            document_id: self.document_id as i64,
+            content: self.content,
+            page_id: self.page_id.map(|id| id as i64),
+            track_id: self.track_id.map(|id| id as i64),
+            track_time: self.track_time.map(|t| t as f64),
+        }
+    }
+}
+
+impl Into<CreateUserNoteSuccessPayload> for create_user_note::ResponseData {
+    fn into(self) -> CreateUserNoteSuccessPayload {
+        let note = self.create_user_note;
+        CreateUserNoteSuccessPayload {
+            user_note: UserNote {
+                id: note.id as i32,
+                document_id: note.document.id as i32,
+                content: note.content,
+                page_id: note.page.map(|p| p.id as i32),
+                track_id: note.track.map(|t| t.id as i32),
+                track_time: note.track_time.unwrap_or(0.0) as f32,
+            },
+        }
+    }
+}
+
+pub async fn create_user_note(
+    input: CreateUserNoteRequestPayload,
+    token: Option<String>,
+) -> Result<CreateUserNoteSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        CreateUserNoteRequestPayload,
+        create_user_note::Variables,
+        create_user_note::ResponseData,
+        CreateUserNoteSuccessPayload,
+    >(input, CreateUserNote::build_query, token)
+    .await
+}
+
+impl Into<delete_user_note::Variables> for DeleteUserNoteRequestPayload {
+    fn into(self) -> delete_user_note::Variables {
+        delete_user_note::Variables {
+            user_note_id: self.user_note_id as i64,
+        }
+    }
+}
+
+impl Into<DeleteUserNoteSuccessPayload> for delete_user_note::ResponseData {
+    fn into(self) -> DeleteUserNoteSuccessPayload {
+        DeleteUserNoteSuccessPayload {
+            success: self.delete_user_note.success,
+        }
+    }
+}
+
+pub async fn delete_user_note(
+    input: DeleteUserNoteRequestPayload,
+    token: Option<String>,
+) -> Result<DeleteUserNoteSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        DeleteUserNoteRequestPayload,
+        delete_user_note::Variables,
+        delete_user_note::ResponseData,
+        DeleteUserNoteSuccessPayload,
+    >(input, DeleteUserNote::build_query, token)
+    .await
+}
+
+impl Into<search_document_text::Variables> for SearchDocumentTextRequestPayload {
+    fn into(self) -> search_document_text::Variables {
+        search_document_text::Variables {
+            document_id: self.document_id as i64,
+            query: self.query,
+        }
+    }
+}
+
+impl TryFrom<search_document_text::ResponseData> for SearchDocumentTextSuccessPayload {
+    type Error = ErrorPayload;
+
+    fn try_from(data: search_document_text::ResponseData) -> Result<Self, Self::Error> {
+        let document = data.document_by_id.ok_or_else(|| not_found("Document"))?;
+        Ok(SearchDocumentTextSuccessPayload {
+            matches: document
+                .search
+                .into_iter()
+                .map(|m| TextMatch {
+                    page_id: m.page_id as i32,
+                    page_number: m.page_number as i32,
+                    content: m.content,
+                    position_top: m.position_top as f32,
+                    position_left: m.position_left as f32,
+                    anchor_id: m.anchor.map(|a| a.id as i32),
+                    track_id: m.track.map(|t| t.id as i32),
+                    track_time: m.track_time.map(|t| t as f32),
+                })
+                .collect(),
+        })
+    }
+}
+
+pub async fn search_document_text(
+    input: SearchDocumentTextRequestPayload,
+    token: Option<String>,
+) -> Result<SearchDocumentTextSuccessPayload, ErrorPayload> {
+    graphQLRequest::<
+        SearchDocumentTextRequestPayload,
+        search_document_text::Variables,
+        search_document_text::ResponseData,
+        search_document_text::ResponseData,
+    >(input, SearchDocumentText::build_query, token)
+    .await
+    .and_then(SearchDocumentTextSuccessPayload::try_from)
+}
+
 impl Into<jump_to_anchor::Variables> for JumpToAnchorRequestPayload {
     fn into(self) -> jump_to_anchor::Variables {
         jump_to_anchor::Variables {
@@ -566,21 +1299,24 @@ impl Into<jump_to_anchor::Variables> for JumpToAnchorRequestPayload {
     }
 }
 
-impl Into<JumpToAnchorSuccessPayload> for jump_to_anchor::ResponseData {
-    fn into(self) -> JumpToAnchorSuccessPayload {
-        JumpToAnchorSuccessPayload {
+impl TryFrom<jump_to_anchor::ResponseData> for JumpToAnchorSuccessPayload {
+    type Error = ErrorPayload;
+
+    fn try_from(data: jump_to_anchor::ResponseData) -> Result<Self, Self::Error> {
+        let anchor = data.anchor_by_id.ok_or_else(|| not_found("Anchor"))?;
+        Ok(JumpToAnchorSuccessPayload {
             anchor: Anchor {
-                id: self.anchor_by_id.id as i32,
-                title: self.anchor_by_id.title,
-                track_time: self.anchor_by_id.track_time as f32,
-                position_top: self.anchor_by_id.position_top as f32,
-                position_left: self.anchor_by_id.position_left as f32,
-                page_id: self.anchor_by_id.page.id as i32,
-                track_id: self.anchor_by_id.track.id as i32,
-                created_at: self.anchor_by_id.created_at,
-                updated_at: self.anchor_by_id.updated_at,
+                id: anchor.id as i32,
+                title: anchor.title,
+                track_time: anchor.track_time as f32,
+                position_top: anchor.position_top as f32,
+                position_left: anchor.position_left as f32,
+                page_id: anchor.page.id as i32,
+                track_id: anchor.track.id as i32,
+                created_at: anchor.created_at,
+                updated_at: anchor.updated_at,
             },
-        }
+        })
     }
 }
 
@@ -592,7 +1328,46 @@ pub async fn jump_to_anchor(
         JumpToAnchorRequestPayload,
         jump_to_anchor::Variables,
         jump_to_anchor::ResponseData,
-        JumpToAnchorSuccessPayload,
+        jump_to_anchor::ResponseData,
     >(input, JumpToAnchor::build_query, token)
     .await
+    .and_then(JumpToAnchorSuccessPayload::try_from)
+}
+
+impl Into<AnchorChangedPayload> for page_anchors_changed::ResponseData {
+    fn into(self) -> AnchorChangedPayload {
+        let change = self.page_anchors_changed;
+        AnchorChangedPayload {
+            kind: match change.kind {
+                page_anchors_changed::AnchorChangeKind::DELETED => AnchorChangeKind::Deleted,
+                _ => AnchorChangeKind::Upserted,
+            },
+            anchor: Anchor {
+                id: change.anchor.id as i32,
+                title: change.anchor.title,
+                track_time: change.anchor.track_time as f32,
+                position_top: change.anchor.position_top as f32,
+                position_left: change.anchor.position_left as f32,
+                page_id: change.anchor.page.id as i32,
+                track_id: change.anchor.track.id as i32,
+                created_at: change.anchor.created_at,
+                updated_at: change.anchor.updated_at,
+            },
+        }
+    }
+}
+
+pub fn page_anchors_changed(
+    document_id: i32,
+) -> Result<GraphQLSubscription<AnchorChangedPayload>, ErrorPayload> {
+    graphQLSubscription::<
+        page_anchors_changed::Variables,
+        page_anchors_changed::ResponseData,
+        AnchorChangedPayload,
+    >(
+        page_anchors_changed::Variables {
+            document_id: document_id as i64,
+        },
+        PageAnchorsChanged::build_query,
+    )
 }
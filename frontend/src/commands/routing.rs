@@ -3,10 +3,10 @@ use iced::Command;
 
 use crate::{
     messages::{application, routing, Msg},
-    state::Route,
+    state::{Model, Route},
 };
 
-pub fn get_command(msg: &routing::Msg) -> Command<Msg> {
+pub fn get_command(msg: &routing::Msg, state: &Model) -> Command<Msg> {
     match msg {
         routing::Msg::Push(r) => {
             let new_url = String::from(r.clone());
@@ -42,14 +42,11 @@ pub fn get_command(msg: &routing::Msg) -> Command<Msg> {
             Command::none()
         }
         routing::Msg::Navigate(r) => match r {
-            Route::Courses => Command::perform(ready(()), |_| {
-                Msg::Application(application::Msg::AllDocumentsRequest(
-                    application::AllDocumentsRequestPayload {
-                        limit: std::i32::MAX,
-                        offset: 0,
-                    },
-                ))
-            }),
+            Route::Courses => {
+                Command::perform(ready(state.ui.courses_screen.search_payload()), |payload| {
+                    Msg::Application(application::Msg::SearchDocumentsRequest(payload))
+                })
+            }
             Route::Course(id, _anchor) => {
                 let document_id = id.clone();
                 Command::perform(ready(()), move |_| {
@@ -5,28 +5,69 @@ use wasm_bindgen::JsCast;
 use crate::{
     messages::{
         application::{
-            self, CreateAnchorRequestPayload, DeleteAnchorRequestPayload, PageRequestPayload,
+            self, CreateAnchorRequestPayload, CreateUserNoteRequestPayload,
+            DeleteAnchorRequestPayload, DeleteUserNoteRequestPayload, PageRequestPayload,
+            PromoteUserAnchorRequestPayload, SaveProgressRequestPayload,
+            SearchDocumentTextRequestPayload, UpdateAnchorRequestPayload,
         },
         routing, ui, Msg,
     },
     state::{ui::course_screen::CursorMode, Model, Route},
 };
 
+/// Page of the document closest to the scroll position
+fn active_page(state: &Model, document_id: i32, relative_scroll: f32) -> i32 {
+    let (mut active_page, mut min_distance) = (0, std::f32::MAX);
+    for page_id in state.entities.document_pages.get(&document_id).unwrap() {
+        let distance =
+            (relative_scroll - state.entities.pages_by_id.get(page_id).unwrap().height).abs();
+        if distance < min_distance {
+            active_page = *page_id;
+            min_distance = distance;
+        }
+    }
+    active_page
+}
+
+/// Track loaded in the audio element along with its current time
+fn loaded_track(state: &Model, document_id: i32) -> Option<(i32, f64)> {
+    let window = web_sys::window().expect("no global `window` exists");
+    let document = window.document().expect("should have a document on window");
+    let el = document
+        .get_element_by_id("audio")
+        .and_then(|el| el.dyn_into::<web_sys::HtmlAudioElement>().ok())?;
+
+    let current_src = el.current_src();
+    state
+        .entities
+        .document_tracks
+        .get(&document_id)?
+        .iter()
+        .find(|t| {
+            current_src
+                .find(&state.entities.tracks_by_id.get(t).unwrap().audio_url)
+                .is_some()
+        })
+        .map(|t| (*t, el.current_time()))
+}
+
+/// New title for an anchor asked of the user, or `None` if they dismissed the prompt or kept the
+/// title as it was
+fn prompt_title(current: &str) -> Option<String> {
+    let window = web_sys::window().expect("no global `window` exists");
+    window
+        .prompt_with_message_and_default("Anchor title", current)
+        .ok()
+        .flatten()
+        .filter(|title| title != current)
+}
+
 pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
     match msg {
         ui::Msg::Course(ui::course::Msg::UpdateRelativeScroll(relative_scroll)) => {
             if !state.ui.course_screen.loading {
                 if let Route::Course(course_id, _) = state.routing.route {
-                    let (mut active_page, mut min_distance) = (0, std::f32::MAX);
-                    for page_id in state.entities.document_pages.get(&course_id).unwrap() {
-                        let distance = (relative_scroll
-                            - state.entities.pages_by_id.get(page_id).unwrap().height)
-                            .abs();
-                        if distance < min_distance {
-                            active_page = *page_id;
-                            min_distance = distance;
-                        }
-                    }
+                    let active_page = active_page(state, course_id, *relative_scroll);
 
                     if state.entities.page_anchors.get(&active_page).is_none() {
                         return Command::perform(ready(active_page), |page_id| {
@@ -50,7 +91,7 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                 .dyn_into::<web_sys::HtmlAudioElement>()
                 .unwrap();
 
-            el.set_src(format!("https://synchrotron.nsenger.com/{}", track.audio_path).as_str());
+            el.set_src(track.audio_url.as_str());
             el.set_current_time(0.0);
             el.play();
 
@@ -70,16 +111,13 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                         .unwrap();
 
                     el.set_src(
-                        format!(
-                            "https://synchrotron.nsenger.com/{}",
-                            state
-                                .entities
-                                .tracks_by_id
-                                .get(&anchor.track_id)
-                                .unwrap()
-                                .audio_path
-                        )
-                        .as_str(),
+                        state
+                            .entities
+                            .tracks_by_id
+                            .get(&anchor.track_id)
+                            .unwrap()
+                            .audio_url
+                            .as_str(),
                     );
                     el.set_current_time(anchor.track_time as f64);
                     el.play();
@@ -101,6 +139,26 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                     )),
                     Msg::Application,
                 ),
+                CursorMode::Rename => {
+                    let anchor = state.entities.anchors_by_id.get(anchor_id).unwrap();
+                    match prompt_title(&anchor.title) {
+                        Some(title) => Command::perform(
+                            ready(application::Msg::UpdateAnchorRequest(
+                                UpdateAnchorRequestPayload {
+                                    anchor_id: *anchor_id,
+                                    title,
+                                    track_time: anchor.track_time,
+                                    position_top: anchor.position_top,
+                                    position_left: anchor.position_left,
+                                    page_id: anchor.page_id,
+                                    track_id: anchor.track_id,
+                                },
+                            )),
+                            Msg::Application,
+                        ),
+                        None => Command::none(),
+                    }
+                }
                 _ => Command::none(),
             }
         }
@@ -122,16 +180,13 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                         .unwrap();
 
                     el.set_src(
-                        format!(
-                            "https://synchrotron.nsenger.com/{}",
-                            state
-                                .entities
-                                .tracks_by_id
-                                .get(&anchor.track_id)
-                                .unwrap()
-                                .audio_path
-                        )
-                        .as_str(),
+                        state
+                            .entities
+                            .tracks_by_id
+                            .get(&anchor.track_id)
+                            .unwrap()
+                            .audio_url
+                            .as_str(),
                     );
                     el.set_current_time(anchor.track_time as f64);
                     el.play();
@@ -154,37 +209,41 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                         .unwrap_or(0)
                         > 0
                     {
-                        let existing_anchor = state
-                            .entities
-                            .user_anchors_by_id
-                            .get(user_anchor_id)
-                            .unwrap();
-                        return Command::batch(vec![
-                            Command::perform(
-                                ready(application::Msg::DeleteUserAnchorRequest(
-                                    DeleteAnchorRequestPayload {
-                                        anchor_id: *user_anchor_id,
-                                    },
-                                )),
-                                Msg::Application,
-                            ),
-                            Command::perform(
-                                ready(application::Msg::CreateAnchorRequest(
-                                    CreateAnchorRequestPayload {
-                                        title: existing_anchor.title.clone(),
-                                        track_time: existing_anchor.track_time.clone(),
-                                        position_top: existing_anchor.position_top.clone(),
-                                        position_left: existing_anchor.position_left.clone(),
-                                        page_id: existing_anchor.page_id,
-                                        track_id: existing_anchor.track_id,
-                                    },
-                                )),
-                                Msg::Application,
-                            ),
-                        ]);
+                        return Command::perform(
+                            ready(application::Msg::PromoteUserAnchorRequest(
+                                PromoteUserAnchorRequestPayload {
+                                    user_anchor_id: *user_anchor_id,
+                                },
+                            )),
+                            Msg::Application,
+                        );
                     }
                     Command::none()
                 }
+                CursorMode::Rename => {
+                    let anchor = state
+                        .entities
+                        .user_anchors_by_id
+                        .get(user_anchor_id)
+                        .unwrap();
+                    match prompt_title(&anchor.title) {
+                        Some(title) => Command::perform(
+                            ready(application::Msg::UpdateUserAnchorRequest(
+                                UpdateAnchorRequestPayload {
+                                    anchor_id: *user_anchor_id,
+                                    title,
+                                    track_time: anchor.track_time,
+                                    position_top: anchor.position_top,
+                                    position_left: anchor.position_left,
+                                    page_id: anchor.page_id,
+                                    track_id: anchor.track_id,
+                                },
+                            )),
+                            Msg::Application,
+                        ),
+                        None => Command::none(),
+                    }
+                }
                 _ => Command::none(),
             }
         }
@@ -203,35 +262,24 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                 .unwrap()
                 .aspect_ratio
                 * inner_width;
-            Command::batch(vec![
-                Command::perform(
-                    ready(application::Msg::DeleteAnchorRequest(
-                        DeleteAnchorRequestPayload {
-                            anchor_id: payload.anchor_id,
-                        },
-                    )),
-                    Msg::Application,
-                ),
-                Command::perform(
-                    ready(application::Msg::CreateAnchorRequest(
-                        CreateAnchorRequestPayload {
-                            title: existing_anchor.title.clone(),
-                            track_time: existing_anchor.track_time.clone(),
-                            position_top: existing_anchor.position_top.clone()
-                                + (((payload.y - state.ui.course_screen.drag_y) as f32
-                                    / page_height)
-                                    * 100.0),
-                            position_left: existing_anchor.position_left.clone()
-                                + (((payload.x - state.ui.course_screen.drag_x) as f32
-                                    / inner_width)
-                                    * 100.0),
-                            page_id: existing_anchor.page_id,
-                            track_id: existing_anchor.track_id,
-                        },
-                    )),
-                    Msg::Application,
-                ),
-            ])
+            Command::perform(
+                ready(application::Msg::UpdateAnchorRequest(
+                    UpdateAnchorRequestPayload {
+                        anchor_id: payload.anchor_id,
+                        title: existing_anchor.title.clone(),
+                        track_time: existing_anchor.track_time.clone(),
+                        position_top: existing_anchor.position_top.clone()
+                            + (((payload.y - state.ui.course_screen.drag_y) as f32 / page_height)
+                                * 100.0),
+                        position_left: existing_anchor.position_left.clone()
+                            + (((payload.x - state.ui.course_screen.drag_x) as f32 / inner_width)
+                                * 100.0),
+                        page_id: existing_anchor.page_id,
+                        track_id: existing_anchor.track_id,
+                    },
+                )),
+                Msg::Application,
+            )
         }
         ui::Msg::Course(ui::course::Msg::DragUserAnchor(payload)) => {
             let window = web_sys::window().expect("no global `window` exists");
@@ -248,35 +296,24 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                 .unwrap()
                 .aspect_ratio
                 * inner_width;
-            Command::batch(vec![
-                Command::perform(
-                    ready(application::Msg::DeleteUserAnchorRequest(
-                        DeleteAnchorRequestPayload {
-                            anchor_id: payload.user_anchor_id,
-                        },
-                    )),
-                    Msg::Application,
-                ),
-                Command::perform(
-                    ready(application::Msg::CreateUserAnchorRequest(
-                        CreateAnchorRequestPayload {
-                            title: existing_anchor.title.clone(),
-                            track_time: existing_anchor.track_time.clone(),
-                            position_top: existing_anchor.position_top.clone()
-                                + (((payload.y - state.ui.course_screen.drag_y) as f32
-                                    / page_height)
-                                    * 100.0),
-                            position_left: existing_anchor.position_left.clone()
-                                + (((payload.x - state.ui.course_screen.drag_x) as f32
-                                    / inner_width)
-                                    * 100.0),
-                            page_id: existing_anchor.page_id,
-                            track_id: existing_anchor.track_id,
-                        },
-                    )),
-                    Msg::Application,
-                ),
-            ])
+            Command::perform(
+                ready(application::Msg::UpdateUserAnchorRequest(
+                    UpdateAnchorRequestPayload {
+                        anchor_id: payload.user_anchor_id,
+                        title: existing_anchor.title.clone(),
+                        track_time: existing_anchor.track_time.clone(),
+                        position_top: existing_anchor.position_top.clone()
+                            + (((payload.y - state.ui.course_screen.drag_y) as f32 / page_height)
+                                * 100.0),
+                        position_left: existing_anchor.position_left.clone()
+                            + (((payload.x - state.ui.course_screen.drag_x) as f32 / inner_width)
+                                * 100.0),
+                        page_id: existing_anchor.page_id,
+                        track_id: existing_anchor.track_id,
+                    },
+                )),
+                Msg::Application,
+            )
         }
         ui::Msg::Course(ui::course::Msg::SelectBookmark(bookmark_id)) => {
             let window = web_sys::window().expect("no global `window` exists");
@@ -334,7 +371,7 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
                                     .entities
                                     .tracks_by_id
                                     .values()
-                                    .find(|&t| current_src.find(&t.audio_path).is_some())
+                                    .find(|&t| current_src.find(&t.audio_url).is_some())
                                     .unwrap()
                                     .id,
                             },
@@ -345,6 +382,213 @@ pub fn get_command(msg: &ui::Msg, state: &Model) -> Command<Msg> {
             }
             Command::none()
         }
+        ui::Msg::Course(ui::course::Msg::ProgressTick) => {
+            if state.authentication.token.is_none() || state.ui.course_screen.loading {
+                return Command::none();
+            }
+
+            if let Route::Course(document_id, _) = state.routing.route {
+                let window = web_sys::window().expect("no global `window` exists");
+                let document = window.document().expect("should have a document on window");
+                let el = match document
+                    .get_element_by_id("audio")
+                    .and_then(|el| el.dyn_into::<web_sys::HtmlAudioElement>().ok())
+                {
+                    None => return Command::none(),
+                    Some(el) => el,
+                };
+
+                // Nothing new to save while the audio is not playing
+                if el.paused() {
+                    return Command::none();
+                }
+
+                let current_src = el.current_src();
+                let tracks = state.entities.document_tracks.get(&document_id).unwrap();
+                let track_index = match tracks.iter().position(|t| {
+                    current_src
+                        .find(&state.entities.tracks_by_id.get(t).unwrap().audio_url)
+                        .is_some()
+                }) {
+                    None => return Command::none(),
+                    Some(i) => i,
+                };
+
+                let duration = el.duration();
+                let track_fraction = if duration.is_finite() && duration > 0.0 {
+                    el.current_time() / duration
+                } else {
+                    0.0
+                };
+
+                return Command::perform(
+                    ready(application::Msg::SaveProgressRequest(
+                        SaveProgressRequestPayload {
+                            document_id,
+                            track_id: tracks[track_index],
+                            track_time: el.current_time() as f32,
+                            page_id: active_page(
+                                state,
+                                document_id,
+                                state.ui.course_screen.relative_scroll,
+                            ),
+                            completion: (100.0 * (track_index as f64 + track_fraction)
+                                / tracks.len() as f64)
+                                as f32,
+                        },
+                    )),
+                    Msg::Application,
+                );
+            }
+            Command::none()
+        }
+        ui::Msg::Course(ui::course::Msg::ResumeProgress) => {
+            if let Route::Course(document_id, _) = state.routing.route {
+                if let Some(progress) = state.entities.document_progress.get(&document_id) {
+                    let window = web_sys::window().expect("no global `window` exists");
+                    let document = window.document().expect("should have a document on window");
+
+                    let el = document
+                        .get_element_by_id("audio")
+                        .unwrap()
+                        .dyn_into::<web_sys::HtmlAudioElement>()
+                        .unwrap();
+
+                    el.set_src(
+                        state
+                            .entities
+                            .tracks_by_id
+                            .get(&progress.track_id)
+                            .unwrap()
+                            .audio_url
+                            .as_str(),
+                    );
+                    el.set_current_time(progress.track_time as f64);
+                    el.play();
+
+                    document
+                        .get_element_by_id(format!("p-{}", progress.page_id).as_str())
+                        .and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok())
+                        .map(|el| el.scroll_into_view());
+                }
+            }
+            Command::none()
+        }
+        ui::Msg::Course(ui::course::Msg::AddNote) => {
+            let content = state.ui.course_screen.note_input_value.trim();
+            if content.is_empty() || state.authentication.token.is_none() {
+                return Command::none();
+            }
+
+            if let Route::Course(document_id, _) = state.routing.route {
+                let track = loaded_track(state, document_id);
+                return Command::perform(
+                    ready(application::Msg::CreateUserNoteRequest(
+                        CreateUserNoteRequestPayload {
+                            document_id,
+                            content: content.to_owned(),
+                            page_id: Some(active_page(
+                                state,
+                                document_id,
+                                state.ui.course_screen.relative_scroll,
+                            )),
+                            track_id: track.map(|(track_id, _)| track_id),
+                            track_time: track.map(|(_, time)| time as f32),
+                        },
+                    )),
+                    Msg::Application,
+                );
+            }
+            Command::none()
+        }
+        ui::Msg::Course(ui::course::Msg::SelectNote(user_note_id)) => {
+            let window = web_sys::window().expect("no global `window` exists");
+            let document = window.document().expect("should have a document on window");
+
+            let note = state.entities.user_notes_by_id.get(user_note_id).unwrap();
+            if let Some(track) = note
+                .track_id
+                .and_then(|track_id| state.entities.tracks_by_id.get(&track_id))
+            {
+                let el = document
+                    .get_element_by_id("audio")
+                    .unwrap()
+                    .dyn_into::<web_sys::HtmlAudioElement>()
+                    .unwrap();
+
+                el.set_src(track.audio_url.as_str());
+                el.set_current_time(note.track_time as f64);
+                el.play();
+            }
+
+            if let Some(page_id) = note.page_id {
+                document
+                    .get_element_by_id(format!("p-{}", page_id).as_str())
+                    .and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok())
+                    .map(|el| el.scroll_into_view());
+            }
+
+            Command::none()
+        }
+        ui::Msg::Course(ui::course::Msg::DeleteNote(user_note_id)) => Command::perform(
+            ready(application::Msg::DeleteUserNoteRequest(
+                DeleteUserNoteRequestPayload {
+                    user_note_id: *user_note_id,
+                },
+            )),
+            Msg::Application,
+        ),
+        ui::Msg::Course(ui::course::Msg::SearchText) => {
+            let query = state.ui.course_screen.text_search_value.trim();
+            if query.is_empty() {
+                return Command::none();
+            }
+
+            if let Route::Course(document_id, _) = state.routing.route {
+                return Command::perform(
+                    ready(application::Msg::SearchDocumentTextRequest(
+                        SearchDocumentTextRequestPayload {
+                            document_id,
+                            query: query.to_owned(),
+                        },
+                    )),
+                    Msg::Application,
+                );
+            }
+            Command::none()
+        }
+        ui::Msg::Course(ui::course::Msg::SelectTextMatch(index)) => {
+            let text_match = match state.ui.course_screen.text_matches.get(*index) {
+                None => return Command::none(),
+                Some(text_match) => text_match,
+            };
+
+            if let Route::Course(document_id, _) = state.routing.route {
+                if let Some(anchor_id) = text_match.anchor_id {
+                    return Command::perform(
+                        ready(routing::Msg::Push(Route::Course(
+                            document_id,
+                            Some(anchor_id),
+                        ))),
+                        Msg::Routing,
+                    );
+                }
+
+                // Pages without anchors have no audio to cue, so only scroll to the match
+                let window = web_sys::window().expect("no global `window` exists");
+                let document = window.document().expect("should have a document on window");
+                document
+                    .get_element_by_id(format!("p-{}", text_match.page_id).as_str())
+                    .and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok())
+                    .map(|el| el.scroll_into_view());
+            }
+            Command::none()
+        }
+        ui::Msg::Courses(_) => {
+            Command::perform(ready(state.ui.courses_screen.search_payload()), |payload| {
+                Msg::Application(application::Msg::SearchDocumentsRequest(payload))
+            })
+        }
         _ => Command::none(),
     }
 }
@@ -8,7 +8,7 @@ mod state;
 mod subscription;
 mod view;
 
-use messages::{routing, Msg};
+use messages::{application, routing, ui, Msg};
 
 #[wasm_bindgen]
 pub fn main() {
@@ -75,7 +75,32 @@ impl Application for Synchrotron {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        subscription::route_change(self.state.routing.route.clone())
-            .map(|r| Msg::Routing(routing::Msg::Navigate(r)))
+        let route_changes = subscription::route_change(self.state.routing.route.clone())
+            .map(|r| Msg::Routing(routing::Msg::Navigate(r)));
+
+        match self.state.routing.route {
+            state::Route::Course(document_id, _) => {
+                let mut subscriptions = vec![
+                    route_changes,
+                    subscription::anchor_changes(document_id).map(|event| match event {
+                        subscription::AnchorChangesEvent::Changed(change) => {
+                            Msg::Application(application::Msg::AnchorChanged(change))
+                        }
+                        subscription::AnchorChangesEvent::Missed(document_id) => {
+                            Msg::Application(application::Msg::AnchorChangesMissed(document_id))
+                        }
+                    }),
+                ];
+                // Progress is only saved for logged in users
+                if self.state.authentication.token.is_some() {
+                    subscriptions.push(
+                        subscription::progress_ticks(document_id)
+                            .map(|_| Msg::Ui(ui::Msg::Course(ui::course::Msg::ProgressTick))),
+                    );
+                }
+                Subscription::batch(subscriptions)
+            }
+            _ => route_changes,
+        }
     }
 }
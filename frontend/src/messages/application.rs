@@ -1,5 +1,11 @@
 use super::ErrorPayload;
-use crate::state::entities::{Anchor, Bookmark, Document, Page, Track, UserAnchor};
+use crate::state::{
+    entities::{
+        Anchor, Bookmark, Document, Page, Progress, TextMatch, TextRegion, Track, UserAnchor,
+        UserNote,
+    },
+    ui::courses_screen::DocumentSort,
+};
 
 #[derive(Clone, Debug)]
 pub struct AllDocumentsRequestPayload {
@@ -12,6 +18,20 @@ pub struct AllDocumentsSuccessPayload {
     pub documents: Vec<Document>,
 }
 
+#[derive(Clone, Debug)]
+pub struct SearchDocumentsRequestPayload {
+    pub query: String,
+    pub language: String,
+    pub level: String,
+    pub sort: DocumentSort,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchDocumentsSuccessPayload {
+    pub documents: Vec<Document>,
+    pub total: i32,
+}
+
 #[derive(Clone, Debug)]
 pub struct DocumentRequestPayload {
     pub document_id: i32,
@@ -23,6 +43,8 @@ pub struct DocumentSuccessPayload {
     pub bookmarks: Vec<Bookmark>,
     pub pages: Vec<Page>,
     pub tracks: Vec<Track>,
+    pub progress: Option<Progress>,
+    pub user_notes: Vec<UserNote>,
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +57,7 @@ pub struct PageSuccessPayload {
     pub page: Page,
     pub anchors: Vec<Anchor>,
     pub user_anchors: Vec<UserAnchor>,
+    pub text_regions: Vec<TextRegion>,
 }
 #[derive(Clone, Debug)]
 pub struct CreateAnchorRequestPayload {
@@ -56,6 +79,27 @@ pub struct CreateUserAnchorSuccessPayload {
     pub user_anchor: UserAnchor,
 }
 
+#[derive(Clone, Debug)]
+pub struct UpdateAnchorRequestPayload {
+    pub anchor_id: i32,
+    pub title: String,
+    pub track_time: f32,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub page_id: i32,
+    pub track_id: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct UpdateAnchorSuccessPayload {
+    pub anchor: Anchor,
+}
+
+#[derive(Clone, Debug)]
+pub struct UpdateUserAnchorSuccessPayload {
+    pub user_anchor: UserAnchor,
+}
+
 #[derive(Clone, Debug)]
 pub struct DeleteAnchorRequestPayload {
     pub anchor_id: i32,
@@ -66,6 +110,11 @@ pub struct DeleteAnchorSuccessPayload {
     pub success: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct PromoteUserAnchorRequestPayload {
+    pub user_anchor_id: i32,
+}
+
 #[derive(Clone, Debug)]
 pub struct JumpToAnchorRequestPayload {
     pub anchor_id: i32,
@@ -76,21 +125,101 @@ pub struct JumpToAnchorSuccessPayload {
     pub anchor: Anchor,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnchorChangeKind {
+    Upserted,
+    Deleted,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnchorChangedPayload {
+    pub kind: AnchorChangeKind,
+    pub anchor: Anchor,
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveProgressRequestPayload {
+    pub document_id: i32,
+    pub track_id: i32,
+    pub track_time: f32,
+    pub page_id: i32,
+    pub completion: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveProgressSuccessPayload {
+    pub progress: Progress,
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateUserNoteRequestPayload {
+    pub document_id: i32,
+    pub content: String,
+    pub page_id: Option<i32>,
+    pub track_id: Option<i32>,
+    pub track_time: Option<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateUserNoteSuccessPayload {
+    pub user_note: UserNote,
+}
+
+#[derive(Clone, Debug)]
+pub struct DeleteUserNoteRequestPayload {
+    pub user_note_id: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct DeleteUserNoteSuccessPayload {
+    pub success: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchDocumentTextRequestPayload {
+    pub document_id: i32,
+    pub query: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchDocumentTextSuccessPayload {
+    pub matches: Vec<TextMatch>,
+}
+
 #[derive(Clone, Debug)]
 pub enum Msg {
     AllDocumentsRequest(AllDocumentsRequestPayload),
     AllDocumentsResponse(Result<AllDocumentsSuccessPayload, ErrorPayload>),
+    SearchDocumentsRequest(SearchDocumentsRequestPayload),
+    SearchDocumentsResponse(Result<SearchDocumentsSuccessPayload, ErrorPayload>),
     DocumentRequest(DocumentRequestPayload),
     DocumentResponse(Result<DocumentSuccessPayload, ErrorPayload>),
     PageRequest(PageRequestPayload),
     PageResponse(Result<PageSuccessPayload, ErrorPayload>),
     CreateAnchorRequest(CreateAnchorRequestPayload),
     CreateAnchorResponse(Result<CreateAnchorSuccessPayload, ErrorPayload>),
+    UpdateAnchorRequest(UpdateAnchorRequestPayload),
+    UpdateAnchorResponse(Result<UpdateAnchorSuccessPayload, ErrorPayload>),
     DeleteAnchorRequest(DeleteAnchorRequestPayload),
     DeleteAnchorResponse(Result<DeleteAnchorSuccessPayload, ErrorPayload>),
     CreateUserAnchorRequest(CreateAnchorRequestPayload),
     CreateUserAnchorResponse(Result<CreateUserAnchorSuccessPayload, ErrorPayload>),
+    UpdateUserAnchorRequest(UpdateAnchorRequestPayload),
+    UpdateUserAnchorResponse(Result<UpdateUserAnchorSuccessPayload, ErrorPayload>),
     DeleteUserAnchorRequest(DeleteAnchorRequestPayload),
     DeleteUserAnchorResponse(Result<DeleteAnchorSuccessPayload, ErrorPayload>),
+    PromoteUserAnchorRequest(PromoteUserAnchorRequestPayload),
+    PromoteUserAnchorResponse(Result<CreateAnchorSuccessPayload, ErrorPayload>),
     JumpToAnchorResponse(Result<JumpToAnchorSuccessPayload, ErrorPayload>),
+    AnchorChanged(AnchorChangedPayload),
+    // Changes to the anchors of the document may have been missed, so its pages are reloaded
+    AnchorChangesMissed(i32),
+    SaveProgressRequest(SaveProgressRequestPayload),
+    SaveProgressResponse(Result<SaveProgressSuccessPayload, ErrorPayload>),
+    CreateUserNoteRequest(CreateUserNoteRequestPayload),
+    CreateUserNoteResponse(Result<CreateUserNoteSuccessPayload, ErrorPayload>),
+    DeleteUserNoteRequest(DeleteUserNoteRequestPayload),
+    DeleteUserNoteResponse(Result<DeleteUserNoteSuccessPayload, ErrorPayload>),
+    SearchDocumentTextRequest(SearchDocumentTextRequestPayload),
+    SearchDocumentTextResponse(Result<SearchDocumentTextSuccessPayload, ErrorPayload>),
 }
@@ -10,9 +10,26 @@ pub struct LoginRequestPayload {
 #[derive(Clone, Debug)]
 pub struct LoginSuccessPayload {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i32,
     pub user: User,
 }
 
+#[derive(Clone, Debug)]
+pub struct RefreshTokenRequestPayload {
+    pub refresh_token: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct LogoutRequestPayload {
+    pub refresh_token: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct LogoutSuccessPayload {
+    pub success: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct RegisterRequestPayload {
     pub username: String,
@@ -28,6 +45,9 @@ pub struct RegisterSuccessPayload {
 pub enum Msg {
     LoginRequest(LoginRequestPayload),
     LoginResponse(Result<LoginSuccessPayload, ErrorPayload>),
+    RefreshTokenRequest(RefreshTokenRequestPayload),
+    RefreshTokenResponse(Result<LoginSuccessPayload, ErrorPayload>),
+    LogoutRequest(LogoutRequestPayload),
     RegisterRequest(RegisterRequestPayload),
     RegisterResponse(Result<RegisterSuccessPayload, ErrorPayload>),
     Logout,
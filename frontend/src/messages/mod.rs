@@ -9,6 +9,18 @@ pub use routing::*;
 #[derive(Clone, Debug)]
 pub struct ErrorPayload {
     pub content: String,
+    // `code` extension of the first GraphQL error, if any
+    pub code: Option<String>,
+}
+
+impl ErrorPayload {
+    /// Whether the request failed because the session's token was rejected
+    pub fn is_unauthenticated(&self) -> bool {
+        match self.code.as_deref() {
+            Some("TOKEN_EXPIRED") | Some("INVALID_TOKEN") | Some("UNKNOWN_USER") => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -18,3 +30,34 @@ pub enum Msg {
     Routing(routing::Msg),
     Ui(ui::Msg),
 }
+
+impl Msg {
+    /// Error carried by a failed response message
+    pub fn error(&self) -> Option<&ErrorPayload> {
+        use application::Msg as A;
+        use authentication::Msg as Auth;
+
+        match self {
+            Msg::Application(A::AllDocumentsResponse(Err(e)))
+            | Msg::Application(A::SearchDocumentsResponse(Err(e)))
+            | Msg::Application(A::DocumentResponse(Err(e)))
+            | Msg::Application(A::PageResponse(Err(e)))
+            | Msg::Application(A::CreateAnchorResponse(Err(e)))
+            | Msg::Application(A::UpdateAnchorResponse(Err(e)))
+            | Msg::Application(A::DeleteAnchorResponse(Err(e)))
+            | Msg::Application(A::CreateUserAnchorResponse(Err(e)))
+            | Msg::Application(A::UpdateUserAnchorResponse(Err(e)))
+            | Msg::Application(A::DeleteUserAnchorResponse(Err(e)))
+            | Msg::Application(A::PromoteUserAnchorResponse(Err(e)))
+            | Msg::Application(A::JumpToAnchorResponse(Err(e)))
+            | Msg::Application(A::SaveProgressResponse(Err(e)))
+            | Msg::Application(A::CreateUserNoteResponse(Err(e)))
+            | Msg::Application(A::DeleteUserNoteResponse(Err(e)))
+            | Msg::Application(A::SearchDocumentTextResponse(Err(e)))
+            | Msg::Authentication(Auth::LoginResponse(Err(e)))
+            | Msg::Authentication(Auth::RefreshTokenResponse(Err(e)))
+            | Msg::Authentication(Auth::RegisterResponse(Err(e))) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -40,4 +40,15 @@ pub enum Msg {
     ToggleMode(CursorMode),
     TogglePlayback,
     PageClick(PageClickPayload),
+    ProgressTick,
+    ResumeProgress,
+    DismissProgress,
+    NoteInputChanged(String),
+    AddNote,
+    FilterNotes(Option<i32>),
+    SelectNote(i32),
+    DeleteNote(i32),
+    TextSearchInputChanged(String),
+    SearchText,
+    SelectTextMatch(usize),
 }
@@ -0,0 +1,9 @@
+use crate::state::ui::courses_screen::DocumentSort;
+
+#[derive(Clone, Debug)]
+pub enum Msg {
+    SearchInputChanged(String),
+    LanguageInputChanged(String),
+    LevelInputChanged(String),
+    SortChanged(DocumentSort),
+}
@@ -1,10 +1,12 @@
 pub mod login;
 pub mod register;
 pub mod course;
+pub mod courses;
 
 #[derive(Clone, Debug)]
 pub enum Msg {
     Login(login::Msg),
     Register(register::Msg),
     Course(course::Msg),
+    Courses(courses::Msg),
 }
@@ -4,6 +4,7 @@ use crate::messages::{authentication, Msg};
 pub struct Model {
     pub active_user: Option<i32>,
     pub token: Option<String>,
+    pub refresh_token: Option<String>,
 }
 
 impl Model {
@@ -12,10 +13,13 @@ impl Model {
             Msg::Authentication(authentication::Msg::Logout) => {
                 self.active_user = None;
                 self.token = None;
+                self.refresh_token = None;
             }
-            Msg::Authentication(authentication::Msg::LoginResponse(Ok(payload))) => {
+            Msg::Authentication(authentication::Msg::LoginResponse(Ok(payload)))
+            | Msg::Authentication(authentication::Msg::RefreshTokenResponse(Ok(payload))) => {
                 self.active_user = Some(payload.user.id);
                 self.token = Some(payload.token.clone());
+                self.refresh_token = Some(payload.refresh_token.clone());
             }
             _ => {}
         }
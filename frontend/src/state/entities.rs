@@ -10,13 +10,16 @@ pub struct Document {
     pub title: std::string::String,
     pub created_at: DateTime<FixedOffset>,
     pub updated_at: DateTime<FixedOffset>,
+    pub language: std::string::String,
+    pub level: std::string::String,
+    pub description: std::string::String,
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Page {
     pub id: i32,
     pub page_number: i32,
-    pub image_path: std::string::String,
+    pub image_url: std::string::String,
     pub aspect_ratio: f32,
     pub height: f32,
     pub document_id: i32,
@@ -27,7 +30,7 @@ pub struct Track {
     pub id: i32,
     pub track_number: i32,
     pub title: std::string::String,
-    pub audio_path: std::string::String,
+    pub audio_url: std::string::String,
     pub document_id: i32,
 }
 
@@ -66,6 +69,48 @@ pub struct UserAnchor {
     pub owner: i32,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextRegion {
+    pub id: i32,
+    pub content: std::string::String,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub width: f32,
+    pub height: f32,
+    pub page_id: i32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextMatch {
+    pub page_id: i32,
+    pub page_number: i32,
+    pub content: std::string::String,
+    pub position_top: f32,
+    pub position_left: f32,
+    pub anchor_id: Option<i32>,
+    pub track_id: Option<i32>,
+    pub track_time: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Progress {
+    pub document_id: i32,
+    pub track_id: i32,
+    pub track_time: f32,
+    pub page_id: i32,
+    pub completion: f32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct UserNote {
+    pub id: i32,
+    pub document_id: i32,
+    pub content: String,
+    pub page_id: Option<i32>,
+    pub track_id: Option<i32>,
+    pub track_time: f32,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct User {
     pub id: i32,
@@ -79,6 +124,7 @@ pub struct Model {
     pub bookmarks_by_id: HashMap<i32, Bookmark>,
     pub anchors_by_id: HashMap<i32, Anchor>,
     pub user_anchors_by_id: HashMap<i32, UserAnchor>,
+    pub user_notes_by_id: HashMap<i32, UserNote>,
     pub pages_by_id: HashMap<i32, Page>,
     pub tracks_by_id: HashMap<i32, Track>,
     pub users_by_id: HashMap<i32, User>,
@@ -87,12 +133,16 @@ pub struct Model {
     pub document_pages: HashMap<i32, Vec<i32>>,
     pub page_anchors: HashMap<i32, HashSet<i32>>,
     pub page_user_anchors: HashMap<i32, HashSet<i32>>,
+    pub page_text_regions: HashMap<i32, Vec<TextRegion>>,
+    pub document_progress: HashMap<i32, Progress>,
+    pub document_user_notes: HashMap<i32, Vec<i32>>,
 }
 
 impl Model {
     pub fn update(&mut self, message: &Msg) {
         match message {
-            Msg::Authentication(authentication::Msg::LoginResponse(Ok(x))) => {
+            Msg::Authentication(authentication::Msg::LoginResponse(Ok(x)))
+            | Msg::Authentication(authentication::Msg::RefreshTokenResponse(Ok(x))) => {
                 self.users_by_id.insert(x.user.id, x.user.clone());
             }
             Msg::Authentication(authentication::Msg::RegisterResponse(Ok(x))) => {
@@ -103,6 +153,11 @@ impl Model {
                     self.documents_by_id.insert(d.id, d.clone());
                 });
             }
+            Msg::Application(application::Msg::SearchDocumentsResponse(Ok(x))) => {
+                x.documents.clone().into_iter().for_each(|d| {
+                    self.documents_by_id.insert(d.id, d.clone());
+                });
+            }
             Msg::Application(application::Msg::DocumentResponse(Ok(x))) => {
                 self.documents_by_id
                     .insert(x.document.id, x.document.clone());
@@ -140,6 +195,44 @@ impl Model {
                         .push(b.id);
                     self.bookmarks_by_id.insert(b.id, b);
                 }
+
+                match &x.progress {
+                    Some(progress) => {
+                        self.document_progress
+                            .insert(x.document.id, progress.clone());
+                    }
+                    None => {
+                        self.document_progress.remove(&x.document.id);
+                    }
+                }
+
+                self.document_user_notes.insert(x.document.id, vec![]);
+                for n in x.user_notes.clone() {
+                    self.document_user_notes
+                        .entry(x.document.id)
+                        .or_insert(vec![])
+                        .push(n.id);
+                    self.user_notes_by_id.insert(n.id, n);
+                }
+            }
+            Msg::Application(application::Msg::SaveProgressResponse(Ok(x))) => {
+                self.document_progress
+                    .insert(x.progress.document_id, x.progress.clone());
+            }
+            Msg::Application(application::Msg::CreateUserNoteResponse(Ok(x))) => {
+                self.document_user_notes
+                    .entry(x.user_note.document_id)
+                    .or_insert(vec![])
+                    .push(x.user_note.id);
+                self.user_notes_by_id
+                    .insert(x.user_note.id, x.user_note.clone());
+            }
+            Msg::Application(application::Msg::DeleteUserNoteRequest(x)) => {
+                if let Some(note) = self.user_notes_by_id.remove(&x.user_note_id) {
+                    if let Some(notes) = self.document_user_notes.get_mut(&note.document_id) {
+                        notes.retain(|id| *id != x.user_note_id);
+                    }
+                }
             }
             Msg::Application(application::Msg::PageRequest(payload)) => {
                 self.page_anchors.insert(payload.page_id, HashSet::new());
@@ -162,6 +255,8 @@ impl Model {
                         .insert(user_anchor.id);
                     self.user_anchors_by_id.insert(user_anchor.id, user_anchor);
                 }
+                self.page_text_regions
+                    .insert(x.page.id, x.text_regions.clone());
             }
             Msg::Application(application::Msg::CreateAnchorResponse(Ok(x))) => {
                 self.page_anchors
@@ -211,6 +306,31 @@ impl Model {
                         .insert(x.user_anchor.id, x.user_anchor.clone());
                 }
             }
+            Msg::Application(application::Msg::UpdateAnchorRequest(payload)) => {
+                if let Some(anchor) = self.anchors_by_id.get_mut(&payload.anchor_id) {
+                    anchor.title = payload.title.clone();
+                    anchor.track_time = payload.track_time;
+                    anchor.position_top = payload.position_top;
+                    anchor.position_left = payload.position_left;
+                    anchor.track_id = payload.track_id;
+                }
+            }
+            Msg::Application(application::Msg::UpdateAnchorResponse(Ok(x))) => {
+                self.anchors_by_id.insert(x.anchor.id, x.anchor.clone());
+            }
+            Msg::Application(application::Msg::UpdateUserAnchorRequest(payload)) => {
+                if let Some(user_anchor) = self.user_anchors_by_id.get_mut(&payload.anchor_id) {
+                    user_anchor.title = payload.title.clone();
+                    user_anchor.track_time = payload.track_time;
+                    user_anchor.position_top = payload.position_top;
+                    user_anchor.position_left = payload.position_left;
+                    user_anchor.track_id = payload.track_id;
+                }
+            }
+            Msg::Application(application::Msg::UpdateUserAnchorResponse(Ok(x))) => {
+                self.user_anchors_by_id
+                    .insert(x.user_anchor.id, x.user_anchor.clone());
+            }
             Msg::Application(application::Msg::DeleteAnchorRequest(x)) => {
                 if let Some(set) = self
                     .page_anchors
@@ -229,6 +349,45 @@ impl Model {
                 }
                 self.user_anchors_by_id.remove(&x.anchor_id);
             }
+            Msg::Application(application::Msg::PromoteUserAnchorRequest(x)) => {
+                if let Some(set) = self.page_user_anchors.get_mut(
+                    &self
+                        .user_anchors_by_id
+                        .get(&x.user_anchor_id)
+                        .unwrap()
+                        .page_id,
+                ) {
+                    set.remove(&x.user_anchor_id);
+                }
+                self.user_anchors_by_id.remove(&x.user_anchor_id);
+            }
+            Msg::Application(application::Msg::PromoteUserAnchorResponse(Ok(x))) => {
+                self.page_anchors
+                    .entry(x.anchor.page_id)
+                    .or_insert(HashSet::new())
+                    .insert(x.anchor.id);
+                self.anchors_by_id.insert(x.anchor.id, x.anchor.clone());
+            }
+            Msg::Application(application::Msg::AnchorChanged(x)) => {
+                // The anchor may have moved to another page
+                if let Some(previous) = self.anchors_by_id.get(&x.anchor.id) {
+                    if let Some(set) = self.page_anchors.get_mut(&previous.page_id) {
+                        set.remove(&x.anchor.id);
+                    }
+                }
+                match x.kind {
+                    application::AnchorChangeKind::Upserted => {
+                        // Pages that haven't been loaded yet will fetch the anchor themselves
+                        if let Some(set) = self.page_anchors.get_mut(&x.anchor.page_id) {
+                            set.insert(x.anchor.id);
+                        }
+                        self.anchors_by_id.insert(x.anchor.id, x.anchor.clone());
+                    }
+                    application::AnchorChangeKind::Deleted => {
+                        self.anchors_by_id.remove(&x.anchor.id);
+                    }
+                }
+            }
             Msg::Application(application::Msg::JumpToAnchorResponse(Ok(payload))) => {
                 self.anchors_by_id
                     .insert(payload.anchor.id, payload.anchor.clone());
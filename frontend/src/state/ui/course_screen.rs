@@ -1,4 +1,7 @@
-use crate::messages::{application, ui, ui::course, Msg};
+use crate::{
+    messages::{application, ui, ui::course, Msg},
+    state::entities::TextMatch,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CursorMode {
@@ -7,6 +10,7 @@ pub enum CursorMode {
     Delete,
     Add,
     Upgrade,
+    Rename,
 }
 
 pub struct Model {
@@ -15,6 +19,16 @@ pub struct Model {
     pub mode: CursorMode,
     pub drag_x: i32,
     pub drag_y: i32,
+    // Whether the user should be asked to resume from their saved progress
+    pub resume_prompt: bool,
+    pub note_input_value: String,
+    // Page that the notes panel is limited to, if any
+    pub note_page_filter: Option<i32>,
+    pub text_search_value: String,
+    // Document that the last text search ran against
+    pub text_search_document: Option<i32>,
+    // Results of the last search through the document's text
+    pub text_matches: Vec<TextMatch>,
 }
 
 impl Model {
@@ -25,16 +39,36 @@ impl Model {
             mode: CursorMode::Default,
             drag_x: 0,
             drag_y: 0,
+            resume_prompt: false,
+            note_input_value: "".to_owned(),
+            note_page_filter: None,
+            text_search_value: "".to_owned(),
+            text_search_document: None,
+            text_matches: vec![],
         }
     }
 
     pub fn update(&mut self, message: &Msg) {
         match message {
-            Msg::Application(application::Msg::DocumentRequest(_)) => {
+            Msg::Application(application::Msg::DocumentRequest(payload)) => {
                 self.loading = true;
+                self.note_page_filter = None;
+                // Jumping to a match reloads the same document, which should keep the results
+                if self.text_search_document != Some(payload.document_id) {
+                    self.text_search_value = "".to_owned();
+                    self.text_search_document = None;
+                    self.text_matches = vec![];
+                }
             }
             Msg::Application(application::Msg::DocumentResponse(resp)) => {
                 self.loading = false;
+                self.resume_prompt = resp.as_ref().map_or(false, |x| x.progress.is_some());
+            }
+            Msg::Application(application::Msg::JumpToAnchorResponse(_))
+            | Msg::Ui(ui::Msg::Course(course::Msg::SelectTrack(_)))
+            | Msg::Ui(ui::Msg::Course(course::Msg::ResumeProgress))
+            | Msg::Ui(ui::Msg::Course(course::Msg::DismissProgress)) => {
+                self.resume_prompt = false;
             }
             Msg::Ui(ui::Msg::Course(course::Msg::UpdateRelativeScroll(relative_scroll))) => {
                 self.relative_scroll = *relative_scroll;
@@ -50,6 +84,24 @@ impl Model {
                 self.drag_x = payload.x;
                 self.drag_y = payload.y;
             }
+            Msg::Ui(ui::Msg::Course(course::Msg::NoteInputChanged(value))) => {
+                self.note_input_value = value.clone();
+            }
+            Msg::Application(application::Msg::CreateUserNoteRequest(_)) => {
+                self.note_input_value = "".to_owned();
+            }
+            Msg::Ui(ui::Msg::Course(course::Msg::FilterNotes(page_id))) => {
+                self.note_page_filter = *page_id;
+            }
+            Msg::Ui(ui::Msg::Course(course::Msg::TextSearchInputChanged(value))) => {
+                self.text_search_value = value.clone();
+            }
+            Msg::Application(application::Msg::SearchDocumentTextRequest(payload)) => {
+                self.text_search_document = Some(payload.document_id);
+            }
+            Msg::Application(application::Msg::SearchDocumentTextResponse(Ok(x))) => {
+                self.text_matches = x.matches.clone();
+            }
             _ => {}
         }
     }
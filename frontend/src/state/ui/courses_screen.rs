@@ -2,11 +2,28 @@ use std::collections::HashMap;
 
 use iced::button;
 
-use crate::messages::{application, ui, Msg};
+use crate::messages::{
+    application::{self, SearchDocumentsRequestPayload},
+    ui::{self, courses},
+    Msg,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentSort {
+    Relevance,
+    RecentlyUpdated,
+    Title,
+}
 
 pub struct Model {
     pub loading: bool,
     pub btn_states: HashMap<i32, button::State>,
+    pub search_input_value: String,
+    pub language_input_value: String,
+    pub level_input_value: String,
+    pub sort: DocumentSort,
+    pub results: Vec<i32>,
+    pub total: i32,
 }
 
 impl Model {
@@ -14,6 +31,21 @@ impl Model {
         Self {
             loading: false,
             btn_states: HashMap::new(),
+            search_input_value: String::new(),
+            language_input_value: String::new(),
+            level_input_value: String::new(),
+            sort: DocumentSort::Relevance,
+            results: vec![],
+            total: 0,
+        }
+    }
+
+    pub fn search_payload(&self) -> SearchDocumentsRequestPayload {
+        SearchDocumentsRequestPayload {
+            query: self.search_input_value.clone(),
+            language: self.language_input_value.clone(),
+            level: self.level_input_value.clone(),
+            sort: self.sort,
         }
     }
 
@@ -32,6 +64,34 @@ impl Model {
                     }
                 }
             }
+            Msg::Application(application::Msg::SearchDocumentsRequest(_)) => {
+                self.loading = true;
+            }
+            Msg::Application(application::Msg::SearchDocumentsResponse(resp)) => {
+                self.loading = false;
+
+                if let Ok(result) = resp {
+                    self.results = result.documents.iter().map(|d| d.id).collect();
+                    self.total = result.total;
+                    for d in &result.documents {
+                        self.btn_states
+                            .entry(d.id)
+                            .or_insert_with(button::State::default);
+                    }
+                }
+            }
+            Msg::Ui(ui::Msg::Courses(courses::Msg::SearchInputChanged(val))) => {
+                self.search_input_value = val.to_owned();
+            }
+            Msg::Ui(ui::Msg::Courses(courses::Msg::LanguageInputChanged(val))) => {
+                self.language_input_value = val.to_owned();
+            }
+            Msg::Ui(ui::Msg::Courses(courses::Msg::LevelInputChanged(val))) => {
+                self.level_input_value = val.to_owned();
+            }
+            Msg::Ui(ui::Msg::Courses(courses::Msg::SortChanged(sort))) => {
+                self.sort = *sort;
+            }
             _ => {}
         }
     }
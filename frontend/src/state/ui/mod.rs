@@ -1,7 +1,7 @@
 use crate::messages::Msg;
 
 pub mod course_screen;
-mod courses_screen;
+pub mod courses_screen;
 mod login_screen;
 mod register_screen;
 
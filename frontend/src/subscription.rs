@@ -1,10 +1,18 @@
+use std::any::TypeId;
 use std::hash::Hash;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
-use iced_futures::futures::{channel::mpsc, stream::LocalBoxStream};
+use iced_futures::futures::{
+    channel::mpsc,
+    future::ready,
+    stream::{self, LocalBoxStream},
+    Stream, StreamExt,
+};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
-use crate::state::Route;
+use crate::{commands::operations, messages::application::AnchorChangedPayload, state::Route};
 
 pub fn route_change(route: Route) -> iced_web::Subscription<Route> {
     iced_web::Subscription::from_recipe(RouteChange { route })
@@ -46,3 +54,134 @@ where
         Box::pin(receiver)
     }
 }
+
+pub fn anchor_changes(document_id: i32) -> iced_web::Subscription<AnchorChangesEvent> {
+    iced_web::Subscription::from_recipe(AnchorChanges { document_id })
+}
+
+#[derive(Clone, Debug)]
+pub enum AnchorChangesEvent {
+    Changed(AnchorChangedPayload),
+    // The subscription of the document was ended and reopened, so changes made in between may
+    // have been missed
+    Missed(i32),
+}
+
+pub struct AnchorChanges {
+    document_id: i32,
+}
+
+impl<H, I> iced_web::subscription::Recipe<H, I> for AnchorChanges
+where
+    H: std::hash::Hasher,
+{
+    type Output = AnchorChangesEvent;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+        self.document_id.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: LocalBoxStream<'static, I>,
+    ) -> LocalBoxStream<'static, Self::Output> {
+        let document_id = self.document_id;
+        // The server ends the subscription when it falls behind on the document's changes, so
+        // it's reopened in its place each time it ends
+        Box::pin(stream::iter(0..).flat_map(move |attempt: u32| {
+            let missed = if attempt > 0 {
+                Some(AnchorChangesEvent::Missed(document_id))
+            } else {
+                None
+            };
+            let changes: LocalBoxStream<'static, AnchorChangesEvent> =
+                match operations::page_anchors_changed(document_id) {
+                    // Failed updates are skipped, the anchors are refreshed when the page is
+                    // reloaded
+                    Ok(changes) => {
+                        Box::pin(changes.filter_map(|change| {
+                            ready(change.ok().map(AnchorChangesEvent::Changed))
+                        }))
+                    }
+                    // Not retried, since the socket can't be opened at all
+                    Err(_) => Box::pin(stream::pending()),
+                };
+            stream::iter(missed).chain(changes)
+        }))
+    }
+}
+
+/// Milliseconds between saves of the listening progress
+const PROGRESS_INTERVAL_MS: i32 = 15_000;
+
+pub fn progress_ticks(document_id: i32) -> iced_web::Subscription<()> {
+    iced_web::Subscription::from_recipe(ProgressTicks { document_id })
+}
+
+pub struct ProgressTicks {
+    document_id: i32,
+}
+
+/// Stream of interval ticks, cleared when the stream is dropped
+struct Interval {
+    handle: i32,
+    receiver: mpsc::UnboundedReceiver<()>,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for Interval {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            window.clear_interval_with_handle(self.handle);
+        }
+    }
+}
+
+impl<H, I> iced_web::subscription::Recipe<H, I> for ProgressTicks
+where
+    H: std::hash::Hasher,
+{
+    type Output = ();
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+        self.document_id.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: LocalBoxStream<'static, I>,
+    ) -> LocalBoxStream<'static, Self::Output> {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let closure = Closure::wrap(Box::new(move || {
+            let _ = sender.unbounded_send(());
+        }) as Box<dyn FnMut()>);
+        let handle = web_sys::window().and_then(|window| {
+            window
+                .set_interval_with_callback_and_timeout_and_arguments_0(
+                    closure.as_ref().unchecked_ref(),
+                    PROGRESS_INTERVAL_MS,
+                )
+                .ok()
+        });
+
+        match handle {
+            Some(handle) => Box::pin(Interval {
+                handle,
+                receiver,
+                _closure: closure,
+            }),
+            None => Box::pin(stream::empty()),
+        }
+    }
+}
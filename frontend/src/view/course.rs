@@ -59,6 +59,11 @@ pub fn render<'b>(
                             ui::course::Msg::ToggleMode(CursorMode::Upgrade),
                         )));
                     },
+                    "T" => {
+                        hotkey_bus.publish(Msg::Ui(ui::Msg::Course(
+                            ui::course::Msg::ToggleMode(CursorMode::Rename),
+                        )));
+                    },
                     "P" => {
                         hotkey_bus.publish(Msg::Ui(ui::Msg::Course(
                             ui::course::Msg::TogglePlayback,
@@ -81,6 +86,7 @@ pub fn render<'b>(
                             CursorMode::Delete => "no-drop",
                             CursorMode::Move => "grab",
                             CursorMode::Upgrade => "copy",
+                            CursorMode::Rename => "text",
                             _ => "auto"
                         })
                             .as_str(),
@@ -143,10 +149,27 @@ pub fn render<'b>(
                                             None => return,
                                             Some(el) => el,
                                         };
+                                    // Selecting text in the text layer shouldn't also place an anchor
+                                    if web_sys::window()
+                                        .and_then(|w| w.get_selection().ok().flatten())
+                                        .map(|s| !s.is_collapsed())
+                                        .unwrap_or(false)
+                                    {
+                                        return;
+                                    }
+                                    // Text regions cover parts of the image, so clicks on them are measured against the page
+                                    let (el, offset_x, offset_y) = if el.class_name() == "page__text-region" {
+                                        match el.parent_element().and_then(|p| p.dyn_into::<web_sys::HtmlElement>().ok()) {
+                                            None => return,
+                                            Some(page_el) => (page_el, el.offset_left() + ev.offset_x(), el.offset_top() + ev.offset_y()),
+                                        }
+                                    } else {
+                                        (el, ev.offset_x(), ev.offset_y())
+                                    };
                                     anchor_creation_bus.publish(Msg::Ui(ui::Msg::Course(ui::course::Msg::PageClick(ui::course::PageClickPayload {
                                         page_id: p_id,
-                                        position_top: (ev.offset_y() as f32) / (el.client_height() as f32) * 100.0,
-                                        position_left: (ev.offset_x() as f32) / (el.client_width() as f32) * 100.0
+                                        position_top: (offset_y as f32) / (el.client_height() as f32) * 100.0,
+                                        position_left: (offset_x as f32) / (el.client_width() as f32) * 100.0
                                     }))));
                                 })
                                 .children(
@@ -400,10 +423,7 @@ pub fn render<'b>(
                                             "src",
                                             if (state.ui.course_screen.relative_scroll - page.height).abs() < 3.0 * page.aspect_ratio {
                                             bumpalo::collections::String::from_str_in(
-                                                    format!(
-                                                        "https://synchrotron.nsenger.com/{}",
-                                                        page.image_path
-                                                    ).as_str(),
+                                                    page.image_url.as_str(),
                                                     bump,
                                                 )
                                                 .into_bump_str()
@@ -412,7 +432,7 @@ pub fn render<'b>(
                                             }
                                         )
                                         .finish()
-                                        ]),
+                                        ]).chain(text_layer(bump, state, p_id)),
                                         bump
                                     )
                                 )
@@ -461,7 +481,7 @@ pub fn render<'b>(
                                     .attr("id", "audio")
                                     .attr("controls", "true")
                                     .attr("class", "synchrotron__audio")
-                                    .attr("src", bumpalo::collections::String::from_str_in(format!("https://synchrotron.nsenger.com/{}", state.entities.tracks_by_id.get(&state.entities.document_tracks.get(&document_id).unwrap().first().unwrap()).unwrap().audio_path).as_str(), bump).into_bump_str())
+                                    .attr("src", bumpalo::collections::String::from_str_in(state.entities.tracks_by_id.get(&state.entities.document_tracks.get(&document_id).unwrap().first().unwrap()).unwrap().audio_url.as_str(), bump).into_bump_str())
                                     .finish(),
                                 select(bump)
                                     .attr("class", "synchrotron__dropdown")
@@ -494,7 +514,11 @@ pub fn render<'b>(
                                         )
                                     )
                                     .finish()
-                            ],
+                            ]
+                            .into_iter()
+                            .chain(resume_prompt(bump, state, bus, document_id))
+                            .chain(notes_panel(bump, state, bus, document_id))
+                            .chain(search_panel(bump, state, bus, document_id)),
                             bump
                         ),
                     )
@@ -504,3 +528,345 @@ pub fn render<'b>(
         ))
         .finish()
 }
+
+/// Asks whether to continue from the saved progress, if there is any to resume from
+fn resume_prompt<'b>(
+    bump: &'b bumpalo::Bump,
+    state: &Model,
+    bus: &Bus<Msg>,
+    document_id: i32,
+) -> Option<dodrio::Node<'b>> {
+    use dodrio::builder::*;
+
+    if !state.ui.course_screen.resume_prompt {
+        return None;
+    }
+    let progress = state.entities.document_progress.get(&document_id)?;
+    let track = state.entities.tracks_by_id.get(&progress.track_id)?;
+
+    let resume_bus = bus.clone();
+    let dismiss_bus = bus.clone();
+    let seconds = progress.track_time as i32;
+
+    Some(
+        div(bump)
+            .attr("class", "synchrotron__resume")
+            .children(bumpalo::collections::Vec::from_iter_in(
+                vec![
+                    span(bump)
+                        .child(text(
+                            bumpalo::collections::String::from_str_in(
+                                format!(
+                                    "Resume {} at {}:{:02}? ({:.0}% complete)",
+                                    track.title,
+                                    seconds / 60,
+                                    seconds % 60,
+                                    progress.completion
+                                )
+                                .as_str(),
+                                bump,
+                            )
+                            .into_bump_str(),
+                        ))
+                        .finish(),
+                    button(bump)
+                        .child(text("Resume"))
+                        .on("click", move |_root, _vdom, _event| {
+                            resume_bus
+                                .publish(Msg::Ui(ui::Msg::Course(ui::course::Msg::ResumeProgress)));
+                        })
+                        .finish(),
+                    button(bump)
+                        .child(text("Dismiss"))
+                        .on("click", move |_root, _vdom, _event| {
+                            dismiss_bus.publish(Msg::Ui(ui::Msg::Course(
+                                ui::course::Msg::DismissProgress,
+                            )));
+                        })
+                        .finish(),
+                ],
+                bump,
+            ))
+            .finish(),
+    )
+}
+
+/// Renders the page's text regions as transparent, selectable text over the page image
+fn text_layer<'b>(bump: &'b bumpalo::Bump, state: &Model, page_id: i32) -> Vec<dodrio::Node<'b>> {
+    use dodrio::builder::*;
+
+    state
+        .entities
+        .page_text_regions
+        .get(&page_id)
+        .map(|regions| regions.as_slice())
+        .unwrap_or(&[])
+        .iter()
+        .map(|region| {
+            span(bump)
+                .attr("class", "page__text-region")
+                .attr(
+                    "style",
+                    bumpalo::collections::String::from_str_in(
+                        format!(
+                            "top: {}%; left: {}%; width: {}%; height: {}%;",
+                            region.position_top, region.position_left, region.width, region.height
+                        )
+                        .as_str(),
+                        bump,
+                    )
+                    .into_bump_str(),
+                )
+                .child(text(
+                    bumpalo::collections::String::from_str_in(region.content.as_str(), bump)
+                        .into_bump_str(),
+                ))
+                .finish()
+        })
+        .collect()
+}
+
+/// Lists the user's private notes for the document with a form for adding more
+fn notes_panel<'b>(
+    bump: &'b bumpalo::Bump,
+    state: &Model,
+    bus: &Bus<Msg>,
+    document_id: i32,
+) -> Option<dodrio::Node<'b>> {
+    use dodrio::builder::*;
+
+    // Notes belong to a user, so there is nothing to show when logged out
+    state.authentication.token.as_ref()?;
+
+    let filter_bus = bus.clone();
+    let input_bus = bus.clone();
+    let add_bus = bus.clone();
+    let page_filter = state.ui.course_screen.note_page_filter;
+    let pages = state.entities.document_pages.get(&document_id)?;
+    let notes = state
+        .entities
+        .document_user_notes
+        .get(&document_id)
+        .map(|ids| ids.as_slice())
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|id| state.entities.user_notes_by_id.get(id))
+        .filter(|note| page_filter.is_none() || note.page_id == page_filter);
+
+    Some(
+        div(bump)
+            .attr("class", "synchrotron__notes")
+            .children(bumpalo::collections::Vec::from_iter_in(
+                vec![
+                    select(bump)
+                        .attr("class", "synchrotron__dropdown")
+                        .on("input", move |_root, _vdom, event| {
+                            let el = match event
+                                .target()
+                                .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+                            {
+                                None => return,
+                                Some(el) => el,
+                            };
+
+                            filter_bus.publish(Msg::Ui(ui::Msg::Course(
+                                ui::course::Msg::FilterNotes(el.value().parse::<i32>().ok()),
+                            )));
+                        })
+                        .children(bumpalo::collections::Vec::from_iter_in(
+                            vec![option(bump)
+                                .attr("value", "")
+                                .child(text("All pages"))
+                                .finish()]
+                            .into_iter()
+                            .chain(pages.iter().map(|page_id| {
+                                let page = state.entities.pages_by_id.get(page_id).unwrap();
+                                let mut page_option = option(bump).attr(
+                                    "value",
+                                    bumpalo::collections::String::from_str_in(
+                                        format!("{}", page_id).as_str(),
+                                        bump,
+                                    )
+                                    .into_bump_str(),
+                                );
+                                if page_filter == Some(*page_id) {
+                                    page_option = page_option.attr("selected", "true");
+                                }
+                                page_option
+                                    .child(text(
+                                        bumpalo::collections::String::from_str_in(
+                                            format!("Page {}", page.page_number).as_str(),
+                                            bump,
+                                        )
+                                        .into_bump_str(),
+                                    ))
+                                    .finish()
+                            })),
+                            bump,
+                        ))
+                        .finish(),
+                    ul(bump)
+                        .attr("class", "synchrotron__note-list")
+                        .children(bumpalo::collections::Vec::from_iter_in(
+                            notes.map(|note| {
+                                let select_bus = bus.clone();
+                                let delete_bus = bus.clone();
+                                let note_id = note.id;
+                                li(bump)
+                                    .attr("class", "synchrotron__note")
+                                    .children(bumpalo::collections::Vec::from_iter_in(
+                                        vec![
+                                            span(bump)
+                                                .on("click", move |_root, _vdom, _event| {
+                                                    select_bus.publish(Msg::Ui(ui::Msg::Course(
+                                                        ui::course::Msg::SelectNote(note_id),
+                                                    )));
+                                                })
+                                                .child(text(
+                                                    bumpalo::collections::String::from_str_in(
+                                                        note.content.as_str(),
+                                                        bump,
+                                                    )
+                                                    .into_bump_str(),
+                                                ))
+                                                .finish(),
+                                            button(bump)
+                                                .child(text("Delete"))
+                                                .on("click", move |_root, _vdom, _event| {
+                                                    delete_bus.publish(Msg::Ui(ui::Msg::Course(
+                                                        ui::course::Msg::DeleteNote(note_id),
+                                                    )));
+                                                })
+                                                .finish(),
+                                        ],
+                                        bump,
+                                    ))
+                                    .finish()
+                            }),
+                            bump,
+                        ))
+                        .finish(),
+                    input(bump)
+                        .attr(
+                            "value",
+                            bumpalo::collections::String::from_str_in(
+                                state.ui.course_screen.note_input_value.as_str(),
+                                bump,
+                            )
+                            .into_bump_str(),
+                        )
+                        .attr("placeholder", "Add a note")
+                        .on("change", move |_root, _vdom, event| {
+                            let text_input = match event
+                                .target()
+                                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+                            {
+                                None => return,
+                                Some(text_input) => text_input,
+                            };
+
+                            input_bus.publish(Msg::Ui(ui::Msg::Course(
+                                ui::course::Msg::NoteInputChanged(text_input.value()),
+                            )));
+                        })
+                        .finish(),
+                    button(bump)
+                        .child(text("Add note"))
+                        .on("click", move |_root, _vdom, _event| {
+                            add_bus.publish(Msg::Ui(ui::Msg::Course(ui::course::Msg::AddNote)));
+                        })
+                        .finish(),
+                ],
+                bump,
+            ))
+            .finish(),
+    )
+}
+
+/// Searches the document's page text and lists the matches, each jumping to its spot when clicked
+fn search_panel<'b>(
+    bump: &'b bumpalo::Bump,
+    state: &Model,
+    bus: &Bus<Msg>,
+    document_id: i32,
+) -> Option<dodrio::Node<'b>> {
+    use dodrio::builder::*;
+
+    // There is no text to search until the pages have loaded
+    state.entities.document_pages.get(&document_id)?;
+
+    let input_bus = bus.clone();
+    let search_bus = bus.clone();
+
+    Some(
+        div(bump)
+            .attr("class", "synchrotron__search")
+            .children(bumpalo::collections::Vec::from_iter_in(
+                vec![
+                    input(bump)
+                        .attr(
+                            "value",
+                            bumpalo::collections::String::from_str_in(
+                                state.ui.course_screen.text_search_value.as_str(),
+                                bump,
+                            )
+                            .into_bump_str(),
+                        )
+                        .attr("placeholder", "Search this course")
+                        .on("change", move |_root, _vdom, event| {
+                            let text_input = match event
+                                .target()
+                                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+                            {
+                                None => return,
+                                Some(text_input) => text_input,
+                            };
+
+                            input_bus.publish(Msg::Ui(ui::Msg::Course(
+                                ui::course::Msg::TextSearchInputChanged(text_input.value()),
+                            )));
+                        })
+                        .finish(),
+                    button(bump)
+                        .child(text("Search"))
+                        .on("click", move |_root, _vdom, _event| {
+                            search_bus
+                                .publish(Msg::Ui(ui::Msg::Course(ui::course::Msg::SearchText)));
+                        })
+                        .finish(),
+                    ul(bump)
+                        .attr("class", "synchrotron__search-results")
+                        .children(bumpalo::collections::Vec::from_iter_in(
+                            state.ui.course_screen.text_matches.iter().enumerate().map(
+                                |(index, text_match)| {
+                                    let select_bus = bus.clone();
+                                    li(bump)
+                                        .attr("class", "synchrotron__search-result")
+                                        .on("click", move |_root, _vdom, _event| {
+                                            select_bus.publish(Msg::Ui(ui::Msg::Course(
+                                                ui::course::Msg::SelectTextMatch(index),
+                                            )));
+                                        })
+                                        .child(text(
+                                            bumpalo::collections::String::from_str_in(
+                                                format!(
+                                                    "Page {}: {}",
+                                                    text_match.page_number, text_match.content
+                                                )
+                                                .as_str(),
+                                                bump,
+                                            )
+                                            .into_bump_str(),
+                                        ))
+                                        .finish()
+                                },
+                            ),
+                            bump,
+                        ))
+                        .finish(),
+                ],
+                bump,
+            ))
+            .finish(),
+    )
+}
@@ -1,50 +1,202 @@
 use iced_web::{dodrio, dodrio::bumpalo, Bus};
+use wasm_bindgen::JsCast;
 
 use crate::{
-    messages::{routing, Msg},
-    state::{Model, Route},
+    messages::{routing, ui, Msg},
+    state::{ui::courses_screen::DocumentSort, Model, Route},
 };
 
+const SORT_OPTIONS: &[(DocumentSort, &str, &str)] = &[
+    (DocumentSort::Relevance, "relevance", "Best match"),
+    (
+        DocumentSort::RecentlyUpdated,
+        "recently-updated",
+        "Recently updated",
+    ),
+    (DocumentSort::Title, "title", "Title"),
+];
+
+fn text_input<'b>(
+    bump: &'b bumpalo::Bump,
+    value: &str,
+    placeholder: &'static str,
+    bus: &Bus<Msg>,
+    to_msg: fn(String) -> ui::courses::Msg,
+) -> dodrio::Node<'b> {
+    use dodrio::builder::*;
+
+    let change_bus = bus.clone();
+    input::<'b>(bump)
+        .attr(
+            "value",
+            bumpalo::collections::String::from_str_in(value, bump).into_bump_str(),
+        )
+        .attr("placeholder", placeholder)
+        .on("change", move |_root, _vdom, event| {
+            let text_input = match event
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                None => return,
+                Some(text_input) => text_input,
+            };
+
+            change_bus.publish(Msg::Ui(ui::Msg::Courses(to_msg(text_input.value()))));
+        })
+        .finish()
+}
+
 pub fn render<'b, 's>(
     bump: &'b bumpalo::Bump,
     state: &'s Model,
     bus: &Bus<Msg>,
 ) -> dodrio::Node<'b> {
     use dodrio::builder::*;
-    if state.ui.courses_screen.loading {
-        return p(bump)
-            .child(text(
-                dodrio::bumpalo::collections::String::from_str_in("Loading...", bump)
-                    .into_bump_str(),
+
+    let screen = &state.ui.courses_screen;
+    let sort_bus = bus.clone();
+
+    let filters = div::<'b>(bump)
+        .children(bumpalo::collections::Vec::from_iter_in(
+            vec![
+                text_input(
+                    bump,
+                    screen.search_input_value.as_str(),
+                    "Search courses",
+                    bus,
+                    ui::courses::Msg::SearchInputChanged,
+                ),
+                text_input(
+                    bump,
+                    screen.language_input_value.as_str(),
+                    "Language",
+                    bus,
+                    ui::courses::Msg::LanguageInputChanged,
+                ),
+                text_input(
+                    bump,
+                    screen.level_input_value.as_str(),
+                    "Level",
+                    bus,
+                    ui::courses::Msg::LevelInputChanged,
+                ),
+                select::<'b>(bump)
+                    .children(bumpalo::collections::Vec::from_iter_in(
+                        SORT_OPTIONS.iter().map(|(sort, value, label)| {
+                            let mut sort_option = option::<'b>(bump).attr("value", *value);
+                            if *sort == screen.sort {
+                                sort_option = sort_option.attr("selected", "true");
+                            }
+                            sort_option.child(text(*label)).finish()
+                        }),
+                        bump,
+                    ))
+                    .on("change", move |_root, _vdom, event| {
+                        let select = match event
+                            .target()
+                            .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+                        {
+                            None => return,
+                            Some(select) => select,
+                        };
+
+                        if let Some((sort, _, _)) = SORT_OPTIONS
+                            .iter()
+                            .find(|(_, value, _)| *value == select.value().as_str())
+                        {
+                            sort_bus.publish(Msg::Ui(ui::Msg::Courses(
+                                ui::courses::Msg::SortChanged(*sort),
+                            )));
+                        }
+                    })
+                    .finish(),
+            ],
+            bump,
+        ))
+        .finish();
+
+    if screen.loading {
+        return div::<'b>(bump)
+            .children(bumpalo::collections::Vec::from_iter_in(
+                vec![
+                    filters,
+                    p(bump)
+                        .child(text(
+                            dodrio::bumpalo::collections::String::from_str_in("Loading...", bump)
+                                .into_bump_str(),
+                        ))
+                        .finish(),
+                ],
+                bump,
             ))
             .finish();
     }
 
-    let ids_titles = state
-        .entities
-        .documents_by_id
+    let documents = screen
+        .results
         .iter()
-        .map(|(&document_id, document)| (document_id, document.title.clone()));
+        .filter_map(|document_id| state.entities.documents_by_id.get(document_id));
+
+    let summary = p(bump)
+        .child(text(
+            bumpalo::format!(in bump, "{} courses", screen.total).into_bump_str(),
+        ))
+        .finish();
 
     div::<'b>(bump)
         .children(bumpalo::collections::Vec::from_iter_in(
-            ids_titles.map(|(document_id, title)| {
-                let button_bus = bus.clone();
+            vec![filters, summary]
+                .into_iter()
+                .chain(documents.map(|document| {
+                    let button_bus = bus.clone();
+                    let document_id = document.id;
+                    let details = [document.language.as_str(), document.level.as_str()]
+                        .iter()
+                        .filter(|s| !s.is_empty())
+                        .cloned()
+                        .collect::<Vec<&str>>()
+                        .join(" · ");
 
-                p(bump).child(
-                    button::<'b>(bump)
-                        .child(text(
-                            dodrio::bumpalo::collections::String::from_str_in(title.as_str(), bump)
-                                .into_bump_str(),
+                    div::<'b>(bump)
+                        .children(bumpalo::collections::Vec::from_iter_in(
+                            vec![
+                                button::<'b>(bump)
+                                    .child(text(
+                                        dodrio::bumpalo::collections::String::from_str_in(
+                                            document.title.as_str(),
+                                            bump,
+                                        )
+                                        .into_bump_str(),
+                                    ))
+                                    .on("click", move |_root, _vdom, event| {
+                                        button_bus.publish(Msg::Routing(routing::Msg::Push(
+                                            Route::Course(document_id, None),
+                                        )));
+                                    })
+                                    .finish(),
+                                span(bump)
+                                    .child(text(
+                                        dodrio::bumpalo::collections::String::from_str_in(
+                                            details.as_str(),
+                                            bump,
+                                        )
+                                        .into_bump_str(),
+                                    ))
+                                    .finish(),
+                                p(bump)
+                                    .child(text(
+                                        dodrio::bumpalo::collections::String::from_str_in(
+                                            document.description.as_str(),
+                                            bump,
+                                        )
+                                        .into_bump_str(),
+                                    ))
+                                    .finish(),
+                            ],
+                            bump,
                         ))
-                        .on("click", move |_root, _vdom, event| {
-                            button_bus.publish(Msg::Routing(routing::Msg::Push(
-                                Route::Course(document_id, None),
-                            )));
-                        })
-                        .finish(),
-                ).finish()
-            }),
+                        .finish()
+                })),
             bump,
         ))
         .finish()
@@ -0,0 +1,120 @@
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use serde::Deserialize;
+
+/// Key ID given to the key loaded from `JWT_SECRET`
+const DEFAULT_KID: &str = "default";
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "algorithm")]
+/// Key as listed in the keys file. Keys without a private key can only verify tokens,
+/// which lets a rotated-out key keep accepting the tokens it signed until they expire.
+enum KeyConfig {
+    HS256 {
+        kid: String,
+        secret: String,
+    },
+    RS256 {
+        kid: String,
+        private_key_path: Option<PathBuf>,
+        public_key_path: PathBuf,
+    },
+    EdDSA {
+        kid: String,
+        private_key_path: Option<PathBuf>,
+        public_key_path: PathBuf,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct KeysConfig {
+    // Key used to sign new tokens
+    active_kid: String,
+    keys: Vec<KeyConfig>,
+}
+
+pub struct Key {
+    pub algorithm: Algorithm,
+    // Missing for keys that can only verify tokens
+    pub encoding: Option<EncodingKey>,
+    pub decoding: DecodingKey,
+}
+
+/// Access token keys shared by the users service, which signs tokens, and the services that
+/// verify them
+pub struct JwtKeySet {
+    pub active_kid: String,
+    pub keys: HashMap<String, Key>,
+}
+
+impl JwtKeySet {
+    /// Loads the keys listed in the JSON file at `JWT_KEYS_FILE`, falling back to a single
+    /// HS256 key read from `JWT_SECRET`. Private keys are only read when `signing` is set, so
+    /// that a service which just verifies tokens needs nothing but the public keys.
+    pub fn from_env(signing: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = if let Ok(path) = env::var("JWT_KEYS_FILE") {
+            serde_json::from_str::<KeysConfig>(&fs::read_to_string(path)?)?
+        } else if let Ok(secret) = env::var("JWT_SECRET") {
+            KeysConfig {
+                active_kid: DEFAULT_KID.to_owned(),
+                keys: vec![KeyConfig::HS256 {
+                    kid: DEFAULT_KID.to_owned(),
+                    secret,
+                }],
+            }
+        } else {
+            return Err("Either JWT_KEYS_FILE or JWT_SECRET must be set".into());
+        };
+
+        let mut keys = HashMap::new();
+        for key in config.keys {
+            let (kid, key) = match key {
+                KeyConfig::HS256 { kid, secret } => (
+                    kid,
+                    Key {
+                        algorithm: Algorithm::HS256,
+                        encoding: Some(EncodingKey::from_secret(secret.as_bytes())),
+                        decoding: DecodingKey::from_secret(secret.as_bytes()),
+                    },
+                ),
+                KeyConfig::RS256 {
+                    kid,
+                    private_key_path,
+                    public_key_path,
+                } => (
+                    kid,
+                    Key {
+                        algorithm: Algorithm::RS256,
+                        encoding: match private_key_path.filter(|_| signing) {
+                            Some(path) => Some(EncodingKey::from_rsa_pem(&fs::read(path)?)?),
+                            None => None,
+                        },
+                        decoding: DecodingKey::from_rsa_pem(&fs::read(public_key_path)?)?,
+                    },
+                ),
+                KeyConfig::EdDSA {
+                    kid,
+                    private_key_path,
+                    public_key_path,
+                } => (
+                    kid,
+                    Key {
+                        algorithm: Algorithm::EdDSA,
+                        encoding: match private_key_path.filter(|_| signing) {
+                            Some(path) => Some(EncodingKey::from_ed_pem(&fs::read(path)?)?),
+                            None => None,
+                        },
+                        decoding: DecodingKey::from_ed_pem(&fs::read(public_key_path)?)?,
+                    },
+                ),
+            };
+            keys.insert(kid, key);
+        }
+
+        Ok(Self {
+            active_kid: config.active_kid,
+            keys,
+        })
+    }
+}
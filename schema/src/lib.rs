@@ -2,6 +2,18 @@ pub use gen::users;
 pub use gen::courses;
 pub use gen::shared;
 
+pub mod jwt_keys;
+
+/// gRPC metadata key carrying the reason an `Unauthenticated` status was returned
+pub const AUTH_ERROR_METADATA_KEY: &str = "x-auth-error";
+
+/// gRPC metadata key carrying how many seconds to wait after a `ResourceExhausted` status
+pub const RETRY_AFTER_METADATA_KEY: &str = "x-retry-after";
+
+/// Key prefix that the media of private documents must be stored under, since only keys under it
+/// are kept from being served without a signature
+pub const PRIVATE_MEDIA_PREFIX: &str = "private/";
+
 mod gen {
     pub mod users;
     pub mod courses;
@@ -0,0 +1,8 @@
+CREATE TABLE refresh_tokens (
+  id SERIAL PRIMARY KEY,
+  token_hash VARCHAR (64) UNIQUE NOT NULL,
+  user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  created_at TIMESTAMPTZ NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+  revoked_at TIMESTAMPTZ
+);
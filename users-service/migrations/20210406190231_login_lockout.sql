@@ -0,0 +1,3 @@
+-- Consecutive failed logins, and when the account may be tried again after too many of them
+ALTER TABLE users ADD COLUMN failed_logins INT NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN locked_until TIMESTAMPTZ;
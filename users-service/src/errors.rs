@@ -1,9 +1,20 @@
 use std::{error, fmt};
 
+use jsonwebtoken::errors::ErrorKind;
+use schema::{AUTH_ERROR_METADATA_KEY, RETRY_AFTER_METADATA_KEY};
+use tonic::metadata::MetadataValue;
+
 #[derive(Debug)]
 pub enum UsersServiceError {
     Database(sqlx::Error),
     Decryption(bcrypt::BcryptError),
+    Token(jsonwebtoken::errors::Error),
+    TokenExpired,
+    InvalidToken(jsonwebtoken::errors::Error),
+    InvalidRefreshToken,
+    UnknownUser(i32),
+    // Too many logins failed, with the number of seconds until the account can be tried again
+    AccountLocked(i64),
 }
 
 impl fmt::Display for UsersServiceError {
@@ -11,6 +22,16 @@ impl fmt::Display for UsersServiceError {
         match *self {
             Self::Database(ref err) => write!(f, "Database error: {}", err),
             Self::Decryption(ref err) => write!(f, "Decryption error: {}", err),
+            Self::Token(ref err) => write!(f, "Token error: {}", err),
+            Self::TokenExpired => write!(f, "Access token has expired"),
+            Self::InvalidToken(ref err) => write!(f, "Invalid access token: {}", err),
+            Self::InvalidRefreshToken => write!(f, "Refresh token is invalid, expired or revoked"),
+            Self::UnknownUser(id) => write!(f, "Token was issued for unknown user {}", id),
+            Self::AccountLocked(seconds) => write!(
+                f,
+                "Too many failed logins, try again in {} seconds",
+                seconds
+            ),
         }
     }
 }
@@ -20,6 +41,34 @@ impl error::Error for UsersServiceError {
         match *self {
             Self::Database(ref err) => Some(err),
             Self::Decryption(ref err) => Some(err),
+            Self::Token(ref err) => Some(err),
+            Self::TokenExpired => None,
+            Self::InvalidToken(ref err) => Some(err),
+            Self::InvalidRefreshToken => None,
+            Self::UnknownUser(_) => None,
+            Self::AccountLocked(_) => None,
+        }
+    }
+}
+
+impl UsersServiceError {
+    /// Classifies a failed access token verification
+    pub fn from_verification(err: jsonwebtoken::errors::Error) -> UsersServiceError {
+        match err.kind() {
+            ErrorKind::ExpiredSignature => UsersServiceError::TokenExpired,
+            _ => UsersServiceError::InvalidToken(err),
+        }
+    }
+
+    /// Reason code sent with `Unauthenticated` statuses so clients can tell why
+    /// the credentials were rejected
+    fn auth_error_code(&self) -> Option<&'static str> {
+        match *self {
+            Self::TokenExpired => Some("TOKEN_EXPIRED"),
+            Self::InvalidToken(_) => Some("INVALID_TOKEN"),
+            Self::InvalidRefreshToken => Some("INVALID_REFRESH_TOKEN"),
+            Self::UnknownUser(_) => Some("UNKNOWN_USER"),
+            _ => None,
         }
     }
 }
@@ -36,8 +85,36 @@ impl From<bcrypt::BcryptError> for UsersServiceError {
     }
 }
 
+impl From<jsonwebtoken::errors::Error> for UsersServiceError {
+    fn from(err: jsonwebtoken::errors::Error) -> UsersServiceError {
+        UsersServiceError::Token(err)
+    }
+}
+
 impl From<UsersServiceError> for tonic::Status {
     fn from(err: UsersServiceError) -> tonic::Status {
-        tonic::Status::unknown(err.to_string())
+        if let UsersServiceError::AccountLocked(seconds) = err {
+            let mut status = tonic::Status::resource_exhausted(err.to_string());
+            status
+                .metadata_mut()
+                .insert(RETRY_AFTER_METADATA_KEY, MetadataValue::from(seconds));
+            return status;
+        }
+
+        match err.auth_error_code() {
+            Some(code) => {
+                let mut status = tonic::Status::unauthenticated(err.to_string());
+                status
+                    .metadata_mut()
+                    .insert(AUTH_ERROR_METADATA_KEY, MetadataValue::from_static(code));
+                status
+            }
+            None => match err {
+                UsersServiceError::Database(sqlx::Error::RowNotFound) => {
+                    tonic::Status::not_found(err.to_string())
+                }
+                err => tonic::Status::unknown(err.to_string()),
+            },
+        }
     }
 }
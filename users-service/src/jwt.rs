@@ -1,6 +1,7 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, errors::ErrorKind, Header, Validation};
+use schema::jwt_keys::JwtKeySet;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -12,46 +13,67 @@ pub struct LoginAttempt {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub user_id: i32,
-    pub exp: i32,
+    // Role at the time the token was issued, trusted by the other services
+    pub role: i32,
+    pub exp: i64,
 }
 
-struct SecretKey {
-    key: &'static str,
+pub struct JwtKeys {
+    keys: JwtKeySet,
+    access_token_seconds: i64,
 }
 
-impl SecretKey {
-    fn get_secret_key() -> SecretKey {
-        SecretKey { key: "secret" }
+impl JwtKeys {
+    /// Loads the keys listed in the JSON file at `JWT_KEYS_FILE`, falling back to a single
+    /// HS256 key read from `JWT_SECRET`
+    pub fn from_env(access_token_minutes: i64) -> Result<Self, Box<dyn std::error::Error>> {
+        let keys = JwtKeySet::from_env(true)?;
+
+        match keys.keys.get(&keys.active_kid) {
+            Some(key) if key.encoding.is_some() => Ok(Self {
+                keys,
+                access_token_seconds: access_token_minutes * 60,
+            }),
+            Some(_) => Err(format!("Active key {} has no private key", keys.active_kid).into()),
+            None => Err(format!("Active key {} is not configured", keys.active_kid).into()),
+        }
     }
-}
 
-pub fn encode_jwt(user_id: i32, exp_day: i32) -> Result<String, jsonwebtoken::errors::Error> {
-    let exp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs() as i32
-        + exp_day * 24 * 60 * 60;
-
-    let jwt_secret_key = SecretKey::get_secret_key();
-    let my_claims = Claims { user_id, exp };
-    let token = encode(
-        &Header::default(),
-        &my_claims,
-        &EncodingKey::from_secret(jwt_secret_key.key.as_ref()),
-    )?;
-
-    Ok(token)
-}
+    /// Lifetime of newly issued access tokens
+    pub fn access_token_seconds(&self) -> i64 {
+        self.access_token_seconds
+    }
+
+    /// Issues an access token for the user, signed with the active key
+    pub fn encode_jwt(
+        &self,
+        user_id: i32,
+        role: i32,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs() as i64
+            + self.access_token_seconds;
 
-pub fn verify_jwt(
-    token: String,
-) -> Result<jsonwebtoken::TokenData<Claims>, jsonwebtoken::errors::Error> {
-    let jwt_secret_key = SecretKey::get_secret_key();
-    let validation = Validation::default();
-
-    decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(jwt_secret_key.key.as_ref()),
-        &validation,
-    )
+        let key = &self.keys.keys[&self.keys.active_kid];
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(self.keys.active_kid.clone());
+        let my_claims = Claims { user_id, role, exp };
+
+        encode(&header, &my_claims, key.encoding.as_ref().unwrap())
+    }
+
+    /// Verifies a token against the key named by its `kid` header
+    pub fn verify_jwt(
+        &self,
+        token: &str,
+    ) -> Result<jsonwebtoken::TokenData<Claims>, jsonwebtoken::errors::Error> {
+        let key = decode_header(token)?
+            .kid
+            .and_then(|kid| self.keys.keys.get(&kid))
+            .ok_or_else(|| jsonwebtoken::errors::Error::from(ErrorKind::InvalidToken))?;
+
+        decode::<Claims>(token, &key.decoding, &Validation::new(key.algorithm))
+    }
 }
@@ -0,0 +1,21 @@
+use chrono::Duration;
+
+/// Consecutive failed logins allowed before an account is locked
+const MAX_FAILED_LOGINS: i32 = 5;
+
+/// Lockout after the first run of failed logins, doubled with every further failure
+const BASE_LOCKOUT_SECONDS: i64 = 30;
+
+/// Longest an account stays locked, however many logins have failed
+const MAX_LOCKOUT_SECONDS: i64 = 60 * 60;
+
+/// How long to lock an account for after its latest failed login, if at all
+pub fn duration(failed_logins: i32) -> Option<Duration> {
+    if failed_logins < MAX_FAILED_LOGINS {
+        return None;
+    }
+    let doublings = (failed_logins - MAX_FAILED_LOGINS).min(16) as u32;
+    Some(Duration::seconds(
+        (BASE_LOCKOUT_SECONDS << doublings).min(MAX_LOCKOUT_SECONDS),
+    ))
+}
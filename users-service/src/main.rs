@@ -1,7 +1,7 @@
 use std::env;
 
 use bcrypt::{hash, verify};
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use sqlx::postgres::{PgPoolOptions, Postgres};
 use tokio::sync::mpsc;
 use tonic::{transport::Server, Request, Response, Status};
@@ -12,29 +12,91 @@ use schema::{
         users_server::{Users, UsersServer},
         AuthenticateRequest, AuthenticateResponse, CreateUserRequest, CreateUserResponse,
         GetAllUsersRequest, GetTokenRequest, GetTokenResponse, GetUsersByIdsRequest,
-        GetUsersByIdsResponse, UpdateUserRoleRequest, UpdateUserRoleResponse,
+        GetUsersByIdsResponse, RefreshTokenRequest, RevokeRefreshTokenRequest,
+        RevokeRefreshTokenResponse, UpdateUserRoleRequest, UpdateUserRoleResponse,
     },
 };
 
 mod errors;
 mod jwt;
+mod lockout;
+mod refresh;
 
 use errors::UsersServiceError;
+use jwt::{Claims, JwtKeys};
+
+/// Work factor of the bcrypt hashes that passwords are stored as
+const PASSWORD_COST: u32 = 10;
 
-#[derive(Debug)]
 pub struct UsersService<T>
 where
     for<'a> &'a T: sqlx::Executor<'a, Database = Postgres>,
 {
     executor: T,
+    keys: JwtKeys,
+    refresh_token_days: i64,
+    // Checked in place of a password when the username doesn't exist
+    dummy_hash: String,
 }
 
 impl<T> UsersService<T>
 where
     for<'a> &'a T: sqlx::Executor<'a, Database = Postgres>,
 {
-    pub fn new(executor: T) -> Self {
-        Self { executor }
+    pub fn new(executor: T, keys: JwtKeys, refresh_token_days: i64) -> Self {
+        Self {
+            executor,
+            keys,
+            refresh_token_days,
+            dummy_hash: hash("dummy password", PASSWORD_COST).unwrap(),
+        }
+    }
+
+    /// Claims of the access token forwarded in the request's `authorization` metadata
+    fn active_user<R>(&self, request: &Request<R>) -> Result<Option<Claims>, UsersServiceError> {
+        match request
+            .metadata()
+            .get("authorization")
+            .and_then(|token| token.to_str().ok())
+        {
+            Some(token) => Ok(Some(
+                self.keys
+                    .verify_jwt(token)
+                    .map_err(UsersServiceError::from_verification)?
+                    .claims,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Issues a fresh access token and refresh token pair for the user
+    async fn issue_tokens(&self, user: User) -> Result<GetTokenResponse, Status> {
+        let refresh_token = refresh::generate();
+        sqlx::query!(
+            "INSERT INTO refresh_tokens (
+                token_hash,
+                user_id,
+                created_at,
+                expires_at
+            ) VALUES ($1, $2, $3, $4);",
+            refresh::hash(&refresh_token),
+            user.id,
+            Utc::now(),
+            Utc::now() + Duration::days(self.refresh_token_days)
+        )
+        .execute(&self.executor)
+        .await
+        .map_err(UsersServiceError::from)?;
+
+        Ok(GetTokenResponse {
+            token: self
+                .keys
+                .encode_jwt(user.id, user.role)
+                .map_err(UsersServiceError::from)?,
+            refresh_token,
+            expires_in: self.keys.access_token_seconds(),
+            user: Some(user),
+        })
     }
 }
 
@@ -59,7 +121,7 @@ where
                 user_role
             ) VALUES ($1, $2, $3, $4, $5) RETURNING *;",
             req.username,
-            hash(&req.password, 10).unwrap(),
+            hash(&req.password, PASSWORD_COST).unwrap(),
             Utc::now(),
             Utc::now(),
             UserRole::Standard as i32
@@ -82,24 +144,129 @@ where
         request: Request<GetTokenRequest>,
     ) -> Result<Response<GetTokenResponse>, Status> {
         let req = request.into_inner();
-        let user = sqlx::query!("SELECT * FROM users WHERE username=$1", req.username)
-            .fetch_one(&self.executor)
+        let user = match sqlx::query!("SELECT * FROM users WHERE username=$1", req.username)
+            .fetch_optional(&self.executor)
             .await
-            .map_err(UsersServiceError::from)?;
+            .map_err(UsersServiceError::from)?
+        {
+            Some(user) => user,
+            None => {
+                // Runs bcrypt all the same, so that unknown usernames take as long to refuse as
+                // wrong passwords and can't be told apart by timing
+                let _ = verify(req.password, self.dummy_hash.as_str());
+                return Err(Status::permission_denied("Invalid login"));
+            }
+        };
+
+        // A locked account is refused before bcrypt runs, so guessing stays cheap to turn away
+        if let Some(locked_until) = user.locked_until {
+            let remaining = locked_until - Utc::now();
+            if remaining > Duration::zero() {
+                return Err(UsersServiceError::AccountLocked(remaining.num_seconds() + 1).into());
+            }
+        }
+
         if verify(req.password, user.password.as_str()).map_err(UsersServiceError::from)? {
-            Ok(Response::new(GetTokenResponse {
-                token: jwt::encode_jwt(user.id, 30).unwrap(),
-                user: Some(User {
+            if user.failed_logins > 0 {
+                sqlx::query!(
+                    "UPDATE users SET failed_logins=0, locked_until=NULL WHERE id=$1;",
+                    user.id
+                )
+                .execute(&self.executor)
+                .await
+                .map_err(UsersServiceError::from)?;
+            }
+
+            Ok(Response::new(
+                self.issue_tokens(User {
                     id: user.id,
                     username: user.username,
-                    role: user.user_role
+                    role: user.user_role,
                 })
-            }))
+                .await?,
+            ))
         } else {
+            let failed = sqlx::query!(
+                "UPDATE users SET failed_logins=failed_logins + 1 WHERE id=$1
+                RETURNING failed_logins;",
+                user.id
+            )
+            .fetch_one(&self.executor)
+            .await
+            .map_err(UsersServiceError::from)?;
+
+            if let Some(lockout) = lockout::duration(failed.failed_logins) {
+                sqlx::query!(
+                    "UPDATE users SET locked_until=$1 WHERE id=$2;",
+                    Utc::now() + lockout,
+                    user.id
+                )
+                .execute(&self.executor)
+                .await
+                .map_err(UsersServiceError::from)?;
+                log::warn!(
+                    "Locked user {} for {} seconds after {} failed logins",
+                    user.id,
+                    lockout.num_seconds(),
+                    failed.failed_logins
+                );
+            }
+
             Err(Status::permission_denied("Invalid login"))
         }
     }
 
+    async fn refresh_token(
+        &self,
+        request: Request<RefreshTokenRequest>,
+    ) -> Result<Response<GetTokenResponse>, Status> {
+        let req = request.into_inner();
+        let revoked = sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at=$1
+            WHERE token_hash=$2 AND revoked_at IS NULL AND expires_at > $1
+            RETURNING user_id;",
+            Utc::now(),
+            refresh::hash(&req.refresh_token)
+        )
+        .fetch_optional(&self.executor)
+        .await
+        .map_err(UsersServiceError::from)?
+        .ok_or(UsersServiceError::InvalidRefreshToken)?;
+
+        let user = sqlx::query!("SELECT * FROM users WHERE id=$1;", revoked.user_id)
+            .fetch_one(&self.executor)
+            .await
+            .map_err(UsersServiceError::from)?;
+
+        Ok(Response::new(
+            self.issue_tokens(User {
+                id: user.id,
+                username: user.username,
+                role: user.user_role,
+            })
+            .await?,
+        ))
+    }
+
+    async fn revoke_refresh_token(
+        &self,
+        request: Request<RevokeRefreshTokenRequest>,
+    ) -> Result<Response<RevokeRefreshTokenResponse>, Status> {
+        let req = request.into_inner();
+        let result = sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at=$1 WHERE token_hash=$2 AND revoked_at IS NULL;",
+            Utc::now(),
+            refresh::hash(&req.refresh_token)
+        )
+        .execute(&self.executor)
+        .await
+        .map_err(UsersServiceError::from)?;
+
+        Ok(Response::new(RevokeRefreshTokenResponse {
+            success: result.rows_affected() > 0,
+        }))
+    }
+
     async fn get_users_by_ids(
         &self,
         request: Request<GetUsersByIdsRequest>,
@@ -155,12 +322,16 @@ where
         &self,
         request: Request<AuthenticateRequest>,
     ) -> Result<Response<AuthenticateResponse>, Status> {
-        let result = jwt::verify_jwt(request.into_inner().token).unwrap();
+        let result = self
+            .keys
+            .verify_jwt(&request.into_inner().token)
+            .map_err(UsersServiceError::from_verification)?;
 
         let user = sqlx::query!("SELECT * FROM users WHERE id=$1;", result.claims.user_id)
-            .fetch_one(&self.executor)
+            .fetch_optional(&self.executor)
             .await
-            .map_err(UsersServiceError::from)?;
+            .map_err(UsersServiceError::from)?
+            .ok_or(UsersServiceError::UnknownUser(result.claims.user_id))?;
 
         log::info!(
             "Verified request from user {} with id {}",
@@ -181,8 +352,9 @@ where
         &self,
         request: Request<UpdateUserRoleRequest>,
     ) -> Result<Response<UpdateUserRoleResponse>, Status> {
+        let active_user = self.active_user(&request)?;
         let message = request.into_inner();
-        if message.active_user.map(|u| u.role).unwrap_or(0) == UserRole::Administrator as i32 {
+        if active_user.map(|u| u.role).unwrap_or(0) == UserRole::Administrator as i32 {
             sqlx::query!(
                 "UPDATE users SET user_role=$1 WHERE id=$2;",
                 message.new_role,
@@ -210,7 +382,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .connect(&env::var("DATABASE_URL")?)
         .await?;
 
-    let service = UsersService::new(pool);
+    let access_token_minutes = env::var("ACCESS_TOKEN_MINUTES")
+        .ok()
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(15);
+    let refresh_token_days = env::var("REFRESH_TOKEN_DAYS")
+        .ok()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(30);
+    let keys = JwtKeys::from_env(access_token_minutes)?;
+
+    let service = UsersService::new(pool, keys, refresh_token_days);
 
     Server::builder()
         .add_service(UsersServer::new(service))
@@ -0,0 +1,16 @@
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// Generates an opaque refresh token to hand to the client
+pub fn generate() -> String {
+    to_hex(&rand::thread_rng().gen::<[u8; 32]>())
+}
+
+/// Hash under which a refresh token is stored, so a leaked table cannot be replayed
+pub fn hash(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}